@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of addresses an `AddressBook` stores
+pub const MAX_ADDRESSES: usize = 1000;
+
+/// The maximum number of addresses sent in reply to a single `GetAddr` request
+pub const MAX_ADDR_REPLY: usize = 250;
+
+/// The number of consecutive failed connection attempts after which an address is forgotten
+pub const MAX_FAILURES: u32 = 3;
+
+/// Gets the current time in seconds since the Unix epoch
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+}
+
+/// What an `AddressBook` knows about a single peer address
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct AddressEntry {
+    /// The time, in seconds, the peer was last known to be reachable, or `0` if never
+    pub last_seen: u64,
+
+    /// The number of consecutive failed connection attempts
+    pub failures: u32,
+
+    /// The hex encoded Ed25519 public key the peer must authenticate with, if it was configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+}
+
+/// Splits a peer written as `public_key@address:port`, as in the seeds of a `ChainSpec`,
+/// into its `address:port` and the identity it must authenticate with
+///
+/// # Returns
+/// - `(&str, Option<&str>)` - The `address:port` of the peer, and its hex encoded Ed25519
+///   public key if one was given
+pub fn parse_peer(peer: &str) -> (&str, Option<&str>) {
+    match peer.split_once('@') {
+        Some((identity, address)) => (address, Some(identity)),
+        None => (peer, None),
+    }
+}
+
+/// An `AddressBook` stores the addresses of the peers of a node, learned from seeds and
+/// from `Addr` messages, and persists them so a restarted node does not need its seeds
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AddressBook {
+    /// The entry of each known `address:port`
+    pub entries: BTreeMap<String, AddressEntry>,
+
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+impl AddressBook {
+    /// Constructs a new, empty `AddressBook` which is never persisted
+    pub fn new() -> Self {
+        AddressBook::default()
+    }
+
+    /// Loads an `AddressBook` from a JSON file, starting empty if the file does not exist
+    ///
+    /// # Parameters
+    /// - `path` - The path the `AddressBook` is loaded from and saved to
+    ///
+    /// # Returns
+    /// - `Result<Self, Error>` - The `AddressBook`, or an `Error` if the file could not be read or parsed
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut book = match fs::read(path) {
+            Ok(contents) => serde_json::from_slice(&contents).map_err(|e| Error::new(ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => AddressBook::new(),
+            Err(e) => return Err(e),
+        };
+        book.path = Some(path.to_path_buf());
+        Ok(book)
+    }
+
+    /// Saves this `AddressBook` to the file it was loaded from, if any
+    pub fn save(&self) -> Result<(), Error> {
+        match &self.path {
+            Some(path) => fs::write(path, serde_json::to_vec_pretty(self)?),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds an address, keeping the most recent last-seen time if it is already known
+    ///
+    /// # Parameters
+    /// - `address` - The `address:port` of the peer
+    /// - `last_seen` - When the peer was last known to be reachable, in seconds
+    /// - `now` - The current time, in seconds, which `last_seen` is capped at
+    pub fn add(&mut self, address: &str, last_seen: u64, now: u64) {
+        let last_seen = last_seen.min(now);
+        if let Some(entry) = self.entries.get_mut(address) {
+            entry.last_seen = entry.last_seen.max(last_seen);
+            return;
+        }
+
+        if self.entries.len() >= MAX_ADDRESSES {
+            let oldest = self.entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_seen)
+                .map(|(address, entry)| (address.clone(), entry.last_seen));
+            match oldest {
+                Some((oldest, oldest_seen)) if oldest_seen < last_seen => {
+                    self.entries.remove(&oldest);
+                }
+                _ => return,
+            }
+        }
+        self.entries.insert(address.to_string(), AddressEntry { last_seen, failures: 0, identity: None });
+    }
+
+    /// Adds a configured peer, such as a seed, written as `address:port` or as
+    /// `public_key@address:port` to pin the identity it must authenticate with
+    pub fn add_peer(&mut self, peer: &str) {
+        let (address, identity) = parse_peer(peer);
+        self.add(address, 0, 0);
+        if let (Some(entry), Some(identity)) = (self.entries.get_mut(address), identity) {
+            entry.identity = Some(identity.to_lowercase());
+        }
+    }
+
+    /// Gets the identity a peer must authenticate with, if one was pinned by `add_peer`
+    pub fn identity(&self, address: &str) -> Option<&str> {
+        self.entries.get(address).and_then(|entry| entry.identity.as_deref())
+    }
+
+    /// Adds the addresses received in an `Addr` message, ignoring any which are not `ip:port`
+    pub fn merge(&mut self, addresses: Vec<(String, u64)>, now: u64) {
+        for (address, last_seen) in addresses.into_iter().take(MAX_ADDR_REPLY) {
+            if address.parse::<SocketAddr>().is_ok() {
+                self.add(&address, last_seen, now);
+            }
+        }
+    }
+
+    /// Records a successful connection to a peer
+    pub fn mark_seen(&mut self, address: &str, now: u64) {
+        self.add(address, now, now);
+        if let Some(entry) = self.entries.get_mut(address) {
+            entry.failures = 0;
+        }
+    }
+
+    /// Records a failed connection attempt, forgetting the address after `MAX_FAILURES`
+    /// unless its identity is pinned, so it cannot be learned again without the pin
+    pub fn mark_failed(&mut self, address: &str) {
+        if let Some(entry) = self.entries.get_mut(address) {
+            entry.failures += 1;
+            if entry.failures >= MAX_FAILURES && entry.identity.is_none() {
+                self.entries.remove(address);
+            }
+        }
+    }
+
+    /// Gets every known address, most recently seen first
+    pub fn candidates(&self) -> Vec<String> {
+        let mut entries: Vec<(&String, &AddressEntry)> = self.entries.iter().collect();
+        entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.last_seen));
+        entries.into_iter().map(|(address, _)| address.clone()).collect()
+    }
+
+    /// Gets the addresses shared with peers in an `Addr` message: those which were
+    /// reachable at some point, most recently seen first
+    pub fn recent(&self, limit: usize) -> Vec<(String, u64)> {
+        self.candidates()
+            .into_iter()
+            .map(|address| {
+                let last_seen = self.entries[&address].last_seen;
+                (address, last_seen)
+            })
+            .filter(|(_, last_seen)| *last_seen > 0)
+            .take(limit)
+            .collect()
+    }
+
+    /// The number of known addresses
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no address is known
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -2,7 +2,64 @@ use sha2::{Digest, Sha256};
 use std::time::{SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
 use crate::arc_string::ArcString;
+use crate::merkle::merkle_root;
 use crate::transaction::Transaction;
+use crate::wire;
+
+/// A `BlockHeader` holds every field of a `Block` except its `Transaction`s, which are
+/// committed to by the `merkle_root`
+///
+/// Headers are small enough to be exchanged in bulk, which lets a node validate the
+/// linkage and [Proof of Work (PoW)](https://www.investopedia.com/terms/p/proof-work.asp)
+/// of a chain before downloading any `Block` bodies
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct BlockHeader {
+    /// The position of the `Block` in a blockchain
+    pub index: u32,
+
+    /// The time, in seconds, the `Block` was created
+    pub timestamp: u64,
+
+    /// The hash of the previous `Block`, or `None` if there isn't any
+    pub previous_hash: Option<ArcString>,
+
+    /// The Merkle root of the `Block`'s `Transaction` and coinbase payouts
+    pub merkle_root: String,
+
+    /// The root of the `ContractState` after the `Block`'s `Transaction` was applied
+    pub state_root: String,
+
+    /// The `nonce` found by [Proof of Work (PoW)](https://www.investopedia.com/terms/p/proof-work.asp) mining
+    pub nonce: u32,
+
+    /// The hex-encoded public key of the validator who proposed the `Block`, if any
+    pub validator: Option<String>,
+
+    /// The validator's signature of the `Block`'s `hash`
+    pub signature: Vec<u8>,
+
+    /// The hash of the `Block`
+    pub hash: String,
+}
+impl BlockHeader {
+    /// Generates the [SHA-256](https://securiti.ai/glossary/secure-hash-algorithm-sha-256-bit/)
+    /// hash of the `Block` this header belongs to
+    ///
+    /// # Returns
+    /// - `String` - A `String` representation containing the hash of the `Block`
+    pub fn calculate_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{:?}{:?}{:?}{:?}{:?}{:?}{:?}",
+                              self.index,
+                              self.timestamp,
+                              self.merkle_root,
+                              self.state_root,
+                              self.previous_hash, 
+                              self.nonce,
+                              self.validator));
+        format!("{:x}", hasher.finalize())
+    }
+}
 
 /// A `Block` stores a transaction, a digital operation
 /// that represents the transfer or exchange of information, assets,
@@ -38,6 +95,23 @@ pub struct Block {
     /// that is adjusted until
     /// a valid hash is found that meets the difficulty target of the `Blockchain`
     pub nonce: u32,
+
+    /// The reward payouts for producing this `Block`, paid to the `Miner`(s) who mined it
+    #[serde(default)]
+    pub coinbase: Vec<Transaction>,
+
+    /// The hex-encoded public key of the validator who proposed this `Block`, or `None`
+    /// if it was produced by [Proof of Work (PoW)](https://www.investopedia.com/terms/p/proof-work.asp) mining
+    #[serde(default)]
+    pub validator: Option<String>,
+
+    /// The validator's Ed25519 signature of this `Block`'s `hash`, or empty if there isn't any
+    #[serde(default)]
+    pub signature: Vec<u8>,
+
+    /// The root of the `ContractState` after this `Block`'s `Transaction` was applied
+    #[serde(default)]
+    pub state_root: String,
 }
 impl Block {
     /// Constructs a new `Block` with the given index, data, and
@@ -47,7 +121,7 @@ impl Block {
     /// - `index` - The position the block should be in a blockchain
     /// - `data` - The information that the `Block` should store
     /// - `previous_hash` - The reference/hash to the previous block in a blockchain,
-    /// or `None` if there isn't one
+    ///   or `None` if there isn't one
     ///
     /// # Returns
     /// - `Self` - A newly constructed current `Block` instance that contains an
@@ -65,6 +139,10 @@ impl Block {
             previous_hash,
             hash: String::new(),
             nonce: 0,
+            coinbase: Vec::new(),
+            validator: None,
+            signature: Vec::new(),
+            state_root: String::new(),
         }
     }
 
@@ -77,13 +155,46 @@ impl Block {
     /// # Returns
     /// - `String` - A `String` representation containing the `hash` of the current `Block` instance
     pub fn calculate_hash(block: &Block) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(format!("{:?}{:?}{:?}{:?}{:?}", 
-                              block.index,
-                              block.timestamp,
-                              block.transaction,
-                              block.previous_hash, 
-                              block.nonce));
-        format!("{:x}", hasher.finalize())
+        block.header().calculate_hash()
+    }
+
+    /// Gets the hashes of this `Block`'s `Transaction` followed by its coinbase payouts,
+    /// which are the leaves of its Merkle tree
+    pub fn transaction_hashes(&self) -> Vec<String> {
+        std::iter::once(&self.transaction)
+            .chain(&self.coinbase)
+            .map(|transaction| transaction.calculate_hash())
+            .collect()
     }
-}
\ No newline at end of file
+
+    /// Gets the number of `Transaction`s of this `Block`, counting its coinbase payouts
+    pub fn transaction_count(&self) -> usize {
+        1 + self.coinbase.len()
+    }
+
+    /// Gets the size in bytes of this `Block` in the binary wire encoding, or `usize::MAX` if
+    /// it cannot be encoded, so that it is over every size limit
+    pub fn size(&self) -> usize {
+        wire::to_bytes(self).map_or(usize::MAX, |bytes| bytes.len())
+    }
+
+    /// Computes the Merkle root of this `Block`'s `Transaction` followed by its coinbase payouts
+    pub fn merkle_root(&self) -> String {
+        merkle_root(&self.transaction_hashes())
+    }
+
+    /// Gets the `BlockHeader` of this `Block`
+    pub fn header(&self) -> BlockHeader {
+        BlockHeader {
+            index: self.index,
+            timestamp: self.timestamp,
+            previous_hash: self.previous_hash.clone(),
+            merkle_root: self.merkle_root(),
+            state_root: self.state_root.clone(),
+            nonce: self.nonce,
+            validator: self.validator.clone(),
+            signature: self.signature.clone(),
+            hash: self.hash.clone(),
+        }
+    }
+}
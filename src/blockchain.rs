@@ -1,10 +1,77 @@
+use std::fmt;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use ring::rand::SystemRandom;
 use ring::signature::{Ed25519KeyPair, KeyPair};
+use tokio::sync::{broadcast, RwLock};
 use crate::arc_string::ArcString;
-use crate::transaction::Transaction;
-use crate::block::Block;
+use crate::block::{Block, BlockHeader};
+use crate::chain_spec::ChainSpec;
+use crate::consensus::Consensus;
+use crate::contract::ContractState;
+use crate::events::{Event, EVENT_CAPACITY};
+use crate::ledger::Ledger;
+use crate::mempool::Mempool;
+use crate::merkle::MerkleProof;
 use crate::miner::Miner;
+use crate::nft::NftRegistry;
+use crate::notary::{Notarization, NotarizationProof};
+use crate::sync;
+use crate::transaction::Transaction;
+
+/// The number of most recent `Block`s whose fees are used by `Blockchain::estimate_fee_rate`
+pub const FEE_ESTIMATION_BLOCKS: usize = 20;
+
+/// The number of most recent `Block`s whose median timestamp is the median time past
+pub const MEDIAN_TIME_SPAN: usize = 11;
+
+/// How far, in seconds, the timestamp of a `Block` may be ahead of the local clock
+pub const MAX_FUTURE_BLOCK_TIME: u64 = 2 * 60 * 60;
+
+/// Why a `Block` was rejected by `Blockchain::validate_block`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlockError {
+    /// The `Block` does not extend the tip because it is already known or belongs to a fork
+    /// at or below the tip, as honest peers racing for the same height send
+    Stale(&'static str),
+
+    /// The `Block` is ahead of the tip, so its parent is not known yet
+    Orphan(&'static str),
+
+    /// The `Block` is not sealed according to the `Consensus` engine
+    BadSeal(&'static str),
+
+    /// The `Block` breaks any other validation rule
+    Invalid(&'static str),
+}
+impl BlockError {
+    /// The description of why the `Block` was rejected
+    pub fn message(&self) -> &'static str {
+        match self {
+            BlockError::Stale(message)
+            | BlockError::Orphan(message)
+            | BlockError::BadSeal(message)
+            | BlockError::Invalid(message) => message,
+        }
+    }
+}
+impl From<&'static str> for BlockError {
+    fn from(message: &'static str) -> Self {
+        BlockError::Invalid(message)
+    }
+}
+impl fmt::Display for BlockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+impl std::error::Error for BlockError {}
+
+/// A `Blockchain` shared by the tasks of a node
+///
+/// Queries only take the read lock, so many clients are served at once, while the write lock
+/// is held just long enough to append a `Block` or add a `Transaction`
+pub type SharedBlockchain = Arc<RwLock<Blockchain>>;
 
 /// A `Blockchain` is a sequence or collection of `Block`s that securely records
 /// transactions, by using cryptographic hashing, to be stored in `Block`s
@@ -19,6 +86,21 @@ pub struct Blockchain {
     /// The higher the value, the more difficult it is to match
     /// the `Block`'s starting hash value 
     pub difficulty: usize,
+
+    /// The `Transaction`s waiting to be included in a future `Block`
+    pub mempool: Mempool,
+
+    /// The `Consensus` engine deciding who may append `Block`s to this `Blockchain`
+    pub consensus: Arc<dyn Consensus>,
+
+    /// The `ChainSpec` of the network this `Blockchain` belongs to
+    pub spec: ChainSpec,
+
+    /// Publishes an `Event` whenever a `Block` or `Mempool` `Transaction` is accepted
+    pub events: broadcast::Sender<Event>,
+
+    /// The `Contract`s deployed on this `Blockchain`, as of its tip
+    pub contracts: ContractState,
 }
 #[allow(dead_code)]
 impl Blockchain {
@@ -28,20 +110,41 @@ impl Blockchain {
     /// # Returns
     /// - `Self` - The current `Blockchain` instance
     pub fn new(difficulty: usize) -> Self {
-        let mut genesis_block = Block::new(
-            0, 
-            Transaction::new(
-                None,
-                None,
-                0.0,
-                None
-            ), 
-            None
-        );
-        Miner::proof_of_work(&mut genesis_block, difficulty);
+        Self::from_spec(ChainSpec {
+            difficulty,
+            ..ChainSpec::default()
+        })
+    }
+
+    /// Constructs a new `Blockchain` instance for the network described by a `ChainSpec`
+    ///
+    /// # Parameters
+    /// - `spec` - The `ChainSpec` the genesis `Block` and `Consensus` engine are derived from
+    ///
+    /// # Returns
+    /// - `Self` - The current `Blockchain` instance
+    pub fn from_spec(spec: ChainSpec) -> Self {
+        let consensus = spec.consensus.build();
+        Self::with_consensus(spec, consensus)
+    }
+
+    /// Constructs a new `Blockchain` instance secured by the given `Consensus` engine
+    ///
+    /// # Parameters
+    /// - `spec` - The `ChainSpec` the genesis `Block` is derived from
+    /// - `consensus` - The `Consensus` engine used for every `Block` after the genesis `Block`
+    ///
+    /// # Returns
+    /// - `Self` - The current `Blockchain` instance
+    pub fn with_consensus(spec: ChainSpec, consensus: Arc<dyn Consensus>) -> Self {
         Blockchain {
-            chain: vec![genesis_block],
-            difficulty
+            chain: vec![spec.genesis_block()],
+            difficulty: spec.difficulty,
+            mempool: Mempool::new(),
+            consensus,
+            spec,
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            contracts: ContractState::default(),
         }
     }
 
@@ -53,8 +156,8 @@ impl Blockchain {
     /// 
     /// # Returns
     /// - `Result<(), &str>` - A result that contains whether the block was
-    ///    successfully added or not. If the signature verification fails, an `Err(&str)`
-    ///    is thrown
+    ///   successfully added or not. If the signature verification fails, an `Err(&str)`
+    ///   is thrown
     pub fn add_block(&mut self, mut new_block: Block) -> Result<(), &str> {
         let rng = SystemRandom::new();
         let key_pair = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
@@ -70,6 +173,335 @@ impl Blockchain {
         }
     }
     
+    /// Validates a `Block` submitted by a `Miner` against the current tip of this `Blockchain`
+    ///
+    /// # Parameters
+    /// - `block` - The mined `Block` to be validated
+    ///
+    /// # Returns
+    /// - `Result<(), BlockError>` - `Ok(())` if the `Block` extends the tip, its hash is correct, its
+    ///   timestamp is after the `median_time_past` and not too far in the future, it is sealed according to the `Consensus` engine, and its coinbase does not pay more than the
+    ///   block reward plus fees. A `BlockError` describing the problem otherwise
+    pub fn validate_block(&self, block: &Block) -> Result<(), BlockError> {
+        if (block.index as usize) < self.chain.len() {
+            return Err(BlockError::Stale("Block index does not follow the tip of the blockchain"));
+        }
+        if block.index as usize > self.chain.len() {
+            return Err(BlockError::Orphan("Block index does not follow the tip of the blockchain"));
+        }
+        if block.previous_hash != self.get_latest_block_hash() {
+            return Err(BlockError::Stale("Block does not reference the tip of the blockchain"));
+        }
+        if block.hash != Block::calculate_hash(block) {
+            return Err(BlockError::Invalid("Block hash does not match its contents"));
+        }
+        if block.transaction_count() > self.spec.limits.max_block_transactions {
+            return Err(BlockError::Invalid("Block has too many transactions"));
+        }
+        if block.size() > self.spec.limits.max_block_size {
+            return Err(BlockError::Invalid("Block exceeds the maximum block size"));
+        }
+        let median_time_past = self.median_time_past();
+        if block.timestamp <= median_time_past {
+            return Err(BlockError::Invalid("Block timestamp is not after the median time of the previous blocks"));
+        }
+        if block.timestamp > now() + MAX_FUTURE_BLOCK_TIME {
+            return Err(BlockError::Invalid("Block timestamp is too far in the future"));
+        }
+        self.consensus.verify(block, self).map_err(BlockError::BadSeal)?;
+        if !block.transaction.is_final(block.index as u64, median_time_past) {
+            return Err(BlockError::Invalid("Block includes a transaction before its lock time"));
+        }
+        block.transaction.verify_sender(block.index as u64, median_time_past)?;
+        let ledger = self.ledger();
+        ledger.check_balance(&block.transaction)?;
+
+        if let Some(call) = &block.transaction.contract {
+            call.check()?;
+        }
+        if block.transaction.moves_tokens() {
+            ledger.check_tokens(&block.transaction)?;
+        }
+        if block.transaction.nft.is_some() {
+            self.nfts().check(&block.transaction)?;
+        }
+        Notarization::check(&block.transaction)?;
+        if self.is_replay(&block.transaction) {
+            return Err(BlockError::Invalid("Block includes a transaction already on the blockchain"));
+        }
+        if self.is_transaction_id_used(&block.transaction) {
+            return Err(BlockError::Invalid("Block includes a transaction id already used by its sender"));
+        }
+        if block.state_root != self.next_state(&block.transaction).root() {
+            return Err(BlockError::Invalid("Block state root does not match its contracts"));
+        }
+
+        if block.coinbase.iter().any(|payout| !payout.amount.is_finite() || payout.amount < 0.0 || payout.recipient.is_none()) {
+            return Err(BlockError::Invalid("Block coinbase pays an invalid amount or no recipient"));
+        }
+        let reward = Miner::calculate_block_reward(self) + block.transaction.total_fee();
+        let payout: f64 = block.coinbase.iter().map(|tx| tx.amount).sum();
+        if payout > reward {
+            return Err(BlockError::Invalid("Block coinbase pays more than the block reward"));
+        }
+        Ok(())
+    }
+
+    /// Validates a mined `Block` and, if it is valid, appends it to this `Blockchain`
+    /// and removes its `Transaction` from the `Mempool`
+    ///
+    /// # Parameters
+    /// - `block` - The mined `Block` submitted by a `Miner`
+    ///
+    /// # Returns
+    /// - `Result<(), BlockError>` - `Ok(())` if the `Block` was accepted, or a `BlockError`
+    ///   describing why it was rejected
+    pub fn submit_block(&mut self, block: Block) -> Result<(), BlockError> {
+        self.validate_block(&block)?;
+        self.append_block(block);
+        Ok(())
+    }
+
+    /// Appends a `Block` which was already validated against the current tip
+    fn append_block(&mut self, block: Block) {
+        self.contracts.apply(&block.transaction);
+        self.mempool.remove(&block.transaction.calculate_hash());
+        let _ = self.events.send(Event::NewBlock(block.clone()));
+        self.chain.push(block);
+
+        // Transactions competing for the same balance or NFT may no longer be valid
+        let ledger = self.ledger();
+        self.mempool.transactions.retain(|transaction| {
+            ledger.check_balance(transaction).is_ok()
+                && (!transaction.moves_tokens() || ledger.check_tokens(transaction).is_ok())
+        });
+        if self.mempool.transactions.iter().any(|transaction| transaction.nft.is_some()) {
+            let nfts = self.nfts();
+            self.mempool.transactions.retain(|transaction| nfts.check(transaction).is_ok());
+        }
+        let included = &self.chain[self.chain.len() - 1].transaction;
+        self.mempool.transactions.retain(|transaction| !transaction.conflicts_with(included));
+    }
+
+    /// Gets the median timestamp of the last `MEDIAN_TIME_SPAN` `Block`s
+    ///
+    /// Unlike the timestamp of a single `Block`, it only moves forward, so the next `Block`
+    /// must be timestamped after it and lock times are checked against it
+    pub fn median_time_past(&self) -> u64 {
+        let mut timestamps: Vec<u64> = self.chain
+            .iter()
+            .rev()
+            .take(MEDIAN_TIME_SPAN)
+            .map(|block| block.timestamp)
+            .collect();
+        timestamps.sort_unstable();
+        timestamps.get(timestamps.len() / 2).copied().unwrap_or(0)
+    }
+
+    /// Checks whether a `Transaction` with a sender is already included in this `Blockchain`,
+    /// so its signed operations cannot be applied a second time
+    pub fn is_replay(&self, transaction: &Transaction) -> bool {
+        if transaction.sender.is_none() {
+            return false;
+        }
+        let hash = transaction.calculate_hash();
+        self.chain.iter().any(|block| block.transaction.sender.is_some() && block.transaction.calculate_hash() == hash)
+    }
+
+    /// Checks whether the sender of a `Transaction` already used its `transaction_id` in a
+    /// `Block` of this `Blockchain`
+    pub fn is_transaction_id_used(&self, transaction: &Transaction) -> bool {
+        transaction.sender.is_some() && transaction.transaction_id.is_some() && self.chain.iter().any(|block| {
+            block.transaction.sender == transaction.sender
+                && block.transaction.transaction_id == transaction.transaction_id
+        })
+    }
+
+    /// Estimates the fee per byte a `Transaction` should pay to be included soon
+    ///
+    /// # Returns
+    /// - `f64` - The median fee per byte paid by the `Transaction`s of the last
+    ///   `FEE_ESTIMATION_BLOCKS` `Block`s, and never less than the minimum relay fee of the
+    ///   `Mempool`
+    pub fn estimate_fee_rate(&self) -> f64 {
+        let mut rates: Vec<f64> = self.chain
+            .iter()
+            .rev()
+            .take(FEE_ESTIMATION_BLOCKS)
+            .map(|block| &block.transaction)
+            .filter(|transaction| transaction.sender.is_some())
+            .map(|transaction| transaction.fee.unwrap_or(0.0) / transaction.size() as f64)
+            .collect();
+        rates.sort_by(f64::total_cmp);
+        let median = rates.get(rates.len() / 2).copied().unwrap_or(0.0);
+        median.max(self.mempool.min_relay_fee_per_byte)
+    }
+
+    /// Computes the `ContractState` once a `Transaction` is applied on top of the tip
+    pub fn next_state(&self, transaction: &Transaction) -> ContractState {
+        let mut state = self.contracts.clone();
+        state.apply(transaction);
+        state
+    }
+
+    /// Adds a `Transaction` to the `Mempool`, notifying subscribers if it was not already pending
+    ///
+    /// # Returns
+    /// - `bool` - `true` if the `Transaction` was added, `false` if it was already pending, or
+    ///   if its lock time or `Script`s prevent the next `Block` from including it
+    pub fn add_transaction(&mut self, transaction: Transaction) -> bool {
+        let (height, time) = (self.chain.len() as u64, self.median_time_past());
+        if !transaction.is_final(height, time) || transaction.verify_sender(height, time).is_err() {
+            return false;
+        }
+        let ledger = self.ledger();
+        if ledger.check_balance(&transaction).is_err() {
+            return false;
+        }
+        if transaction.contract.as_ref().is_some_and(|call| call.check().is_err()) {
+            return false;
+        }
+        if transaction.moves_tokens() && ledger.check_tokens(&transaction).is_err() {
+            return false;
+        }
+        if transaction.nft.is_some() && self.nfts().check(&transaction).is_err() {
+            return false;
+        }
+        if self.is_replay(&transaction) {
+            return false;
+        }
+        if Notarization::check(&transaction).is_err() || self.is_transaction_id_used(&transaction) {
+            return false;
+        }
+        if !self.mempool.add(transaction.clone()) {
+            return false;
+        }
+        let _ = self.events.send(Event::NewTransaction(transaction));
+        true
+    }
+
+    /// Builds the `NftRegistry` holding every `Nft` minted on this `Blockchain`
+    pub fn nfts(&self) -> NftRegistry {
+        NftRegistry::from_chain(&self.chain)
+    }
+
+    /// Builds the `Ledger` holding the balance of every address on this `Blockchain`
+    pub fn ledger(&self) -> Ledger {
+        Ledger::from_chain(&self.chain)
+    }
+
+    /// Builds a block locator, a list of hashes describing this `Blockchain` to a peer,
+    /// as described by `sync::block_locator`
+    ///
+    /// # Returns
+    /// - `Vec<String>` - The hashes of the located `Block`s, newest first
+    pub fn block_locator(&self) -> Vec<String> {
+        sync::block_locator(&self.chain, |block| &block.hash)
+    }
+
+    /// Gets the `BlockHeader`s following the most recent `Block` of a locator this
+    /// `Blockchain` knows about
+    ///
+    /// # Parameters
+    /// - `locator` - A block locator built by a peer, newest first
+    /// - `limit` - The maximum number of `BlockHeader`s to return
+    ///
+    /// # Returns
+    /// - `Vec<BlockHeader>` - The `BlockHeader`s after the shared `Block`, or after the
+    ///   genesis `Block` if no hash of the locator is known
+    pub fn headers_after(&self, locator: &[String], limit: usize) -> Vec<BlockHeader> {
+        let start = locator
+            .iter()
+            .find_map(|hash| self.chain.iter().position(|block| &block.hash == hash))
+            .unwrap_or(0);
+        self.chain
+            .iter()
+            .skip(start + 1)
+            .take(limit)
+            .map(Block::header)
+            .collect()
+    }
+
+    /// Gets the most recent `Block`s of this `Blockchain` whose total size fits a budget
+    ///
+    /// # Parameters
+    /// - `max_size` - The maximum total size in bytes of the returned `Block`s
+    ///
+    /// # Returns
+    /// - `Vec<Block>` - The `Block`s up to the tip, oldest first
+    pub fn recent_blocks(&self, max_size: usize) -> Vec<Block> {
+        let mut size = 0;
+        let mut blocks: Vec<Block> = self.chain
+            .iter()
+            .rev()
+            .take_while(|block| {
+                size = block.size().saturating_add(size);
+                size <= max_size
+            })
+            .cloned()
+            .collect();
+        blocks.reverse();
+        blocks
+    }
+
+    /// Gets the `Block`s with the given hashes, skipping any hash which is not on this `Blockchain`
+    pub fn blocks_by_hash(&self, hashes: &[String]) -> Vec<Block> {
+        hashes
+            .iter()
+            .filter_map(|hash| self.chain.iter().find(|block| &block.hash == hash))
+            .cloned()
+            .collect()
+    }
+
+    /// Builds a `MerkleProof` that a `Transaction` is included in this `Blockchain`
+    ///
+    /// # Parameters
+    /// - `transaction_hash` - The hash of the `Transaction`, or of a coinbase payout
+    ///
+    /// # Returns
+    /// - `Option<(u32, MerkleProof)>` - The index of the `Block` including the `Transaction`
+    ///   and the proof of inclusion against its Merkle root, or `None` if it is not included
+    pub fn merkle_proof(&self, transaction_hash: &str) -> Option<(u32, MerkleProof)> {
+        self.chain.iter().find_map(|block| {
+            let hashes = block.transaction_hashes();
+            let position = hashes.iter().position(|hash| hash == transaction_hash)?;
+            MerkleProof::new(&hashes, position).map(|proof| (block.index, proof))
+        })
+    }
+
+    /// Builds the proof that a document hash was notarized on this `Blockchain`
+    ///
+    /// # Parameters
+    /// - `document_hash` - The hex encoded SHA-256 hash of the document
+    ///
+    /// # Returns
+    /// - `Option<NotarizationProof>` - The proof of the earliest `Notarization` of the document,
+    ///   or `None` if it was never notarized
+    pub fn notarization_proof(&self, document_hash: &str) -> Option<NotarizationProof> {
+        let block = self.chain.iter().find(|block| {
+            block.transaction.notarization.as_ref()
+                .is_some_and(|notarization| notarization.document_hash == document_hash)
+        })?;
+        let merkle_proof = MerkleProof::new(&block.transaction_hashes(), 0)?;
+        Some(NotarizationProof {
+            transaction: block.transaction.clone(),
+            block_index: block.index,
+            timestamp: block.timestamp,
+            block_hash: block.hash.clone(),
+            merkle_proof,
+        })
+    }
+
+    /// Gets every `Transaction` and coinbase payout sent or received by an address
+    pub fn transactions_of(&self, address: &str) -> Vec<Transaction> {
+        self.chain
+            .iter()
+            .flat_map(|block| std::iter::once(&block.transaction).chain(&block.coinbase))
+            .filter(|transaction| transaction.involves(address))
+            .cloned()
+            .collect()
+    }
+
     /// Gets the hash value for the most recent `Block` added to this `Blockchain`
     /// 
     /// # Returns
@@ -97,4 +529,34 @@ impl Blockchain {
         }
         Ok(true)
     }
-}
\ No newline at end of file
+}
+
+/// Validates a mined `Block` while only holding the read lock of a `SharedBlockchain`, then
+/// appends it under a short write lock, so queries are not blocked while it is validated
+///
+/// # Parameters
+/// - `blockchain` - The `SharedBlockchain` the `Block` is submitted to
+/// - `block` - The mined `Block`
+///
+/// # Returns
+/// - `Result<(), BlockError>` - `Ok(())` if the `Block` was accepted, or a `BlockError` describing
+///   why it was rejected, as for `Blockchain::submit_block`
+pub async fn submit_shared_block(blockchain: &RwLock<Blockchain>, block: Block) -> Result<(), BlockError> {
+    blockchain.read().await.validate_block(&block)?;
+
+    let mut blockchain = blockchain.write().await;
+    if block.index as usize != blockchain.chain.len() || block.previous_hash != blockchain.get_latest_block_hash() {
+        // The tip moved while the `Block` was validated
+        return blockchain.submit_block(block);
+    }
+    blockchain.append_block(block);
+    Ok(())
+}
+
+/// Gets the current Unix time in seconds
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use crate::block::Block;
+use crate::consensus::{Consensus, ProofOfAuthority, ProofOfStake, ProofOfWork};
+use crate::contract::ContractState;
+use crate::miner::Miner;
+use crate::network::MAX_FRAME_SIZE;
+use crate::transaction::Transaction;
+
+/// The block reward schedule of a network
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct RewardSchedule {
+    /// The reward, in cryptos, for producing a `Block` before the first halving
+    pub initial_reward: f64,
+
+    /// The number of `Block`s after which the reward is halved
+    pub halving_interval: usize,
+}
+impl Default for RewardSchedule {
+    fn default() -> Self {
+        RewardSchedule {
+            initial_reward: 50.0,
+            halving_interval: 210000,
+        }
+    }
+}
+
+/// The largest `max_block_size` a `ChainSpec` may set, so that a `Block` still fits in a single
+/// frame when JSON encodes each of its bytes as up to four characters
+pub const MAX_BLOCK_SIZE_LIMIT: usize = MAX_FRAME_SIZE / 4;
+
+/// The smallest `max_block_transactions` a `ChainSpec` may set: a `Transaction` and one
+/// coinbase payout
+pub const MIN_BLOCK_TRANSACTIONS: usize = 2;
+
+/// The consensus limits on the size of a `Block`
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub struct BlockLimits {
+    /// The maximum size in bytes of a `Block` in the binary wire encoding, which must leave
+    /// room for the `Block` to fit in a single frame even when encoded as JSON
+    pub max_block_size: usize,
+
+    /// The maximum number of `Transaction`s in a `Block`, counting its coinbase payouts
+    pub max_block_transactions: usize,
+}
+impl Default for BlockLimits {
+    fn default() -> Self {
+        BlockLimits {
+            max_block_size: 256 * 1024,
+            max_block_transactions: 1000,
+        }
+    }
+}
+
+/// The `Consensus` engine selected for a network
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConsensusSpec {
+    /// See `ProofOfWork`
+    ProofOfWork,
+
+    /// See `ProofOfStake`
+    ProofOfStake {
+        minimum_stake: f64,
+
+        /// The number of seconds between proposals of a validator
+        #[serde(default = "default_block_interval")]
+        block_interval: u64,
+    },
+
+    /// See `ProofOfAuthority`
+    ProofOfAuthority {
+        validators: Vec<String>,
+
+        #[serde(default = "default_block_interval")]
+        block_interval: u64,
+    },
+}
+impl ConsensusSpec {
+    /// Builds the `Consensus` engine described by this specification
+    pub fn build(&self) -> Arc<dyn Consensus> {
+        match self {
+            ConsensusSpec::ProofOfWork => Arc::new(ProofOfWork),
+            ConsensusSpec::ProofOfStake { minimum_stake, block_interval } => Arc::new(ProofOfStake {
+                minimum_stake: *minimum_stake,
+                block_interval: *block_interval,
+            }),
+            ConsensusSpec::ProofOfAuthority { validators, block_interval } => Arc::new(ProofOfAuthority {
+                validators: validators.clone(),
+                block_interval: *block_interval,
+            }),
+        }
+    }
+
+    /// The number of seconds between `Block`s produced by a validator, or `None`
+    /// if `Block`s are mined rather than produced by validators
+    pub fn block_interval(&self) -> Option<u64> {
+        match self {
+            ConsensusSpec::ProofOfWork => None,
+            ConsensusSpec::ProofOfStake { block_interval, .. }
+            | ConsensusSpec::ProofOfAuthority { block_interval, .. } => Some(*block_interval),
+        }
+    }
+}
+
+fn default_block_interval() -> u64 {
+    5
+}
+
+/// A `ChainSpec` describes a network, from which every node deterministically
+/// derives the same genesis `Block`
+///
+/// A `ChainSpec` is usually loaded from a JSON file shared by every node of the network.
+/// Allocations are paid to key addresses, the hex encoded Ed25519 public keys, or to script
+/// addresses, since only those can authorize spending them:
+///
+/// ```json
+/// {
+///     "network_id": "staging",
+///     "genesis_timestamp": 1700000000,
+///     "allocations": { "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c": 1000.0 },
+///     "difficulty": 4,
+///     "reward": { "initial_reward": 50.0, "halving_interval": 210000 },
+///     "limits": { "max_block_size": 262144, "max_block_transactions": 1000 },
+///     "consensus": { "type": "proof_of_work" },
+///     "seeds": ["127.0.0.1:8080"]
+/// }
+/// ```
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ChainSpec {
+    /// The identifier of the network, which makes its genesis `Block` unique
+    pub network_id: String,
+
+    /// The time, in seconds, stored in the genesis `Block`
+    pub genesis_timestamp: u64,
+
+    /// The initial balance of each address, paid by the coinbase of the genesis `Block`
+    #[serde(default)]
+    pub allocations: BTreeMap<String, f64>,
+
+    /// The difficulty target of the `Blockchain`
+    pub difficulty: usize,
+
+    /// The block reward schedule
+    #[serde(default)]
+    pub reward: RewardSchedule,
+
+    /// The limits on the size of every `Block` but the genesis `Block`
+    #[serde(default)]
+    pub limits: BlockLimits,
+
+    /// The `Consensus` engine of the network
+    pub consensus: ConsensusSpec,
+
+    /// The `address:port` of the nodes a new node bootstraps peer discovery from, written as
+    /// `public_key@address:port` to pin the identity a seed must authenticate with
+    #[serde(default)]
+    pub seeds: Vec<String>,
+}
+impl Default for ChainSpec {
+    fn default() -> Self {
+        ChainSpec {
+            network_id: String::from("devnet"),
+            genesis_timestamp: 1700000000,
+            allocations: BTreeMap::new(),
+            difficulty: 4,
+            reward: RewardSchedule::default(),
+            limits: BlockLimits::default(),
+            consensus: ConsensusSpec::ProofOfWork,
+            seeds: Vec::new(),
+        }
+    }
+}
+impl ChainSpec {
+    /// Loads a `ChainSpec` from a JSON file
+    ///
+    /// # Parameters
+    /// - `path` - The path of the chain specification file
+    ///
+    /// # Returns
+    /// - `Result<Self, Error>` - The `ChainSpec`, or an `Error` if the file could not be read,
+    ///   parsed or does not pass `ChainSpec::validate`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let contents = fs::read(path)?;
+        let spec: ChainSpec = serde_json::from_slice(&contents).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        spec.validate().map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        Ok(spec)
+    }
+
+    /// Checks that the parameters of this `ChainSpec` can be used by a `Blockchain`
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if this `ChainSpec` is usable, or an `Err(&str)`
+    ///   describing the invalid parameter
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.reward.halving_interval == 0 {
+            return Err("The halving interval must be at least one block");
+        }
+        if self.allocations.values().any(|amount| !amount.is_finite() || *amount < 0.0) {
+            return Err("Genesis allocations must be finite and not negative");
+        }
+        if self.limits.max_block_size == 0 || self.limits.max_block_size > MAX_BLOCK_SIZE_LIMIT {
+            return Err("The maximum block size must be positive and fit in a single frame");
+        }
+        if self.limits.max_block_transactions < MIN_BLOCK_TRANSACTIONS {
+            return Err("The maximum block transactions must leave room for a transaction and a coinbase payout");
+        }
+        Ok(())
+    }
+
+    /// Derives the genesis `Block` of the network
+    ///
+    /// Every field of the genesis `Block` comes from this `ChainSpec`, so every node
+    /// using the same specification computes the same genesis hash
+    pub fn genesis_block(&self) -> Block {
+        let mut transaction = Transaction::new(None, None, 0.0, None);
+        transaction.timestamp = self.genesis_timestamp;
+        transaction.metadata = Some(self.network_id.clone());
+
+        let mut genesis_block = Block::new(0, transaction, None);
+        genesis_block.timestamp = self.genesis_timestamp;
+        genesis_block.coinbase = self
+            .allocations
+            .iter()
+            .map(|(address, amount)| {
+                let mut allocation = Transaction::new(None, Some(address.clone()), *amount, None);
+                allocation.timestamp = self.genesis_timestamp;
+                allocation
+            })
+            .collect();
+        genesis_block.state_root = ContractState::default().root();
+        genesis_block.hash = Block::calculate_hash(&genesis_block);
+        Miner::proof_of_work(&mut genesis_block, self.difficulty);
+        genesis_block
+    }
+}
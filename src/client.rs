@@ -1,12 +1,23 @@
+use std::env;
+use std::fs;
 use std::io;
 use std::io::Error;
 use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
 use blockchain_network::block::Block;
-use blockchain_network::blockchain::Blockchain;
+use blockchain_network::blockchain::{Blockchain, SharedBlockchain};
+use blockchain_network::chain_spec::ChainSpec;
+use blockchain_network::events::{Event, Topic};
+use blockchain_network::hex;
+use blockchain_network::multisig::{MultisigAccount, PartiallySignedTransaction};
+use blockchain_network::light_client::LightClient;
 use blockchain_network::miner::Miner;
-use blockchain_network::network::{address, port, Message};
+use blockchain_network::network::{address, connect, port, pool_port, send_request, Message};
+use blockchain_network::transport::set_json_wire;
+use blockchain_network::version::{set_network_id, Version};
+use blockchain_network::pool::Share;
+use blockchain_network::transaction::LockTime;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair};
 
 pub struct Client {
     miner: Miner,
@@ -35,7 +46,9 @@ impl Client {
         loop {
             println!("Choose an available option:");
             println!("1. Mine and send blocks");
-            println!("2. Exit");
+            println!("2. Mine a block from the node's template");
+            println!("3. Mine shares for the pool");
+            println!("4. Exit");
             
             let mut choice = String::new();
             io::stdin().read_line(&mut choice).expect("Failed to read line");
@@ -43,20 +56,36 @@ impl Client {
             
             match choice {
                 "1" => {
-                    let transaction = blockchain_network::transaction::Transaction::new(
-                        Some("sender".to_string()),
-                        Some("receiver".to_string()),
+                    // Only key addresses can authorize spending, so a local chain funds a fresh key
+                    let sender = generate_key();
+                    let receiver = generate_key();
+                    let mut transaction = blockchain_network::transaction::Transaction::new(
+                        Some(key_address(&sender)),
+                        Some(key_address(&receiver)),
                         10.0,
                         None
                     );
+                    transaction.authorize(&sender);
                     let block = Block::new(1, transaction, None);
-                    let blockchain = Arc::new(tokio::sync::Mutex::new(Blockchain::new(4)));
+                    let allocations = [(key_address(&sender), 1000.0)].into_iter().collect();
+                    let spec = ChainSpec { allocations, ..ChainSpec::default() };
+                    let blockchain = Arc::new(tokio::sync::RwLock::new(Blockchain::from_spec(spec)));
 
                     if let Err(e) = client.request_block(blockchain, block).await {
                         eprintln!("Failed to mine and send block: {}", e);
                     } 
                 }
-                "2" =>  {
+                "2" => {
+                    if let Err(e) = client.mine_from_template().await {
+                        eprintln!("Failed to mine block from template: {}", e);
+                    }
+                }
+                "3" => {
+                    if let Err(e) = client.mine_for_pool().await {
+                        eprintln!("Failed to mine share: {}", e);
+                    }
+                }
+                "4" =>  {
                     if let Err(e) = client.disconnect().await {
                         eprintln!("Failed to disconnect: {}", e);
                     }
@@ -69,51 +98,275 @@ impl Client {
     
     pub async fn connect(&self) -> Result<(), Error> {
         let full_address = format!("{}:{}", *address, *port);
-        let mut socket = TcpStream::connect(&full_address).await?;
+        let (mut channel, _) = connect(&full_address, &Version::client()).await?;
         let auth_message = Message::Connect(self.miner.identifier.to_string()); 
-        let message = serde_json::to_vec(&auth_message)?;
-        socket.write_all(&message).await?;
+        channel.send(&auth_message).await?;
 
-        let mut buffer = [0; 1024];
-        loop {
-            let size = socket.read(&mut buffer).await?;
-            if size == 0 {
-                break;
+        let (mut channel, _) = connect(&full_address, &Version::client()).await?;
+        let topics = vec![Topic::NewBlocks, Topic::Address(self.miner.identifier.to_string())];
+        channel.send(&Message::Subscribe(topics)).await?;
+        tokio::spawn(async move {
+            loop {
+                match channel.receive().await {
+                    Ok(Message::Notification(Event::NewBlock(block))) => {
+                        println!("New block #{}: {}", block.index, block.hash)
+                    }
+                    Ok(Message::Notification(Event::NewTransaction(transaction))) => println!(
+                        "New transaction: {:?} -> {:?} ({})",
+                        transaction.sender, transaction.recipient, transaction.amount
+                    ),
+                    Ok(Message::Ping(nonce)) => {
+                        if channel.send(&Message::Pong(nonce)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(Message::Disconnect(node)) => {
+                        println!("{} is shutting down", node);
+                        break;
+                    }
+                    _ => break,
+                }
             }
-            let response: serde_json::Value = serde_json::from_slice(&buffer[..size])?;
-            println!("Received message: {:?}", response);
-        }
+        });
         Ok(())
     }
     
     pub async fn disconnect(&self) -> Result<(), Error> {
         let full_address = format!("{}:{}", *address, *port);
-        let mut socket = TcpStream::connect(&full_address).await?;
+        let (mut channel, _) = connect(&full_address, &Version::client()).await?;
         let disconnect_message = Message::Disconnect(self.miner.identifier.to_string());
-        let message = serde_json::to_vec(&disconnect_message)?;
-        socket.write_all(&message).await?;
+        channel.send(&disconnect_message).await?;
         Ok(())
     }
 
-    pub async fn request_block(&mut self, blockchain: Arc<tokio::sync::Mutex<Blockchain>>, block: Block)
+    pub async fn request_block(&mut self, blockchain: SharedBlockchain, block: Block)
                                      -> Result<(), Error> {
 
-        let mut blockchain = blockchain.lock().await;
+        let mut blockchain = blockchain.write().await;
         let miner = &mut self.miner;
-        miner.mine_block(&mut blockchain, block.clone()).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        miner.mine_block(&mut blockchain, block.clone()).map_err(io::Error::other)?;
         println!("{}'s crypto balance: {}", miner.identifier, miner.balance);
         
         let message = Message::MineBlock(block);
-        let serialized_message = serde_json::to_vec(&message)?;
         let full_address = format!("{}:{}", *address, *port);
-        let mut socket = TcpStream::connect(&full_address).await?;
-        socket.write_all(&serialized_message).await?;
+        let (mut channel, _) = connect(&full_address, &Version::client()).await?;
+        channel.send(&message).await?;
         
         Ok(())
     }
+
+    /// Requests a `BlockTemplate` from the node, mines it and submits the resulting `Block`
+    pub async fn mine_from_template(&mut self) -> Result<(), Error> {
+        let full_address = format!("{}:{}", *address, *port);
+        let request = Message::GetBlockTemplate(self.miner.identifier.to_string());
+        let template = match send_request(&full_address, &request).await? {
+            Message::BlockTemplate(template) => template,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Expected a block template")),
+        };
+        let reward: f64 = template.coinbase.iter().map(|tx| tx.amount).sum();
+        let block = Miner::mine_template(&template);
+
+        match send_request(&full_address, &Message::SubmitBlock(block)).await? {
+            Message::SubmitResult(Ok(())) => {
+                self.miner.balance += reward;
+                println!("Block #{} accepted! {}'s crypto balance: {}",
+                         template.index, self.miner.identifier, self.miner.balance);
+                Ok(())
+            }
+            Message::SubmitResult(Err(e)) => Err(io::Error::other(e)),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Expected a submit result")),
+        }
+    }
+
+    /// Requests a `Job` from the mining pool, mines it at the share difficulty and submits the share
+    pub async fn mine_for_pool(&mut self) -> Result<(), Error> {
+        let pool_address = format!("{}:{}", *address, *pool_port);
+        let request = Message::GetJob(self.miner.identifier.to_string());
+        let job = match send_request(&pool_address, &request).await? {
+            Message::Job(job) => job,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Expected a job")),
+        };
+        let block = Miner::mine_template(&job.template);
+        let share = Share {
+            miner: self.miner.identifier.to_string(),
+            job_id: job.id,
+            nonce: block.nonce,
+        };
+
+        match send_request(&pool_address, &Message::SubmitShare(share)).await? {
+            Message::ShareResult(Ok(true)) => println!("Share accepted and block #{} found!", job.template.index),
+            Message::ShareResult(Ok(false)) => println!("Share accepted for job #{}", job.id),
+            Message::ShareResult(Err(e)) => return Err(io::Error::other(e)),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Expected a share result")),
+        }
+        Ok(())
+    }
+}
+
+/// Runs the `client` as a light client, which only stores block headers and verifies
+/// its wallet's transactions with Merkle proofs from the node
+async fn run_light(spec: ChainSpec) {
+    let full_address = format!("{}:{}", *address, *port);
+    let mut light_client = LightClient::new(&spec);
+
+    let mut wallet = String::new();
+    println!("Enter your wallet address:");
+    io::stdin().read_line(&mut wallet).expect("Failed to read line");
+    let wallet = wallet.trim().to_string();
+
+    loop {
+        println!("Choose an available option:");
+        println!("1. Sync block headers");
+        println!("2. Verify my transactions");
+        println!("3. Exit");
+
+        let mut choice = String::new();
+        io::stdin().read_line(&mut choice).expect("Failed to read line");
+
+        match choice.trim() {
+            "1" => match light_client.sync_headers(&full_address).await {
+                Ok(()) => println!("Synced {} block headers", light_client.headers.len()),
+                Err(e) => eprintln!("Failed to sync headers: {}", e),
+            },
+            "2" => match light_client.verify_transactions(&full_address, &wallet).await {
+                Ok(verified) => {
+                    for (hash, index) in verified {
+                        println!("Transaction {} is included in block #{}", hash, index);
+                    }
+                }
+                Err(e) => eprintln!("Failed to verify transactions: {}", e),
+            },
+            "3" => return,
+            _ => println!("Invalid option. Please try again!"),
+        }
+    }
+}
+
+/// Prints `question` and reads the trimmed answer from stdin
+fn prompt(question: &str) -> String {
+    let mut answer = String::new();
+    println!("{}", question);
+    io::stdin().read_line(&mut answer).expect("Failed to read line");
+    answer.trim().to_string()
+}
+
+/// Loads an Ed25519 key written by the server's `--generate-key`
+fn load_key(path: &str) -> Result<Ed25519KeyPair, Error> {
+    let pkcs8 = hex::decode(fs::read_to_string(path)?.trim())
+        .ok_or(Error::new(io::ErrorKind::InvalidData, "Invalid key file"))?;
+    Ed25519KeyPair::from_pkcs8(&pkcs8).map_err(|e| Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+/// Generates a new random Ed25519 key pair
+fn generate_key() -> Ed25519KeyPair {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).expect("Failed to generate a key");
+    Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).expect("Failed to parse the generated key")
+}
+
+/// Gets the key address of a key pair, its hex encoded public key
+fn key_address(key_pair: &Ed25519KeyPair) -> String {
+    hex::encode(key_pair.public_key().as_ref())
+}
+
+/// Runs one step of the multisig wallet, passing partially signed transactions between
+/// signers as JSON files
+async fn multisig_step(choice: &str) -> Result<(), Error> {
+    let invalid = |e: &str| Error::new(io::ErrorKind::InvalidInput, e.to_string());
+    match choice {
+        "1" => {
+            let threshold = prompt("Enter the number of required signatures:").parse().map_err(|_| invalid("Invalid number"))?;
+            let public_keys = prompt("Enter the hex public keys of the signers, separated by commas:")
+                .split(',')
+                .map(|key| key.trim().to_string())
+                .collect();
+            let account = MultisigAccount::new(threshold, public_keys).map_err(invalid)?;
+            let path = prompt("Enter the file to save the account to:");
+            fs::write(&path, serde_json::to_vec_pretty(&account)?)?;
+            println!("Multisig account address: {}", account.address());
+        }
+        "2" => {
+            let account: MultisigAccount = serde_json::from_slice(&fs::read(prompt("Enter the account file:"))?)?;
+            let recipient = prompt("Enter the recipient:");
+            let amount = prompt("Enter the amount:").parse().map_err(|_| invalid("Invalid amount"))?;
+            let fee = prompt("Enter the fee:").parse().map_err(|_| invalid("Invalid fee"))?;
+            let mut transaction = PartiallySignedTransaction::new(account, &recipient, amount, Some(fee));
+            let lock_height = prompt("Enter the block height it may be included from, or leave empty:");
+            if !lock_height.is_empty() {
+                let lock_height = lock_height.parse().map_err(|_| invalid("Invalid block height"))?;
+                transaction.transaction.lock_time = Some(LockTime::Height(lock_height));
+            }
+            transaction.save(prompt("Enter the file to save the transaction to:"))?;
+        }
+        "3" => {
+            let path = prompt("Enter the transaction file:");
+            let mut transaction = PartiallySignedTransaction::load(&path)?;
+            transaction.sign(&load_key(&prompt("Enter your key file:"))?).map_err(invalid)?;
+            transaction.save(&path)?;
+            println!("Signed with {} of {} required signatures", transaction.signatures.len(), transaction.account.threshold());
+        }
+        "4" => {
+            let path = prompt("Enter the transaction file:");
+            let mut transaction = PartiallySignedTransaction::load(&path)?;
+            transaction.combine(PartiallySignedTransaction::load(prompt("Enter the file to merge signatures from:"))?)
+                .map_err(invalid)?;
+            transaction.save(&path)?;
+            println!("Collected {} of {} required signatures", transaction.signatures.len(), transaction.account.threshold());
+        }
+        "5" => {
+            let transaction = PartiallySignedTransaction::load(prompt("Enter the transaction file:"))?;
+            let transaction = transaction.finalize().map_err(invalid)?;
+            let full_address = format!("{}:{}", *address, *port);
+            let (mut channel, _) = connect(&full_address, &Version::client()).await?;
+            channel.send(&Message::NewTransaction(transaction.clone())).await?;
+            println!("Broadcast transaction {}", transaction.calculate_hash());
+        }
+        _ => println!("Invalid option. Please try again!"),
+    }
+    Ok(())
+}
+
+/// Runs the `client` as a wallet for multisig accounts
+async fn run_multisig() {
+    loop {
+        println!("Choose an available option:");
+        println!("1. Create a multisig account");
+        println!("2. Create a transaction spending from a multisig account");
+        println!("3. Sign a transaction");
+        println!("4. Merge signatures from another signer");
+        println!("5. Broadcast a fully signed transaction");
+        println!("6. Exit");
+
+        let mut choice = String::new();
+        io::stdin().read_line(&mut choice).expect("Failed to read line");
+        if choice.trim() == "6" {
+            return;
+        }
+        if let Err(e) = multisig_step(choice.trim()).await {
+            eprintln!("Multisig wallet error: {}", e);
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    Client::run().await;
+    set_json_wire(env::args().any(|arg| arg == "--json-wire"));
+    let spec = match env::args().position(|arg| arg == "--chain-spec").and_then(|i| env::args().nth(i + 1)) {
+        Some(path) => match ChainSpec::load(path) {
+            Ok(spec) => spec,
+            Err(e) => {
+                eprintln!("Failed to load chain specification: {}", e);
+                return;
+            }
+        },
+        None => ChainSpec::default(),
+    };
+    set_network_id(spec.network_id.clone()).expect("Network id is set once");
+
+    if env::args().any(|arg| arg == "--light") {
+        run_light(spec).await;
+    } else if env::args().any(|arg| arg == "--multisig") {
+        run_multisig().await;
+    } else {
+        Client::run().await;
+    }
 }
\ No newline at end of file
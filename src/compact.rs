@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::block::{Block, BlockHeader};
+use crate::mempool::Mempool;
+use crate::transaction::Transaction;
+
+/// A `CompactBlock` announces a new `Block` to a peer without sending its `Transaction`s
+///
+/// Peers usually already have the `Transaction`s of a new `Block` in their `Mempool`, so
+/// they are identified by short ids and only the missing ones have to be requested.
+/// The coinbase payouts are never in a `Mempool`, so they are sent along with the header
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CompactBlock {
+    /// The `BlockHeader` of the announced `Block`
+    pub header: BlockHeader,
+
+    /// The short id of every `Transaction` of the `Block`, in Merkle tree order
+    pub short_ids: Vec<String>,
+
+    /// The `Transaction`s sent in full, with their position in the `Block`
+    pub prefilled: Vec<(usize, Transaction)>,
+}
+impl CompactBlock {
+    /// Builds the `CompactBlock` announcing a `Block`
+    pub fn from_block(block: &Block) -> Self {
+        let short_ids = block
+            .transaction_hashes()
+            .iter()
+            .map(|hash| Self::short_id(&block.hash, hash))
+            .collect();
+        let prefilled = block
+            .coinbase
+            .iter()
+            .enumerate()
+            .map(|(i, payout)| (i + 1, payout.clone()))
+            .collect();
+
+        CompactBlock {
+            header: block.header(),
+            short_ids,
+            prefilled,
+        }
+    }
+
+    /// Computes the short id of a `Transaction` within the `Block` with the given hash
+    ///
+    /// Short ids are salted with the `Block` hash, so colliding `Transaction`s
+    /// cannot be crafted ahead of time
+    pub fn short_id(block_hash: &str, transaction_hash: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(block_hash.as_bytes());
+        hasher.update(transaction_hash.as_bytes());
+        format!("{:x}", hasher.finalize())[..12].to_string()
+    }
+
+    /// Matches the short ids of this `CompactBlock` against the `Transaction`s of a `Mempool`
+    ///
+    /// # Returns
+    /// - `Vec<Option<Transaction>>` - The `Transaction` at each position of the `Block`,
+    ///   or `None` if it is neither prefilled nor in the `Mempool`
+    pub fn match_transactions(&self, mempool: &Mempool) -> Vec<Option<Transaction>> {
+        let mut transactions: Vec<Option<Transaction>> = vec![None; self.short_ids.len()];
+        for (position, transaction) in &self.prefilled {
+            if let Some(slot) = transactions.get_mut(*position) {
+                *slot = Some(transaction.clone());
+            }
+        }
+        for transaction in &mempool.transactions {
+            let short_id = Self::short_id(&self.header.hash, &transaction.calculate_hash());
+            for (position, id) in self.short_ids.iter().enumerate() {
+                if *id == short_id && transactions[position].is_none() {
+                    transactions[position] = Some(transaction.clone());
+                }
+            }
+        }
+        transactions
+    }
+
+    /// Gets the positions of the `Transaction`s which could not be matched
+    pub fn missing(transactions: &[Option<Transaction>]) -> Vec<usize> {
+        transactions
+            .iter()
+            .enumerate()
+            .filter(|(_, transaction)| transaction.is_none())
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// Reconstructs the announced `Block` once every `Transaction` is known
+    ///
+    /// # Parameters
+    /// - `transactions` - The `Transaction` at each position of the `Block`
+    ///
+    /// # Returns
+    /// - `Result<Block, &str>` - The `Block`, or an `Err(&str)` if a `Transaction` is
+    ///   missing or the `Transaction`s do not match the header's Merkle root
+    pub fn reconstruct(&self, transactions: Vec<Option<Transaction>>) -> Result<Block, &'static str> {
+        let mut transactions = transactions
+            .into_iter()
+            .collect::<Option<Vec<Transaction>>>()
+            .ok_or("Compact block is missing transactions")?
+            .into_iter();
+
+        let header = &self.header;
+        let mut block = Block::new(
+            header.index,
+            transactions.next().ok_or("Compact block has no transactions")?,
+            header.previous_hash.clone(),
+        );
+        block.timestamp = header.timestamp;
+        block.coinbase = transactions.collect();
+        block.nonce = header.nonce;
+        block.validator = header.validator.clone();
+        block.signature = header.signature.clone();
+        block.state_root = header.state_root.clone();
+        block.hash = header.hash.clone();
+
+        if block.merkle_root() != header.merkle_root {
+            return Err("Reconstructed transactions do not match the merkle root");
+        }
+        Ok(block)
+    }
+}
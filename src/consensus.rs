@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use sha2::{Digest, Sha256};
+use crate::block::{Block, BlockHeader};
+use crate::blockchain::{submit_shared_block, Blockchain};
+use crate::hex;
+use crate::ledger::Ledger;
+use crate::miner::Miner;
+use crate::network::{relay_block, Node};
+use crate::template::BlockTemplate;
+
+/// The number of block intervals a scheduled validator may miss before the next validator
+/// takes over its turn
+pub const TAKEOVER_INTERVALS: u64 = 3;
+
+/// A `Consensus` engine decides who may append a `Block` to the `Blockchain`
+/// and how that right is proven
+///
+/// The engine of a `Blockchain` is chosen when its genesis `Block` is created
+pub trait Consensus: Send + Sync {
+    /// Seals a `Block` so that it can be appended to the `Blockchain`
+    ///
+    /// # Parameters
+    /// - `block` - The `Block` to be sealed, which should extend the tip of `blockchain`
+    /// - `blockchain` - The `Blockchain` the `Block` will be appended to
+    /// - `signer` - The key-pair of the `Block`'s producer, required by engines whose
+    ///   `Block`s are signed
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if the `Block` was sealed, or an `Err(&str)` if
+    ///   `signer` is not allowed to produce it
+    fn seal(&self, block: &mut Block, blockchain: &Blockchain, signer: Option<&Ed25519KeyPair>)
+            -> Result<(), &'static str>;
+
+    /// Verifies that a `Block` was sealed according to this engine's rules
+    ///
+    /// # Parameters
+    /// - `block` - The `Block` to be verified, whose hash has already been checked
+    /// - `blockchain` - The `Blockchain` whose tip the `Block` extends
+    fn verify(&self, block: &Block, blockchain: &Blockchain) -> Result<(), &'static str>;
+
+    /// Verifies that a `BlockHeader` was sealed according to this engine's rules, as far as
+    /// this can be checked without the `Block` bodies
+    ///
+    /// # Parameters
+    /// - `header` - The `BlockHeader` to be verified, whose hash has already been checked
+    /// - `previous` - The `BlockHeader` it extends
+    /// - `difficulty` - The difficulty target of the `Blockchain`
+    /// - `blockchain` - The local `Blockchain`, if the caller holds one, whose state engines
+    ///   may check the header against when `previous` is its tip
+    fn verify_header(&self, header: &BlockHeader, previous: &BlockHeader, difficulty: usize,
+                     blockchain: Option<&Blockchain>) -> Result<(), &'static str>;
+}
+
+/// The [Proof of Work (PoW)](https://www.investopedia.com/terms/p/proof-work.asp) engine,
+/// where a `Block` hash must meet the difficulty target of the `Blockchain`
+pub struct ProofOfWork;
+impl Consensus for ProofOfWork {
+    fn seal(&self, block: &mut Block, blockchain: &Blockchain, _signer: Option<&Ed25519KeyPair>)
+            -> Result<(), &'static str> {
+        Miner::proof_of_work(block, blockchain.difficulty);
+        Ok(())
+    }
+
+    fn verify(&self, block: &Block, blockchain: &Blockchain) -> Result<(), &'static str> {
+        check_target(&block.hash, blockchain.difficulty)
+    }
+
+    fn verify_header(&self, header: &BlockHeader, _previous: &BlockHeader, difficulty: usize,
+                     _blockchain: Option<&Blockchain>) -> Result<(), &'static str> {
+        check_target(&header.hash, difficulty)
+    }
+}
+
+fn check_target(hash: &str, difficulty: usize) -> Result<(), &'static str> {
+    if !hash.starts_with(&"0".repeat(difficulty)) {
+        return Err("Block hash does not meet the difficulty target");
+    }
+    Ok(())
+}
+
+/// The set of validators allowed to propose `Block`s under `ProofOfStake`,
+/// weighted by their stake
+#[derive(Debug, Default, Clone)]
+pub struct StakeRegistry {
+    /// The stake of each validator, in hundredths of a crypto, keyed by hex-encoded public key
+    pub stakes: BTreeMap<String, u64>,
+}
+impl StakeRegistry {
+    /// Builds a `StakeRegistry` from the balances of a `Ledger`
+    ///
+    /// Only addresses which are Ed25519 public keys, and can therefore sign `Block`s,
+    /// and whose balance is at least `minimum_stake` are registered
+    pub fn from_ledger(ledger: &Ledger, minimum_stake: f64) -> Self {
+        let stakes = ledger
+            .balances
+            .iter()
+            .filter(|(address, balance)| {
+                **balance >= minimum_stake && hex::decode(address).is_some_and(|key| key.len() == 32)
+            })
+            .map(|(address, balance)| (address.clone(), (balance * 100.0) as u64))
+            .filter(|(_, stake)| *stake > 0)
+            .collect();
+        StakeRegistry { stakes }
+    }
+
+    /// Deterministically selects the proposer of a slot, weighted by stake
+    ///
+    /// # Parameters
+    /// - `seed` - The hash of the `Block` preceding the slot
+    /// - `slot` - The index of the `Block` to be proposed, plus the turns missed by the
+    ///   proposers selected before
+    ///
+    /// # Returns
+    /// - `Option<&str>` - The public key of the selected validator, or `None` if nobody has stake
+    pub fn select_proposer(&self, seed: &str, slot: u32) -> Option<&str> {
+        let total: u64 = self.stakes.values().sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}{}", seed, slot));
+        let digest = hasher.finalize();
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        let mut point = u64::from_be_bytes(bytes) % total;
+
+        for (validator, stake) in &self.stakes {
+            if point < *stake {
+                return Some(validator);
+            }
+            point -= stake;
+        }
+        None
+    }
+}
+
+/// The [Proof of Stake (PoS)](https://www.investopedia.com/terms/p/proof-stake-pos.asp) engine,
+/// where each `Block` is proposed and signed by a validator selected in proportion to its balance
+pub struct ProofOfStake {
+    /// The minimum balance an address must hold to be selected as a proposer
+    pub minimum_stake: f64,
+
+    /// The number of seconds between proposals, after which a missed turn is counted
+    pub block_interval: u64,
+}
+impl ProofOfStake {
+    /// Builds the `StakeRegistry` used to select the proposer of the next `Block`
+    pub fn registry(&self, blockchain: &Blockchain) -> StakeRegistry {
+        StakeRegistry::from_ledger(&Ledger::from_chain(&blockchain.chain), self.minimum_stake)
+    }
+
+    /// Gets the public key of the validator scheduled to propose the next `Block` of `blockchain`
+    ///
+    /// # Parameters
+    /// - `blockchain` - The `Blockchain` the `Block` extends
+    /// - `timestamp` - The timestamp of the `Block`, which decides how many turns were missed
+    pub fn next_proposer(&self, blockchain: &Blockchain, timestamp: u64) -> Option<String> {
+        let seed = blockchain.chain.last().map(|block| block.hash.clone()).unwrap_or_default();
+        let elapsed = timestamp.saturating_sub(previous_timestamp(blockchain));
+        self.scheduled_proposer(&self.registry(blockchain), &seed, blockchain.chain.len() as u32, elapsed)
+    }
+
+    /// Gets the public key of the validator scheduled to propose the `Block` at `index`
+    ///
+    /// # Parameters
+    /// - `registry` - The `StakeRegistry` of the `Blockchain` the `Block` extends
+    /// - `seed` - The hash of the `Block` preceding it
+    /// - `index` - The index of the `Block`
+    /// - `elapsed` - The number of seconds between the `Block` and the previous one, which
+    ///   decides how many turns were missed
+    fn scheduled_proposer(&self, registry: &StakeRegistry, seed: &str, index: u32, elapsed: u64) -> Option<String> {
+        let missed = missed_turns(self.block_interval, elapsed);
+        registry.select_proposer(seed, (index as u64 + missed) as u32).map(String::from)
+    }
+}
+impl Consensus for ProofOfStake {
+    fn seal(&self, block: &mut Block, blockchain: &Blockchain, signer: Option<&Ed25519KeyPair>)
+            -> Result<(), &'static str> {
+        let signer = signer.ok_or("Proof of stake blocks must be signed by their proposer")?;
+        let validator = hex::encode(signer.public_key().as_ref());
+        if self.next_proposer(blockchain, block.timestamp).as_deref() != Some(validator.as_str()) {
+            return Err("Signer is not the scheduled proposer of this slot");
+        }
+        check_interval(self.block_interval, block.timestamp, previous_timestamp(blockchain))?;
+
+        block.validator = Some(validator);
+        block.hash = Block::calculate_hash(block);
+        block.signature = signer.sign(block.hash.as_bytes()).as_ref().to_vec();
+        Ok(())
+    }
+
+    fn verify(&self, block: &Block, blockchain: &Blockchain) -> Result<(), &'static str> {
+        let validator = block.validator.as_deref().ok_or("Block is missing its proposer")?;
+        if self.next_proposer(blockchain, block.timestamp).as_deref() != Some(validator) {
+            return Err("Block was not proposed by the scheduled proposer of this slot");
+        }
+        check_interval(self.block_interval, block.timestamp, previous_timestamp(blockchain))?;
+        check_takeover(missed_turns(self.block_interval, elapsed(block, blockchain)), block.timestamp)?;
+        verify_block_signature(block, validator)
+    }
+
+    /// The proposer schedule depends on the `Ledger`, which headers do not carry, so the
+    /// proposer is checked against the stakes of `blockchain` when the header extends its tip.
+    /// Deeper headers follow `Block`s whose stakes are not known yet, so their proposer is
+    /// checked by `verify` once the `Block` bodies are validated
+    fn verify_header(&self, header: &BlockHeader, previous: &BlockHeader, _difficulty: usize,
+                     blockchain: Option<&Blockchain>) -> Result<(), &'static str> {
+        let validator = header.validator.as_deref().ok_or("Block is missing its proposer")?;
+        check_interval(self.block_interval, header.timestamp, previous.timestamp)?;
+        let tip = blockchain.filter(|blockchain| blockchain.chain.last().map(|block| &block.hash) == Some(&previous.hash));
+        if let Some(blockchain) = tip {
+            let elapsed = header.timestamp.saturating_sub(previous.timestamp);
+            let proposer = self.scheduled_proposer(&self.registry(blockchain), &previous.hash, header.index, elapsed);
+            if proposer.as_deref() != Some(validator) {
+                return Err("Block was not proposed by the scheduled proposer of this slot");
+            }
+        }
+        verify_signature(&header.hash, &header.signature, validator)
+    }
+}
+
+/// The [Proof of Authority (PoA)](https://www.investopedia.com/terms/p/proof-authority.asp) engine,
+/// where a fixed set of validators take turns producing `Block`s at a fixed interval
+///
+/// When the scheduled validator misses its turn for `TAKEOVER_INTERVALS` block intervals,
+/// the next validator in turn takes over, so the `Blockchain` keeps growing while a validator
+/// is offline
+///
+/// Intended for test and staging networks, where burning CPU on mining is not wanted
+pub struct ProofOfAuthority {
+    /// The hex-encoded Ed25519 public keys of the validators, in the order they take turns
+    pub validators: Vec<String>,
+
+    /// The minimum number of seconds between consecutive `Block`s
+    pub block_interval: u64,
+}
+impl ProofOfAuthority {
+    /// Gets the public key of the validator scheduled to produce the `Block` at `index`
+    ///
+    /// # Parameters
+    /// - `index` - The index of the `Block`
+    /// - `elapsed` - The number of seconds between the `Block` and the previous one, which
+    ///   decides how many turns were missed
+    pub fn scheduled_authority(&self, index: u32, elapsed: u64) -> Option<&str> {
+        if self.validators.is_empty() {
+            return None;
+        }
+        let turn = index as u64 + missed_turns(self.block_interval, elapsed);
+        Some(&self.validators[(turn % self.validators.len() as u64) as usize])
+    }
+}
+impl Consensus for ProofOfAuthority {
+    fn seal(&self, block: &mut Block, blockchain: &Blockchain, signer: Option<&Ed25519KeyPair>)
+            -> Result<(), &'static str> {
+        let signer = signer.ok_or("Proof of authority blocks must be signed by their validator")?;
+        let validator = hex::encode(signer.public_key().as_ref());
+        if self.scheduled_authority(block.index, elapsed(block, blockchain)) != Some(validator.as_str()) {
+            return Err("Signer is not the scheduled authority of this block");
+        }
+        check_interval(self.block_interval, block.timestamp, previous_timestamp(blockchain))?;
+
+        block.validator = Some(validator);
+        block.hash = Block::calculate_hash(block);
+        block.signature = signer.sign(block.hash.as_bytes()).as_ref().to_vec();
+        Ok(())
+    }
+
+    fn verify(&self, block: &Block, blockchain: &Blockchain) -> Result<(), &'static str> {
+        let validator = block.validator.as_deref().ok_or("Block is missing its validator")?;
+        let elapsed = elapsed(block, blockchain);
+        if self.scheduled_authority(block.index, elapsed) != Some(validator) {
+            return Err("Block was not signed by the scheduled authority");
+        }
+        check_interval(self.block_interval, block.timestamp, previous_timestamp(blockchain))?;
+        check_takeover(missed_turns(self.block_interval, elapsed), block.timestamp)?;
+        verify_block_signature(block, validator)
+    }
+
+    fn verify_header(&self, header: &BlockHeader, previous: &BlockHeader, _difficulty: usize,
+                     _blockchain: Option<&Blockchain>) -> Result<(), &'static str> {
+        let validator = header.validator.as_deref().ok_or("Block is missing its validator")?;
+        let elapsed = header.timestamp.saturating_sub(previous.timestamp);
+        if self.scheduled_authority(header.index, elapsed) != Some(validator) {
+            return Err("Block was not signed by the scheduled authority");
+        }
+        check_interval(self.block_interval, header.timestamp, previous.timestamp)?;
+        verify_signature(&header.hash, &header.signature, validator)
+    }
+}
+
+/// Gets the timestamp of the tip of `blockchain`
+fn previous_timestamp(blockchain: &Blockchain) -> u64 {
+    blockchain.chain.last().map(|block| block.timestamp).unwrap_or(0)
+}
+
+/// Gets the number of seconds between a `Block` and the tip of the `Blockchain` it extends
+fn elapsed(block: &Block, blockchain: &Blockchain) -> u64 {
+    block.timestamp.saturating_sub(previous_timestamp(blockchain))
+}
+
+/// Checks that a `Block` timestamped `timestamp` was produced at least `block_interval`
+/// seconds after the previous one, timestamped `previous`
+fn check_interval(block_interval: u64, timestamp: u64, previous: u64) -> Result<(), &'static str> {
+    if timestamp < previous + block_interval {
+        return Err("Block was produced before the end of the block interval");
+    }
+    Ok(())
+}
+
+/// Counts the turns missed by scheduled validators before a `Block` produced `elapsed`
+/// seconds after the previous one
+fn missed_turns(block_interval: u64, elapsed: u64) -> u64 {
+    elapsed.saturating_sub(block_interval) / (block_interval * TAKEOVER_INTERVALS).max(1)
+}
+
+/// Checks that a `Block` taking over a missed turn is not timestamped in the future, since
+/// the turn is only missed once that time has passed
+fn check_takeover(missed: u64, timestamp: u64) -> Result<(), &'static str> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs();
+    if missed > 0 && timestamp > now {
+        return Err("Block takes over a turn which was not missed yet");
+    }
+    Ok(())
+}
+
+/// Runs a validator for signed `Consensus` engines, which tries to produce a `Block`
+/// every `interval` seconds
+///
+/// Whenever the `Consensus` engine allows `key_pair` to seal the next `Block`, including
+/// when it takes over the turn of a validator which missed it, a `BlockTemplate` paying the
+/// validator is built, sealed, appended to the `Blockchain` and relayed to the peers of `node`
+///
+/// # Parameters
+/// - `node` - The `Node` whose `Blockchain` the `Block`s are appended to
+/// - `key_pair` - The Ed25519 identity key of this validator
+/// - `interval` - The number of seconds between attempts
+pub async fn run_validator(node: Arc<Node>, key_pair: Ed25519KeyPair, interval: u64) {
+    let identifier = hex::encode(key_pair.public_key().as_ref());
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+
+    loop {
+        ticker.tick().await;
+        let sealed = {
+            let blockchain = node.blockchain.read().await;
+            let mut block = BlockTemplate::new(&blockchain, &identifier).to_block();
+            blockchain.consensus.seal(&mut block, &blockchain, Some(&key_pair)).map(|_| block)
+        };
+
+        if let Ok(block) = sealed {
+            let index = block.index;
+            match submit_shared_block(&node.blockchain, block.clone()).await {
+                Ok(()) => {
+                    println!("Produced block #{}", index);
+                    tokio::spawn(relay_block(block, node.clone()));
+                }
+                Err(e) => eprintln!("Failed to produce block #{}: {}", index, e),
+            }
+        }
+    }
+}
+
+/// Verifies that a `Block`'s signature was made over its hash by the given validator
+///
+/// # Parameters
+/// - `block` - The signed `Block`
+/// - `validator` - The hex-encoded Ed25519 public key of the expected signer
+pub fn verify_block_signature(block: &Block, validator: &str) -> Result<(), &'static str> {
+    verify_signature(&block.hash, &block.signature, validator)
+}
+
+fn verify_signature(hash: &str, signature: &[u8], validator: &str) -> Result<(), &'static str> {
+    let public_key = hex::decode(validator).ok_or("Block proposer is not a valid public key")?;
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(hash.as_bytes(), signature)
+        .map_err(|_| "Block signature is invalid")
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::merkle::merkle_root;
+use crate::transaction::Transaction;
+use crate::vm::{execute, Execution, Instruction};
+
+/// The prefix of the address of a deployed `Contract`
+pub const CONTRACT_ADDRESS_PREFIX: &str = "contract:";
+
+/// The maximum number of `Instruction`s of a `Contract`
+pub const MAX_CODE_SIZE: usize = 4096;
+
+/// The maximum gas limit of a single `ContractCall`
+pub const MAX_GAS_LIMIT: u64 = 1_000_000;
+
+/// The gas charged for every `Instruction` of a deployed `Contract`
+pub const GAS_PER_DEPLOYED_INSTRUCTION: u64 = 10;
+
+/// What a `Transaction` asks the VM to do
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum ContractAction {
+    /// Deploys a new `Contract` with the given bytecode, at the `contract_address` of the `Transaction`
+    Deploy(Vec<Instruction>),
+
+    /// Calls a deployed `Contract` with the given arguments
+    Call {
+        /// The address of the `Contract`
+        contract: String,
+
+        /// The arguments of the call
+        input: Vec<i64>,
+    },
+}
+
+/// A `Contract` deployment or call carried by a `Transaction`
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ContractCall {
+    /// What the VM should do
+    pub action: ContractAction,
+
+    /// The maximum gas the `Transaction` may use. It is charged to the sender in full
+    pub gas_limit: u64,
+
+    /// The price the sender pays for each unit of gas, in cryptos
+    pub gas_price: f64,
+}
+impl ContractCall {
+    /// The gas fee charged to the sender, `gas_limit` times `gas_price`
+    pub fn gas_fee(&self) -> f64 {
+        self.gas_limit as f64 * self.gas_price
+    }
+
+    /// Checks the limits every `ContractCall` must respect to be included in a `Block`
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.gas_limit > MAX_GAS_LIMIT {
+            return Err("Contract gas limit exceeds the maximum");
+        }
+        if !self.gas_price.is_finite() || self.gas_price < 0.0 {
+            return Err("Contract gas price must not be negative");
+        }
+        match &self.action {
+            ContractAction::Deploy(code) if code.len() > MAX_CODE_SIZE => Err("Contract code exceeds the maximum size"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A deployed program and the storage it persists between calls
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Contract {
+    /// The bytecode of this `Contract`
+    pub code: Vec<Instruction>,
+
+    /// The values this `Contract` stored, by key
+    pub storage: BTreeMap<i64, i64>,
+}
+impl Contract {
+    /// Generates the SHA-256 hash of this `Contract`'s code and storage
+    pub fn calculate_hash(&self, address: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(address.as_bytes());
+        hasher.update(serde_json::to_vec(self).expect("Contracts are always serializable"));
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// Gets the address a `Transaction` deploying a `Contract` deploys it at
+pub fn contract_address(transaction: &Transaction) -> String {
+    format!("{}{}", CONTRACT_ADDRESS_PREFIX, transaction.calculate_hash())
+}
+
+/// The `ContractState` holds every deployed `Contract`, as of the tip of a `Blockchain`
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct ContractState {
+    /// The deployed `Contract`s, by address
+    pub contracts: BTreeMap<String, Contract>,
+}
+impl ContractState {
+    /// Computes the state root committed to by a `Block`: the Merkle root of the hashes
+    /// of every `Contract`, ordered by address
+    pub fn root(&self) -> String {
+        let hashes: Vec<String> = self.contracts
+            .iter()
+            .map(|(address, contract)| contract.calculate_hash(address))
+            .collect();
+        merkle_root(&hashes)
+    }
+
+    /// Runs the `ContractCall` of a `Transaction`, if it carries one
+    ///
+    /// Failed deployments and calls leave the `ContractState` unchanged, but are still
+    /// included in the `Block` so their gas is paid for
+    ///
+    /// # Returns
+    /// - `Option<Execution>` - The outcome of the `ContractCall`, or `None` if there is none
+    pub fn apply(&mut self, transaction: &Transaction) -> Option<Execution> {
+        let call = transaction.contract.as_ref()?;
+        let execution = match &call.action {
+            ContractAction::Deploy(code) => {
+                let gas_used = code.len() as u64 * GAS_PER_DEPLOYED_INSTRUCTION;
+                if gas_used > call.gas_limit {
+                    Execution { gas_used: call.gas_limit, result: Err(String::from("Out of gas")) }
+                } else {
+                    self.contracts.insert(contract_address(transaction), Contract { code: code.clone(), storage: BTreeMap::new() });
+                    Execution { gas_used, result: Ok(None) }
+                }
+            }
+            ContractAction::Call { contract, input } => match self.contracts.get_mut(contract) {
+                Some(contract) => execute(&contract.code, &mut contract.storage, input, call.gas_limit),
+                None => Execution { gas_used: 0, result: Err(String::from("No contract at this address")) },
+            },
+        };
+        Some(execution)
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use crate::block::Block;
+use crate::transaction::Transaction;
+
+/// The number of `Event`s buffered for each subscriber before the slowest ones miss some
+pub const EVENT_CAPACITY: usize = 1024;
+
+/// Something that happened to a `Blockchain` which subscribers are notified about
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum Event {
+    /// A `Block` was appended to the `Blockchain`
+    NewBlock(Block),
+
+    /// A `Transaction` was added to the `Mempool`
+    NewTransaction(Transaction),
+}
+impl Event {
+    /// Checks whether an address sends or receives funds in this `Event`
+    pub fn touches(&self, address: &str) -> bool {
+        let involves = |transaction: &Transaction| {
+            transaction.sender.as_deref() == Some(address) || transaction.recipient.as_deref() == Some(address)
+        };
+        match self {
+            Event::NewBlock(block) => involves(&block.transaction) || block.coinbase.iter().any(involves),
+            Event::NewTransaction(transaction) => involves(transaction),
+        }
+    }
+}
+
+/// A kind of `Event` a client can subscribe to
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Topic {
+    /// Every new `Block`
+    NewBlocks,
+
+    /// Every new `Mempool` `Transaction`
+    NewTransactions,
+
+    /// Every new `Block` or `Mempool` `Transaction` sending funds to or from an address
+    Address(String),
+}
+impl Topic {
+    /// Checks whether an `Event` belongs to this `Topic`
+    pub fn matches(&self, event: &Event) -> bool {
+        match (self, event) {
+            (Topic::NewBlocks, Event::NewBlock(_)) => true,
+            (Topic::NewTransactions, Event::NewTransaction(_)) => true,
+            (Topic::Address(address), event) => event.touches(address),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+/// Encodes bytes, such as a public key or signature, as a lowercase hexadecimal `String`
+pub fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Decodes a hexadecimal `String` back into bytes
+///
+/// # Returns
+/// - `Option<Vec<u8>>` - The decoded bytes, or `None` if `hex` is not valid hexadecimal
+pub fn decode(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect()
+}
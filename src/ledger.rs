@@ -0,0 +1,178 @@
+use std::collections::{BTreeMap, HashMap};
+use crate::block::Block;
+use crate::script::SCRIPT_ADDRESS_PREFIX;
+use crate::token::{is_key_address, is_valid_amount, is_valid_symbol, Token, TokenOperation};
+use crate::transaction::Transaction;
+
+/// A `Ledger` holds the balance of every address, derived by replaying the
+/// `Transaction`s and coinbase payouts of a chain of `Block`s
+#[derive(Debug, Default, Clone)]
+pub struct Ledger {
+    /// The balance of each address, in cryptos
+    pub balances: HashMap<String, f64>,
+
+    /// The issued `Token`s, by symbol
+    pub tokens: BTreeMap<String, Token>,
+
+    /// The balance of each `Token` held by each address, by address and symbol
+    pub token_balances: HashMap<(String, String), f64>,
+}
+impl Ledger {
+    /// Builds the `Ledger` of a chain by replaying all of its `Block`s in order
+    ///
+    /// # Parameters
+    /// - `chain` - The `Block`s of a `Blockchain`, starting from the genesis `Block`
+    pub fn from_chain(chain: &[Block]) -> Self {
+        let mut ledger = Ledger::default();
+        for block in chain {
+            ledger.apply(block);
+        }
+        ledger
+    }
+
+    /// Applies the coinbase payouts and the `Transaction` of a `Block` to this `Ledger`
+    ///
+    /// The sender is debited the amount, fee and gas fee of the `Transaction`, while the
+    /// recipient is credited the amount
+    pub fn apply(&mut self, block: &Block) {
+        for payout in &block.coinbase {
+            if let Some(recipient) = &payout.recipient {
+                *self.balances.entry(recipient.clone()).or_insert(0.0) += payout.amount;
+            }
+        }
+
+        let transaction = &block.transaction;
+        let amount = if transaction.asset.is_some() { 0.0 } else { transaction.amount };
+        if let Some(sender) = &transaction.sender {
+            *self.balances.entry(sender.clone()).or_insert(0.0) -= transaction.cost();
+        }
+        if let Some(recipient) = &transaction.recipient {
+            *self.balances.entry(recipient.clone()).or_insert(0.0) += amount;
+        }
+
+        if let (Some(symbol), Some(sender), Some(recipient)) = (&transaction.asset, &transaction.sender, &transaction.recipient) {
+            self.credit_token(sender, symbol, -transaction.amount);
+            self.credit_token(recipient, symbol, transaction.amount);
+        }
+        match &transaction.token {
+            Some(TokenOperation::Issue { symbol, supply, issuer }) => {
+                self.tokens.insert(symbol.clone(), Token { symbol: symbol.clone(), issuer: issuer.clone(), supply: *supply });
+                self.credit_token(issuer, symbol, *supply);
+            }
+            Some(TokenOperation::Mint { symbol, amount }) => {
+                let Some(token) = self.tokens.get_mut(symbol) else { return };
+                token.supply += amount;
+                let recipient = transaction.recipient.clone().unwrap_or(token.issuer.clone());
+                self.credit_token(&recipient, symbol, *amount);
+            }
+            Some(TokenOperation::Burn { symbol, amount }) => {
+                let Some(token) = self.tokens.get_mut(symbol) else { return };
+                token.supply -= amount;
+                let issuer = token.issuer.clone();
+                self.credit_token(&issuer, symbol, -amount);
+            }
+            None => {}
+        }
+    }
+
+    fn credit_token(&mut self, address: &str, symbol: &str, amount: f64) {
+        *self.token_balances.entry((address.to_string(), symbol.to_string())).or_insert(0.0) += amount;
+    }
+
+    /// Checks that a `Transaction` follows the rules of the `Token`s it moves or operates on,
+    /// against the balances of this `Ledger`
+    ///
+    /// `Token` transfers must come from a key address which signed them with
+    /// `Transaction::authorize`, or from a script address, and may not exceed the sender's
+    /// balance. `TokenOperation`s must be signed by the issuer of the `Token`
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if the `Transaction` is valid, or an `Err(&str)`
+    ///   describing the broken rule
+    pub fn check_tokens(&self, transaction: &Transaction) -> Result<(), &'static str> {
+        if let Some(symbol) = &transaction.asset {
+            if !self.tokens.contains_key(symbol) {
+                return Err("Unknown token");
+            }
+            if !is_valid_amount(transaction.amount) || transaction.recipient.is_none() {
+                return Err("Invalid token transfer");
+            }
+            let sender = transaction.sender.as_deref().ok_or("Invalid token transfer")?;
+            let authorized = if is_key_address(sender) {
+                transaction.is_authorized_by(sender)
+            } else {
+                sender.starts_with(SCRIPT_ADDRESS_PREFIX)
+            };
+            if !authorized {
+                return Err("Token transfer is not authorized by the sender");
+            }
+            if self.token_balance(sender, symbol) < transaction.amount {
+                return Err("Insufficient token balance");
+            }
+        }
+
+        match &transaction.token {
+            Some(TokenOperation::Issue { symbol, supply, issuer }) => {
+                if !is_valid_symbol(symbol) {
+                    return Err("Invalid token symbol");
+                }
+                if self.tokens.contains_key(symbol) {
+                    return Err("Token symbol is already issued");
+                }
+                if !supply.is_finite() || *supply < 0.0 || !is_key_address(issuer) {
+                    return Err("Invalid token issuance");
+                }
+                if !transaction.is_authorized_by(issuer) {
+                    return Err("Token operation is not signed by the issuer");
+                }
+            }
+            Some(TokenOperation::Mint { symbol, amount }) | Some(TokenOperation::Burn { symbol, amount }) => {
+                let token = self.tokens.get(symbol).ok_or("Unknown token")?;
+                if !is_valid_amount(*amount) {
+                    return Err("Invalid token amount");
+                }
+                if !transaction.is_authorized_by(&token.issuer) {
+                    return Err("Token operation is not signed by the issuer");
+                }
+                let burn = matches!(transaction.token, Some(TokenOperation::Burn { .. }));
+                if burn && self.token_balance(&token.issuer, symbol) < *amount {
+                    return Err("Insufficient token balance");
+                }
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Checks that the sender of a `Transaction` can pay its `cost` out of its balance
+    ///
+    /// A `Transaction` without a sender may neither pay an amount nor a fee, since nobody
+    /// would be debited for them
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if the `Transaction` is funded, or an `Err(&str)`
+    ///   describing the broken rule
+    pub fn check_balance(&self, transaction: &Transaction) -> Result<(), &'static str> {
+        let valid = |value: f64| value.is_finite() && value >= 0.0;
+        if !valid(transaction.amount) || !valid(transaction.fee.unwrap_or(0.0)) {
+            return Err("Transaction amount and fee must not be negative");
+        }
+        match &transaction.sender {
+            Some(sender) if self.balance(sender) < transaction.cost() => {
+                Err("Insufficient balance to cover the amount and fees")
+            }
+            None if transaction.cost() > 0.0 => Err("Transaction without a sender cannot pay an amount or fee"),
+            _ => Ok(()),
+        }
+    }
+
+    /// Gets the balance of an address, or `0.0` if it never received anything
+    pub fn balance(&self, address: &str) -> f64 {
+        self.balances.get(address).copied().unwrap_or(0.0)
+    }
+
+    /// Gets the balance of a `Token` held by an address, or `0.0` if it never received any
+    pub fn token_balance(&self, address: &str, symbol: &str) -> f64 {
+        self.token_balances.get(&(address.to_string(), symbol.to_string())).copied().unwrap_or(0.0)
+    }
+}
@@ -4,4 +4,32 @@ pub mod tests;
 pub mod transaction;
 pub mod miner;
 pub mod network;
-pub mod arc_string;
\ No newline at end of file
+pub mod arc_string;
+pub mod mempool;
+pub mod template;
+pub mod rpc;
+pub mod pool;
+pub mod consensus;
+pub mod hex;
+pub mod ledger;
+pub mod chain_spec;
+pub mod merkle;
+pub mod sync;
+pub mod light_client;
+pub mod compact;
+pub mod peer;
+pub mod transport;
+pub mod address_book;
+pub mod wire;
+pub mod version;
+pub mod events;
+pub mod websocket;
+pub mod session;
+pub mod storage;
+pub mod script;
+pub mod multisig;
+pub mod vm;
+pub mod contract;
+pub mod token;
+pub mod nft;
+pub mod notary;
\ No newline at end of file
@@ -0,0 +1,147 @@
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+use crate::block::BlockHeader;
+use crate::chain_spec::ChainSpec;
+use crate::consensus::Consensus;
+use crate::merkle::MerkleProof;
+use crate::network::{send_request_as, Message};
+use crate::sync::{block_locator, validate_headers};
+use crate::transaction::Transaction;
+use crate::version::Version;
+
+/// A `LightClient` performs [Simplified Payment Verification (SPV)](https://bitcoinwiki.org/wiki/simplified-payment-verification)
+///
+/// It stores only `BlockHeader`s, whose linkage and seal it verifies, and relies on
+/// `MerkleProof`s from full nodes to check that its wallet's `Transaction`s were included
+pub struct LightClient {
+    /// The verified `BlockHeader`s, starting from the genesis `Block`
+    pub headers: Vec<BlockHeader>,
+
+    /// The difficulty target of the network
+    pub difficulty: usize,
+
+    /// The `Consensus` engine of the network, which the `BlockHeader`s are sealed with
+    pub consensus: Arc<dyn Consensus>,
+
+    /// The id of the network
+    pub network_id: String,
+}
+impl LightClient {
+    /// Constructs a new `LightClient` knowing only the genesis `Block` of a network
+    ///
+    /// # Parameters
+    /// - `spec` - The `ChainSpec` of the network
+    pub fn new(spec: &ChainSpec) -> Self {
+        LightClient {
+            headers: vec![spec.genesis_block().header()],
+            difficulty: spec.difficulty,
+            consensus: spec.consensus.build(),
+            network_id: spec.network_id.clone(),
+        }
+    }
+
+    /// Builds a block locator from the stored `BlockHeader`s, newest first
+    pub fn block_locator(&self) -> Vec<String> {
+        block_locator(&self.headers, |header| &header.hash)
+    }
+
+    /// Validates `BlockHeader`s received from a full node and appends them to this `LightClient`
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if the headers extend the stored header chain, or an
+    ///   `Err(&str)` if they are invalid, in which case none of them are stored
+    pub fn append_headers(&mut self, headers: Vec<BlockHeader>) -> Result<(), &'static str> {
+        let tip = self.headers.last().expect("Light client has a genesis header");
+        validate_headers(tip, &headers, self.consensus.as_ref(), self.difficulty, None)?;
+        self.headers.extend(headers);
+        Ok(())
+    }
+
+    /// Gets the `Version` this `LightClient` announces to full nodes
+    pub fn version(&self) -> Version {
+        Version::new(&self.network_id, self.headers.len() as u64 - 1, 0)
+    }
+
+    /// Verifies that a `Transaction` is included in the `Block` at `index`
+    ///
+    /// # Parameters
+    /// - `index` - The index of the `Block` the full node claims includes the `Transaction`
+    /// - `proof` - The `MerkleProof` of the `Transaction`'s hash
+    pub fn verify_inclusion(&self, index: u32, proof: &MerkleProof) -> bool {
+        self.headers
+            .get(index as usize)
+            .is_some_and(|header| proof.verify(&header.merkle_root))
+    }
+
+    /// Requests and verifies new `BlockHeader`s from a full node until this `LightClient` is up to date
+    ///
+    /// # Parameters
+    /// - `peer` - The `address:port` of a full node
+    pub async fn sync_headers(&mut self, peer: &str) -> Result<(), Error> {
+        loop {
+            let headers = match send_request_as(peer, &self.version(), &Message::GetHeaders(self.block_locator())).await? {
+                Message::Headers(headers) => headers,
+                _ => return Err(Error::new(ErrorKind::InvalidData, "Expected headers")),
+            };
+            if headers.is_empty() {
+                return Ok(());
+            }
+            self.append_headers(headers).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        }
+    }
+
+    /// Requests the `Transaction`s of an address from a full node and verifies the inclusion of each
+    ///
+    /// Every `Transaction` is hashed locally and must be sent or received by `address`, so the
+    /// node cannot pass off the proofs of unrelated `Transaction`s
+    ///
+    /// # Parameters
+    /// - `peer` - The `address:port` of a full node
+    /// - `address` - The wallet address whose `Transaction`s are checked
+    ///
+    /// # Returns
+    /// - `Result<Vec<(String, u32)>, Error>` - The hash of every verified `Transaction`
+    ///   and the index of the `Block` including it
+    pub async fn verify_transactions(&self, peer: &str, address: &str) -> Result<Vec<(String, u32)>, Error> {
+        let transactions = match send_request_as(peer, &self.version(), &Message::GetTransactions(address.to_string())).await? {
+            Message::Transactions(transactions) => transactions,
+            _ => return Err(Error::new(ErrorKind::InvalidData, "Expected transactions")),
+        };
+        if !transactions.iter().all(|transaction| transaction.involves(address)) {
+            return Err(Error::new(ErrorKind::InvalidData, "Transaction does not involve the wallet"));
+        }
+
+        let mut verified = Vec::new();
+        for hash in transactions.iter().map(Transaction::calculate_hash) {
+            match send_request_as(peer, &self.version(), &Message::GetMerkleProof(hash.clone())).await? {
+                Message::MerkleProof(Some((index, proof))) if proof.leaf == hash && self.verify_inclusion(index, &proof) => {
+                    verified.push((hash, index));
+                }
+                _ => return Err(Error::new(ErrorKind::InvalidData, "Invalid proof of inclusion")),
+            }
+        }
+        Ok(verified)
+    }
+
+    /// Requests the proof that a document hash was notarized from a full node and verifies it
+    /// against the synced `BlockHeader`s
+    ///
+    /// # Parameters
+    /// - `peer` - The `address:port` of a full node
+    /// - `document_hash` - The hex encoded SHA-256 hash of the document
+    ///
+    /// # Returns
+    /// - `Result<Option<(u32, u64)>, Error>` - The index and timestamp of the `Block` the
+    ///   document hash was recorded in, or `None` if the node does not know of it
+    pub async fn verify_notarization(&self, peer: &str, document_hash: &str) -> Result<Option<(u32, u64)>, Error> {
+        match send_request_as(peer, &self.version(), &Message::GetNotarization(document_hash.to_string())).await? {
+            Message::Notarization(None) => Ok(None),
+            Message::Notarization(Some(proof)) if self.headers
+                .get(proof.block_index as usize)
+                .is_some_and(|header| proof.verify(document_hash, header)) => {
+                Ok(Some((proof.block_index, proof.timestamp)))
+            }
+            _ => Err(Error::new(ErrorKind::InvalidData, "Invalid notarization proof")),
+        }
+    }
+}
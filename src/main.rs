@@ -1,21 +1,140 @@
+use std::env;
 use std::error::Error;
+use std::fs;
 use std::sync::Arc;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair};
 use tokio::runtime::Runtime;
-use blockchain_network::blockchain::Blockchain;
-use blockchain_network::network::{start_server};
+use blockchain_network::chain_spec::ChainSpec;
+use blockchain_network::consensus::run_validator;
+use blockchain_network::hex;
+use blockchain_network::address_book::AddressBook;
+use blockchain_network::network::{address, announce_shutdown, maintain_peers, port, rpc_port, start_server, Node, PEER_MAINTENANCE_INTERVAL};
+use blockchain_network::pool::{start_pool_server, PayoutScheme, Pool};
+use blockchain_network::rpc::start_rpc_server;
+use blockchain_network::storage;
+use blockchain_network::sync::initial_block_download;
+use blockchain_network::transport::{identity, set_identity, set_json_wire};
+use blockchain_network::version::set_network_id;
+
+/// Gets the value following a command line flag, such as `--chain-spec <path>`
+fn arg_value(flag: &str) -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1).cloned())
+}
+
+/// Shuts the node down once the process receives Ctrl-C or, on Unix, `SIGTERM`
+async fn shutdown_on_signal(node: Arc<Node>) {
+    #[cfg(unix)]
+    {
+        let mut terminate = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(terminate) => terminate,
+            Err(_) => return,
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+
+    println!("Shutting down");
+    node.shutdown();
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
+    if let Some(path) = arg_value("--generate-key") {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| "Failed to generate key")?;
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).map_err(|e| e.to_string())?;
+        fs::write(&path, hex::encode(pkcs8.as_ref()))?;
+        println!("Wrote key to {} with public key {}", path, hex::encode(key_pair.public_key().as_ref()));
+        return Ok(());
+    }
+
+    if let Some(path) = arg_value("--node-key") {
+        let pkcs8 = hex::decode(fs::read_to_string(path)?.trim()).ok_or("Invalid node key")?;
+        set_identity(Ed25519KeyPair::from_pkcs8(&pkcs8).map_err(|e| e.to_string())?)?;
+    }
+    println!("Node identity: {}", hex::encode(identity().public_key().as_ref()));
+    set_json_wire(env::args().any(|arg| arg == "--json-wire"));
+
     let rt = Runtime::new()?;
 
     rt.block_on(async {
+        let spec = match arg_value("--chain-spec") {
+            Some(path) => ChainSpec::load(path)?,
+            None => ChainSpec::default(),
+        };
+        set_network_id(spec.network_id.clone())?;
+
+        if env::args().any(|arg| arg == "--pool") {
+            let pool = Arc::new(
+                tokio::sync::Mutex::new(
+                    Pool::new("pool", 2, PayoutScheme::Pplns(100))
+                )
+            );
+
+            start_pool_server(pool).await?;
+            return Ok(());
+        }
+
+        let block_interval = spec.consensus.block_interval();
+        let seeds = spec.seeds.clone();
+        let data_dir = arg_value("--data-dir").unwrap_or(String::from("data"));
         let blockchain = Arc::new(
-            tokio::sync::Mutex::new(
-                Blockchain::new(4)
+            tokio::sync::RwLock::new(
+                storage::load(spec, &data_dir)?
             )
         );
-        
-        start_server(blockchain).await?;
-        
+        println!("Genesis block: {}", blockchain.read().await.chain[0].hash);
+        println!("Loaded {} blocks from {}", blockchain.read().await.chain.len(), data_dir);
+        tokio::spawn(storage::save_on_new_blocks(blockchain.clone(), data_dir.clone().into()));
+
+        if let Some(peers) = arg_value("--sync-from") {
+            let peers: Vec<String> = peers.split(',').map(String::from).collect();
+            let blockchain = blockchain.clone();
+            tokio::spawn(async move {
+                match initial_block_download(blockchain, &peers).await {
+                    Ok(downloaded) => println!("Initial block download finished with {} new blocks", downloaded),
+                    Err(e) => eprintln!("Initial block download failed: {}", e),
+                }
+            });
+        }
+
+        let listen_port = match arg_value("--port") {
+            Some(listen_port) => listen_port.parse()?,
+            None => **port,
+        };
+        let listen_rpc_port = match arg_value("--rpc-port") {
+            Some(listen_rpc_port) => listen_rpc_port.parse()?,
+            None => **rpc_port,
+        };
+
+        let mut address_book = AddressBook::load(arg_value("--address-book").unwrap_or(String::from("address_book.json")))?;
+        let peers: Vec<String> = arg_value("--peers")
+            .map(|peers| peers.split(',').map(String::from).collect())
+            .unwrap_or_default();
+        for seed in seeds.iter().chain(&peers) {
+            address_book.add_peer(seed);
+        }
+        let node = Arc::new(Node::new(blockchain.clone(), format!("{}:{}", *address, listen_port), address_book));
+
+        if let (Some(path), Some(interval)) = (arg_value("--validator-key"), block_interval) {
+            let pkcs8 = hex::decode(fs::read_to_string(path)?.trim()).ok_or("Invalid validator key")?;
+            let key_pair = Ed25519KeyPair::from_pkcs8(&pkcs8).map_err(|e| e.to_string())?;
+            tokio::spawn(run_validator(node.clone(), key_pair, interval));
+        }
+
+        tokio::spawn(start_rpc_server(blockchain, listen_rpc_port));
+        tokio::spawn(maintain_peers(node.clone(), PEER_MAINTENANCE_INTERVAL));
+        tokio::spawn(shutdown_on_signal(node.clone()));
+        start_server(node.clone()).await?;
+
+        announce_shutdown(&node).await;
+        storage::save(&*node.blockchain.read().await, &data_dir)?;
+        println!("Shutdown complete");
         Ok(())
     })
 }
\ No newline at end of file
@@ -0,0 +1,148 @@
+use crate::transaction::Transaction;
+
+/// The minimum fee, per byte of its serialized size, a `Transaction` must pay to be relayed
+pub const DEFAULT_MIN_RELAY_FEE_PER_BYTE: f64 = 0.001;
+
+/// The maximum number of pending `Transaction`s, so peers cannot exhaust the node's memory
+pub const DEFAULT_MAX_TRANSACTIONS: usize = 5000;
+
+/// A `Mempool` holds the `Transaction`s that have been announced to the node
+/// but have not yet been included in a `Block` of the `Blockchain`
+#[derive(Debug, Clone)]
+pub struct Mempool {
+    /// The pending `Transaction`s, in the order they were received
+    pub transactions: Vec<Transaction>,
+
+    /// The minimum fee per byte a `Transaction` must pay to be accepted
+    pub min_relay_fee_per_byte: f64,
+
+    /// The maximum number of pending `Transaction`s, beyond which the one paying the lowest
+    /// fee per byte is evicted
+    pub max_transactions: usize,
+}
+impl Default for Mempool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Mempool {
+    /// Constructs a new, empty `Mempool`
+    pub fn new() -> Self {
+        Mempool {
+            transactions: Vec::new(),
+            min_relay_fee_per_byte: DEFAULT_MIN_RELAY_FEE_PER_BYTE,
+            max_transactions: DEFAULT_MAX_TRANSACTIONS,
+        }
+    }
+
+    /// Adds a `Transaction` to this `Mempool`
+    ///
+    /// A `Transaction` conflicting with a pending one, because it has the same sender and
+    /// `transaction_id`, replaces it if its fee is higher by at least the minimum relay fee
+    /// of the replacement, so a sender can bump the fee of a stuck `Transaction`. Once this
+    /// `Mempool` holds `max_transactions`, a new `Transaction` evicts the pending one paying
+    /// the lowest fee per byte if it pays more per byte than it
+    ///
+    /// # Parameters
+    /// - `transaction` - The pending `Transaction` to be added
+    ///
+    /// # Returns
+    /// - `bool` - `true` if the `Transaction` was added, `false` if it was already pending,
+    ///   pays less than the minimum relay fee, does not pay enough to replace the
+    ///   `Transaction` it conflicts with, or this `Mempool` is full of `Transaction`s paying
+    ///   at least as much per byte
+    pub fn add(&mut self, transaction: Transaction) -> bool {
+        let hash = transaction.calculate_hash();
+        if self.contains(&hash) {
+            return false;
+        }
+        let fee = transaction.fee.unwrap_or(0.0);
+        let minimum_fee = self.minimum_fee(&transaction);
+        if fee < minimum_fee {
+            return false;
+        }
+
+        match self.transactions.iter().position(|pending| pending.conflicts_with(&transaction)) {
+            Some(position) if fee >= self.transactions[position].fee.unwrap_or(0.0) + minimum_fee => {
+                self.transactions[position] = transaction;
+                true
+            }
+            Some(_) => false,
+            None if self.transactions.len() < self.max_transactions => {
+                self.transactions.push(transaction);
+                true
+            }
+            None => match self.lowest_fee_rate() {
+                Some(position) if fee_rate(&transaction) > fee_rate(&self.transactions[position]) => {
+                    self.transactions.remove(position);
+                    self.transactions.push(transaction);
+                    true
+                }
+                _ => false,
+            },
+        }
+    }
+
+    /// Gets the position of the pending `Transaction` paying the lowest fee per byte
+    fn lowest_fee_rate(&self) -> Option<usize> {
+        self.transactions
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| fee_rate(a).total_cmp(&fee_rate(b)))
+            .map(|(position, _)| position)
+    }
+
+    /// Gets the minimum fee a `Transaction` must pay to be relayed, based on its size
+    pub fn minimum_fee(&self, transaction: &Transaction) -> f64 {
+        transaction.size() as f64 * self.min_relay_fee_per_byte
+    }
+
+    /// Checks whether a `Transaction` with the given hash is pending
+    pub fn contains(&self, hash: &str) -> bool {
+        self.transactions.iter().any(|tx| tx.calculate_hash() == hash)
+    }
+
+    /// Removes the `Transaction` with the given hash from this `Mempool`, if present
+    pub fn remove(&mut self, hash: &str) {
+        self.transactions.retain(|tx| tx.calculate_hash() != hash);
+    }
+
+    /// Selects the pending `Transaction` that a new `Block` should include
+    ///
+    /// # Returns
+    /// - `Option<&Transaction>` - The pending `Transaction` paying the highest fee,
+    ///   or `None` if this `Mempool` is empty
+    pub fn select(&self) -> Option<&Transaction> {
+        self.select_within(usize::MAX)
+    }
+
+    /// Selects the pending `Transaction` paying the highest fee among those no larger than
+    /// `max_size` bytes
+    ///
+    /// # Returns
+    /// - `Option<&Transaction>` - The selected `Transaction`, or `None` if none fits
+    pub fn select_within(&self, max_size: usize) -> Option<&Transaction> {
+        self.transactions
+            .iter()
+            .filter(|tx| tx.size() <= max_size)
+            .fold(None, |best: Option<&Transaction>, tx| match best {
+                Some(best) if best.fee.unwrap_or(0.0) >= tx.fee.unwrap_or(0.0) => Some(best),
+                _ => Some(tx),
+            })
+    }
+
+    /// The number of pending `Transaction`s
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Whether there are no pending `Transaction`s
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+}
+
+/// The fee a `Transaction` pays per byte of its serialized size
+fn fee_rate(transaction: &Transaction) -> f64 {
+    transaction.fee.unwrap_or(0.0) / transaction.size() as f64
+}
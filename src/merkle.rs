@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The byte hashed in front of a leaf, so a leaf hash never equals the hash of an internal node
+const LEAF_PREFIX: u8 = 0;
+
+/// The byte hashed in front of the two children of an internal node
+const NODE_PREFIX: u8 = 1;
+
+/// Computes the [Merkle root](https://en.wikipedia.org/wiki/Merkle_tree) of a list of hashes
+///
+/// Every leaf is hashed on its own, then pairs of hashes are repeatedly hashed together
+/// until a single hash remains. When a level has an odd number of hashes, the last one is
+/// carried up to the next level unchanged, so no two lists of leaves share a root
+///
+/// # Parameters
+/// - `hashes` - The hexadecimal hashes of the leaves, such as `Transaction` hashes
+///
+/// # Returns
+/// - `String` - The hexadecimal Merkle root, or the hash of nothing if `hashes` is empty
+pub fn merkle_root(hashes: &[String]) -> String {
+    if hashes.is_empty() {
+        return format!("{:x}", Sha256::digest(b""));
+    }
+
+    let mut level: Vec<String> = hashes.iter().map(|leaf| hash_leaf(leaf)).collect();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.remove(0)
+}
+
+/// Hashes a leaf into the node it is stored as in the tree
+pub fn hash_leaf(leaf: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(leaf.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hashes two child hashes into their parent hash
+pub fn hash_pair(left: &str, right: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn next_level(level: &[String]) -> Vec<String> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_pair(left, right),
+            [single] => single.clone(),
+            _ => unreachable!("Chunks hold one or two hashes"),
+        })
+        .collect()
+}
+
+/// A proof that a leaf hash is included in a Merkle tree, without knowing the other leaves
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct MerkleProof {
+    /// The hash whose inclusion is proven, such as a `Transaction` hash
+    pub leaf: String,
+
+    /// The position of the leaf among the leaves of the tree
+    pub index: usize,
+
+    /// The sibling hash at each level of the tree, from the leaves up to the root, or `None`
+    /// where the node was the last of an odd level and carried up unchanged
+    pub siblings: Vec<Option<String>>,
+}
+impl MerkleProof {
+    /// Builds the proof that the leaf at `index` is included in the tree of `hashes`
+    ///
+    /// # Returns
+    /// - `Option<Self>` - The `MerkleProof`, or `None` if `index` is out of bounds
+    pub fn new(hashes: &[String], index: usize) -> Option<Self> {
+        let leaf = hashes.get(index)?.clone();
+        let mut siblings = Vec::new();
+        let mut level: Vec<String> = hashes.iter().map(|leaf| hash_leaf(leaf)).collect();
+        let mut position = index;
+        while level.len() > 1 {
+            siblings.push(level.get(position ^ 1).cloned());
+            level = next_level(&level);
+            position /= 2;
+        }
+        Some(MerkleProof { leaf, index, siblings })
+    }
+
+    /// Verifies this proof against a Merkle root, such as the one stored in a `BlockHeader`
+    pub fn verify(&self, root: &str) -> bool {
+        let mut hash = hash_leaf(&self.leaf);
+        let mut position = self.index;
+        for sibling in &self.siblings {
+            hash = match sibling {
+                // Only the last node of a level, which is at an even position, has no sibling
+                None if position.is_multiple_of(2) => hash,
+                None => return false,
+                Some(sibling) if position.is_multiple_of(2) => hash_pair(&hash, sibling),
+                Some(sibling) => hash_pair(sibling, &hash),
+            };
+            position /= 2;
+        }
+        position == 0 && hash == root
+    }
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 use crate::block::Block;
-use crate::blockchain::Blockchain;
+use crate::blockchain::{BlockError, Blockchain};
+use crate::template::BlockTemplate;
+use crate::transaction::Transaction;
 
 /// A modular representation of a `Miner`, which generally refers to
 /// a person or their computing resources - a GPU (Graphics Processing Unit) or
@@ -37,56 +39,68 @@ impl Miner {
     /// # Parameters
     /// - `block` - The `Block` instance, as a mutable reference, to be mined
     /// - `difficulty` - The difficulty target for the `hash`,
-    ///    representing how difficult it is for miners to
-    ///    add new `Block`s to the `Blockchain`. 
+    ///   representing how difficult it is for miners to
+    ///   add new `Block`s to the `Blockchain`. 
     ///    - Represented in this prototype as the number of leading zeros required 
     ///      in the hash to consider the block valid.
     pub fn proof_of_work(block: &mut Block, difficulty: usize) {
         let target = "0".repeat(difficulty);
+        let mut header = block.header();
         while !block.hash.starts_with(&target) {
             block.nonce += 1;
-            block.hash = Block::calculate_hash(block);
+            header.nonce = block.nonce;
+            block.hash = header.calculate_hash();
         }
     }
     
+    /// Mines a `Block` from a `BlockTemplate` handed out by a node
+    ///
+    /// Used by `Miner` processes running separately from the node: the returned `Block`
+    /// is ready to be sent back to the node with a `SubmitBlock` message
+    ///
+    /// # Parameters
+    /// - `template` - The `BlockTemplate` describing the `Block` to be mined
+    ///
+    /// # Returns
+    /// - `Block` - The mined `Block`, whose hash meets the template's difficulty target
+    pub fn mine_template(template: &BlockTemplate) -> Block {
+        let mut block = template.to_block();
+        Self::proof_of_work(&mut block, template.difficulty);
+        block
+    }
+
     /// Mines a new `Block` and adds it to the `Blockchain`
     /// 
-    /// Performs the [Proof of Work (PoW)](https://www.investopedia.com/terms/p/proof-work.asp)
-    /// algorithm to mine the given `Block`
-    /// and then attempts to add it to the given `Blockchain` if this `Miner`
-    /// has enough crypto balance to do so
+    /// Builds the `Block` on top of the tip of the given `Blockchain` with a coinbase paying
+    /// this `Miner` the block reward plus the fees of the `Block`'s `Transaction`, performs the
+    /// [Proof of Work (PoW)](https://www.investopedia.com/terms/p/proof-work.asp)
+    /// algorithm and submits it, so its `Transaction` must be authorized and funded by its sender
     /// 
     /// # Parameters
     /// - `blockchain` - A mutable reference to the `Blockchain`, 
-    ///    where the mined `Block` will be added
-    /// - `block` - The `Block` to be mined and added to the `Blockchain`
+    ///   where the mined `Block` will be added
+    /// - `block` - The `Block` whose `Transaction` is to be mined and added to the `Blockchain`
     /// 
     /// # Returns
-    /// - `Result<(), &str>` - Returns a result based on whether the given `Block` was successfully
-    ///    mined and added to the given `Blockchain`
-    pub fn mine_block<'a>(&mut self, blockchain: &'a mut Blockchain, mut block: Block) -> Result<(), &'a str> {
-        Self::proof_of_work(&mut block, blockchain.difficulty);
-        
-        if let Some(fee) = block.transaction.fee {
-            if self.balance >= fee {
-                self.balance -= fee;
-            } else {
-                return Err("Insufficient balance to cover the transaction fee");
-            }
-        }
-        
-        let reward = Self::calculate_block_reward(blockchain);
-        blockchain.add_block(block.clone())?;
+    /// - `Result<(), BlockError>` - `Ok(())` if the mined `Block` was accepted by the given
+    ///   `Blockchain`, in which case the reward is added to the balance of this `Miner`,
+    ///   or a `BlockError` describing why it was rejected
+    pub fn mine_block(&mut self, blockchain: &mut Blockchain, block: Block) -> Result<(), BlockError> {
+        let reward = Self::calculate_block_reward(blockchain) + block.transaction.total_fee();
+        let mut template = BlockTemplate::new(blockchain, &self.identifier);
+        template.state_root = blockchain.next_state(&block.transaction).root();
+        template.coinbase = vec![Transaction::new(None, Some(self.identifier.to_string()), reward, None)];
+        template.transaction = block.transaction;
+        blockchain.submit_block(Self::mine_template(&template))?;
         
         self.balance += reward;
-        self.balance += block.transaction.amount;
-        
         Ok(())
     }
     
     /// Calculates the reward for mining a block based on the block height.
     ///
-    /// The reward is initially 50 cryptos and is halved every 210,000 blocks.
+    /// The reward follows the `RewardSchedule` of the `Blockchain`'s `ChainSpec`, by default
+    /// initially 50 cryptos and halved every 210,000 blocks.
     /// This method follows the reward halving schedule, from many other cryptocurrencies like 
     /// [Bitcoin](https://bitcoin.org/en/)
     ///
@@ -96,9 +110,9 @@ impl Miner {
     /// # Returns
     /// - `f64` - The calculated reward for the given block height.
     pub fn calculate_block_reward(blockchain: &Blockchain) -> f64 {
-        let initial_reward = 50.0;
-        let halving_interval = 210000;
-        let halvings = blockchain.chain.len() - 1 / halving_interval;
+        let initial_reward = blockchain.spec.reward.initial_reward;
+        let halving_interval = blockchain.spec.reward.halving_interval;
+        let halvings = (blockchain.chain.len() - 1) / halving_interval;
         let base_reward = initial_reward / 2f64.powi(halvings as i32);
         base_reward * blockchain.difficulty as f64
     }
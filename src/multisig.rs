@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use crate::hex;
+use crate::script::{Script, MAX_MULTISIG_KEYS};
+use crate::transaction::Transaction;
+
+/// The length in bytes of an Ed25519 public key
+const PUBLIC_KEY_LENGTH: usize = 32;
+
+/// A `MultisigAccount` holds funds which can only be spent once `threshold` of its
+/// `public_keys` signed the spending `Transaction`
+///
+/// A `MultisigAccount` is validated by `MultisigAccount::new`, including when it is
+/// deserialized, so it always locks its funds with a spendable `Script`
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(try_from = "MultisigAccountData")]
+pub struct MultisigAccount {
+    threshold: usize,
+    public_keys: Vec<String>,
+}
+
+/// The fields of a `MultisigAccount` as stored, before they are validated
+#[derive(Deserialize)]
+struct MultisigAccountData {
+    threshold: usize,
+    public_keys: Vec<String>,
+}
+impl TryFrom<MultisigAccountData> for MultisigAccount {
+    type Error = &'static str;
+
+    fn try_from(data: MultisigAccountData) -> Result<Self, Self::Error> {
+        MultisigAccount::new(data.threshold, data.public_keys)
+    }
+}
+impl MultisigAccount {
+    /// Constructs a new M-of-N `MultisigAccount`
+    ///
+    /// # Parameters
+    /// - `threshold` - The number of signatures required, `M`
+    /// - `public_keys` - The hex encoded public keys of the `N` signers, in either case
+    ///
+    /// # Returns
+    /// - `Result<Self, &str>` - The `MultisigAccount`, with its public keys in lowercase, or an
+    ///   `Err(&str)` if the threshold or one of the public keys is invalid
+    pub fn new(threshold: usize, public_keys: Vec<String>) -> Result<Self, &'static str> {
+        if public_keys.is_empty() || public_keys.len() > MAX_MULTISIG_KEYS {
+            return Err("A multisig account needs at least one and at most MAX_MULTISIG_KEYS public keys");
+        }
+        let public_keys: Vec<String> = public_keys.iter().map(|key| key.to_lowercase()).collect();
+        if threshold == 0 || threshold > public_keys.len() {
+            return Err("Multisig threshold must be between 1 and the number of public keys");
+        }
+        if public_keys.iter().any(|key| hex::decode(key).is_none_or(|key| key.len() != PUBLIC_KEY_LENGTH)) {
+            return Err("Invalid multisig public key");
+        }
+        if public_keys.iter().enumerate().any(|(i, key)| public_keys[..i].contains(key)) {
+            return Err("Duplicate multisig public key");
+        }
+        Ok(MultisigAccount { threshold, public_keys })
+    }
+
+    /// The number of signatures required to spend the funds of this account
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// The hex encoded Ed25519 public keys of the signers, in the order their signatures are checked
+    pub fn public_keys(&self) -> &[String] {
+        &self.public_keys
+    }
+
+    /// Gets the `Script::multisig` locking the funds of this account
+    pub fn script(&self) -> Script {
+        // Every key was decoded once by `MultisigAccount::new`, so none is skipped here
+        let public_keys: Vec<Vec<u8>> = self.public_keys.iter().filter_map(|key| hex::decode(key)).collect();
+        Script::multisig(self.threshold, &public_keys)
+    }
+
+    /// Gets the address funds are sent to in order to be held by this account
+    pub fn address(&self) -> String {
+        self.script().address()
+    }
+}
+
+/// A `Transaction` spending the funds of a `MultisigAccount`, passed between its signers
+/// until enough of them signed it
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PartiallySignedTransaction {
+    /// The `Transaction` being signed, without its `unlocking_script`
+    pub transaction: Transaction,
+
+    /// The `MultisigAccount` the funds are spent from
+    pub account: MultisigAccount,
+
+    /// The hex encoded signatures collected so far, by hex encoded public key
+    pub signatures: BTreeMap<String, String>,
+}
+impl PartiallySignedTransaction {
+    /// Constructs a new, unsigned `PartiallySignedTransaction`
+    ///
+    /// # Parameters
+    /// - `account` - The `MultisigAccount` the funds are spent from
+    /// - `recipient` - The recipient of the funds
+    /// - `amount` - The amount sent
+    /// - `fee` - The fee paid by the account
+    pub fn new(account: MultisigAccount, recipient: &str, amount: f64, fee: Option<f64>) -> Self {
+        let mut transaction = Transaction::new(Some(account.address()), Some(recipient.to_string()), amount, fee);
+        transaction.redeem_script = Some(account.script());
+        PartiallySignedTransaction { transaction, account, signatures: BTreeMap::new() }
+    }
+
+    /// Loads a `PartiallySignedTransaction` shared by another signer
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let contents = fs::read(path)?;
+        serde_json::from_slice(&contents).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Saves this `PartiallySignedTransaction`, to be passed on to the next signer
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Adds the signature of one of the signers of the `MultisigAccount`
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())`, or an `Err(&str)` if the key does not belong to the account
+    ///   or a signature collected so far does not sign this `Transaction`
+    pub fn sign(&mut self, key_pair: &Ed25519KeyPair) -> Result<(), &'static str> {
+        let public_key = hex::encode(key_pair.public_key().as_ref()).to_lowercase();
+        if !self.account.public_keys.contains(&public_key) {
+            return Err("Key is not part of the multisig account");
+        }
+        self.check_signatures(&self.signatures)?;
+        self.signatures.insert(public_key, hex::encode(&self.transaction.sign_script(key_pair)));
+        Ok(())
+    }
+
+    /// Merges the signatures collected by another signer on a copy of the same `Transaction`
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())`, or an `Err(&str)` if `other` spends differently or
+    ///   one of its signatures does not sign this `Transaction`
+    pub fn combine(&mut self, other: PartiallySignedTransaction) -> Result<(), &'static str> {
+        if other.account != self.account || other.transaction.signature_hash() != self.transaction.signature_hash() {
+            return Err("Partially signed transactions differ");
+        }
+        self.check_signatures(&other.signatures)?;
+        self.signatures.extend(other.signatures);
+        Ok(())
+    }
+
+    /// Checks that every signature was made over the `signature_hash` of this `Transaction`
+    /// by the key of the `MultisigAccount` it is filed under
+    fn check_signatures(&self, signatures: &BTreeMap<String, String>) -> Result<(), &'static str> {
+        let signature_hash = self.transaction.signature_hash();
+        for (public_key, signature) in signatures {
+            if !self.account.public_keys.contains(public_key) {
+                return Err("Key is not part of the multisig account");
+            }
+            let (Some(public_key), Some(signature)) = (hex::decode(public_key), hex::decode(signature)) else {
+                return Err("Invalid multisig signature");
+            };
+            if UnparsedPublicKey::new(&ED25519, public_key).verify(&signature_hash, &signature).is_err() {
+                return Err("Invalid multisig signature");
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether enough signers signed the `Transaction`
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= self.account.threshold
+    }
+
+    /// Builds the `Transaction`, with an `unlocking_script` holding the collected signatures
+    ///
+    /// # Returns
+    /// - `Result<Transaction, &str>` - The signed `Transaction`, or an `Err(&str)` if fewer than
+    ///   `threshold` signers signed it
+    pub fn finalize(&self) -> Result<Transaction, &'static str> {
+        if !self.is_complete() {
+            return Err("Not enough signatures to spend from the multisig account");
+        }
+        let signatures = self.account.public_keys
+            .iter()
+            .filter_map(|key| self.signatures.get(key))
+            .take(self.account.threshold)
+            .map(|signature| hex::decode(signature).ok_or("Invalid multisig signature"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut transaction = self.transaction.clone();
+        transaction.unlocking_script = Some(Script::unlock_multisig(signatures));
+        Ok(transaction)
+    }
+}
@@ -1,18 +1,64 @@
-use std::io::Error;
-use std::ops::Deref;
+use std::io::{Error, ErrorKind};
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, watch, Notify};
+use tokio::task::JoinSet;
+use tokio::time::{interval, timeout, Instant};
 use serde::{Serialize, Deserialize};
 use lazy_static::lazy_static;
-use crate::block::Block;
-use crate::blockchain::Blockchain;
+use crate::address_book::{parse_peer, unix_time, AddressBook, MAX_ADDR_REPLY};
+use crate::block::{Block, BlockHeader};
+use crate::blockchain::{submit_shared_block, BlockError, SharedBlockchain};
+use crate::compact::CompactBlock;
+use crate::events::{Event, Topic};
+use crate::merkle::MerkleProof;
+use crate::notary::NotarizationProof;
+use crate::peer::{Misbehavior, PeerLimits, PeerManager};
+use crate::pool::{Job, Share};
+use crate::session::SessionRegistry;
+use crate::template::BlockTemplate;
+use crate::transaction::Transaction;
+use crate::transport::SecureChannel;
+use crate::version::{accept_version, initiate_version, Version, FULL_NODE_CAPABILITIES, NODE_COMPACT_BLOCKS, NODE_NETWORK};
 
 lazy_static! {
     pub static ref address: Arc<str> = Arc::from("127.0.0.1");
     pub static ref port: Arc<u16> = Arc::from(8080);
+    pub static ref rpc_port: Arc<u16> = Arc::from(8081);
+    pub static ref pool_port: Arc<u16> = Arc::from(3333);
 }
 
+/// The maximum number of `BlockHeader`s sent in reply to a single `GetHeaders` request
+pub const MAX_HEADERS: usize = 2000;
+
+/// The maximum size in bytes of a single frame sent between nodes
+pub const MAX_FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+/// The maximum total size in bytes, in the binary wire encoding, of the `Block`s sent in
+/// reply to a `RequestChain` request, leaving room for them to fit in a frame as JSON
+pub const MAX_RESPONSE_CHAIN_SIZE: usize = MAX_FRAME_SIZE / 4;
+
+/// How long a peer has to complete the encrypted transport handshake
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a node waits for a peer to answer during peer discovery
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a node refreshes its outbound peers from its `AddressBook`
+pub const PEER_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a connection may stay silent before the node closes it
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often a node pings the clients of long-lived connections to check they are still there
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(20);
+
+/// How long a node waits for its open connections to finish when shutting down
+pub const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Message {
     MineBlock(Block),
@@ -20,49 +66,604 @@ pub enum Message {
     ResponseChain(Vec<Block>),
     Connect(String),
     Disconnect(String),
+    NewTransaction(Transaction),
+    GetBlockTemplate(String),
+    BlockTemplate(BlockTemplate),
+    SubmitBlock(Block),
+    SubmitResult(Result<(), String>),
+    GetJob(String),
+    Job(Job),
+    SubmitShare(Share),
+    ShareResult(Result<bool, String>),
+    GetHeaders(Vec<String>),
+    Headers(Vec<BlockHeader>),
+    GetBlocks(Vec<String>),
+    Blocks(Vec<Block>),
+    GetTransactions(String),
+    Transactions(Vec<Transaction>),
+    GetMerkleProof(String),
+    MerkleProof(Option<(u32, MerkleProof)>),
+    CompactBlock(CompactBlock),
+    GetBlockTxn(String, Vec<usize>),
+    BlockTxn(String, Vec<Transaction>),
+    GetAddr(String),
+    Addr(Vec<(String, u64)>),
+    Version(Version),
+    Reject(String),
+    Subscribe(Vec<Topic>),
+    Notification(Event),
+    Ping(u64),
+    Pong(u64),
+    GetNotarization(String),
+    Notarization(Option<NotarizationProof>),
+}
+
+impl Message {
+    /// The class of this `Message` used for per-peer rate limiting
+    pub fn rate_class(&self) -> &'static str {
+        match self {
+            Message::MineBlock(_) | Message::SubmitBlock(_) | Message::CompactBlock(_) => "block",
+            Message::NewTransaction(_) => "transaction",
+            _ => "query",
+        }
+    }
+}
+
+/// The state shared by every connection of a node
+pub struct Node {
+    /// The `Blockchain` of this node
+    pub blockchain: SharedBlockchain,
+
+    /// The `address:port` this node listens on and advertises to its peers
+    pub listen_address: String,
+
+    /// The `address:port` of the reachable outbound peers new `Block`s are relayed to
+    pub peers: std::sync::Mutex<Vec<String>>,
+
+    /// The addresses of every peer this node knows about
+    pub address_book: std::sync::Mutex<AddressBook>,
+
+    /// The misbehavior scores, bans and connection limits of the peers of this node
+    pub peer_manager: std::sync::Mutex<PeerManager>,
+
+    /// The open connections of the peers and clients of this node
+    pub sessions: std::sync::Mutex<SessionRegistry>,
+
+    shutdown: watch::Sender<bool>,
+}
+impl Node {
+    /// Constructs a new `Node` enforcing the default `PeerLimits`
+    ///
+    /// # Parameters
+    /// - `blockchain` - The `Blockchain` of the node
+    /// - `listen_address` - The `address:port` the node listens on
+    /// - `address_book` - The `AddressBook` peers are discovered from, usually holding the seeds
+    pub fn new(blockchain: SharedBlockchain, listen_address: String, address_book: AddressBook)
+               -> Self {
+        Node {
+            blockchain,
+            listen_address,
+            peers: std::sync::Mutex::new(Vec::new()),
+            address_book: std::sync::Mutex::new(address_book),
+            peer_manager: std::sync::Mutex::new(PeerManager::new(PeerLimits::default())),
+            sessions: std::sync::Mutex::new(SessionRegistry::new()),
+            shutdown: watch::channel(false).0,
+        }
+    }
+
+    /// Asks the server of this node to stop accepting connections and close the open ones
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// Waits until `shutdown` is called
+    pub async fn shutting_down(&self) {
+        let mut shutdown = self.shutdown.subscribe();
+        let _ = shutdown.wait_for(|shutting_down| *shutting_down).await;
+    }
+
+    /// Gets the `Version` this node announces to its peers
+    pub async fn version(&self) -> Version {
+        let blockchain = self.blockchain.read().await;
+        Version::new(&blockchain.spec.network_id, blockchain.chain.len() as u64 - 1, FULL_NODE_CAPABILITIES)
+    }
+
+    /// Gets the `address:port` of the current outbound peers
+    pub fn peers(&self) -> Vec<String> {
+        self.peers.lock().expect("Peers lock poisoned").clone()
+    }
+
+    /// Records a misbehavior of the peer at `ip`
+    pub fn misbehaving(&self, ip: IpAddr, misbehavior: Misbehavior) {
+        self.peer_manager.lock().expect("Peer manager lock poisoned").misbehaving(ip, misbehavior);
+    }
 }
 
-pub async fn start_server(blockchain: Arc<tokio::sync::Mutex<Blockchain>>) -> Result<(), Error> {
-    let listener = TcpListener::bind(format!("{}:{}", *address, *port))
+/// Opens a `SecureChannel` to a peer and performs the version handshake
+///
+/// # Parameters
+/// - `target` - The `address:port` of the peer, or `public_key@address:port` to fail
+///   the handshake unless the peer authenticates with the given identity
+/// - `local` - The `Version` announced to the peer
+///
+/// # Returns
+/// - `Result<(SecureChannel, Version), Error>` - The `SecureChannel` and the `Version` of the peer
+pub async fn connect(target: &str, local: &Version) -> Result<(SecureChannel, Version), Error> {
+    let (target, expected_identity) = parse_peer(target);
+    let mut channel = SecureChannel::connect(target, expected_identity).await?;
+    let remote = initiate_version(&mut channel, local).await?;
+    Ok((channel, remote))
+}
+
+/// Opens a `SecureChannel` to a peer of a `Node` and performs the version handshake,
+/// expecting the identity pinned for the peer in the `AddressBook` of the `Node`
+///
+/// # Parameters
+/// - `node` - The `Node` connecting to its peer
+/// - `peer` - The `address:port` of the peer
+/// - `local` - The `Version` announced to the peer
+async fn connect_peer(node: &Node, peer: &str, local: &Version) -> Result<(SecureChannel, Version), Error> {
+    let expected_identity = node.address_book.lock().expect("Address book lock poisoned")
+        .identity(peer)
+        .map(String::from);
+    let mut channel = SecureChannel::connect(peer, expected_identity.as_deref()).await?;
+    let remote = initiate_version(&mut channel, local).await?;
+    Ok((channel, remote))
+}
+
+/// Sends a `Message` to the node listening on `target` and waits for its reply,
+/// announcing the `Version` of a process without a `Blockchain`
+///
+/// # Parameters
+/// - `target` - The `address:port` of the node
+/// - `message` - The request to be sent
+///
+/// # Returns
+/// - `Result<Message, Error>` - The `Message` the node replied with
+pub async fn send_request(target: &str, message: &Message) -> Result<Message, Error> {
+    send_request_as(target, &Version::client(), message).await
+}
+
+/// Sends a `Message` to the node listening on `target` and waits for its reply
+///
+/// # Parameters
+/// - `target` - The `address:port` of the node
+/// - `local` - The `Version` announced to the node
+/// - `message` - The request to be sent
+///
+/// # Returns
+/// - `Result<Message, Error>` - The `Message` the node replied with
+pub async fn send_request_as(target: &str, local: &Version, message: &Message) -> Result<Message, Error> {
+    let (mut channel, _) = connect(target, local).await?;
+    channel.send(message).await?;
+    channel.receive().await
+}
+
+/// Accepts connections from peers and clients until `Node::shutdown` is called, then
+/// waits up to `SHUTDOWN_GRACE_PERIOD` for the open connections to finish
+///
+/// # Parameters
+/// - `node` - The `Node` serving the connections
+pub async fn start_server(node: Arc<Node>) -> Result<(), Error> {
+    let listener = TcpListener::bind(&node.listen_address)
         .await?;
-    println!("Blockchain server running on {}", format!("{}:{}", *address, *port));
+    println!("Blockchain server running on {}", node.listen_address);
 
+    let mut connections = JoinSet::new();
     loop {
-        let (socket, _) = listener.accept().await?;
-        let blockchain = blockchain.clone();
-        tokio::spawn(async move {
-            handle_connection(socket, blockchain).await;
+        let (socket, peer) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+            _ = node.shutting_down() => break,
+        };
+        if let Err(e) = node.peer_manager.lock().expect("Peer manager lock poisoned").accept_inbound(peer.ip()) {
+            println!("Refused connection from {}: {}", peer, e);
+            continue;
+        }
+
+        let node = node.clone();
+        connections.spawn(async move {
+            handle_connection(socket, peer.ip(), node.clone()).await;
+            node.peer_manager.lock().expect("Peer manager lock poisoned").release_inbound();
         });
     }
+
+    drop(listener);
+    println!("Closing {} open connections", connections.len());
+    let finished = timeout(SHUTDOWN_GRACE_PERIOD, async {
+        while connections.join_next().await.is_some() {}
+    }).await;
+    if finished.is_err() {
+        connections.abort_all();
+    }
+    Ok(())
+}
+
+async fn handle_connection(socket: TcpStream, ip: IpAddr, node: Arc<Node>) {
+    let mut channel = match timeout(HANDSHAKE_TIMEOUT, SecureChannel::accept(socket)).await {
+        Ok(Ok(channel)) => channel,
+        Ok(Err(e)) if e.kind() == ErrorKind::PermissionDenied => return node.misbehaving(ip, Misbehavior::MalformedMessage),
+        Ok(Err(e)) if e.kind() == ErrorKind::InvalidData => return node.misbehaving(ip, Misbehavior::OversizedFrame),
+        _ => return,
+    };
+    let remote = match accept_version(&mut channel, &node.version().await).await {
+        Ok(remote) => remote,
+        Err(e) if e.kind() == ErrorKind::InvalidData => return node.misbehaving(ip, Misbehavior::MalformedMessage),
+        Err(_) => return,
+    };
+
+    let identity = channel.remote_identity();
+    let (session, closer) = node.sessions.lock().expect("Sessions lock poisoned")
+        .open(ip, identity.clone(), remote.software_version.clone());
+    handle_session(channel, ip, &identity, remote, closer, node.clone()).await;
+    node.sessions.lock().expect("Sessions lock poisoned").close(session);
+}
+
+async fn handle_session(mut channel: SecureChannel, ip: IpAddr, identity: &str, remote: Version, closer: Arc<Notify>,
+                        node: Arc<Node>) {
+    let message = match timeout(IDLE_TIMEOUT, channel.receive_frame()).await {
+        Ok(Ok(frame)) => match channel.encoding().decode::<Message>(&frame) {
+            Ok(message) => message,
+            Err(_) => return node.misbehaving(ip, Misbehavior::MalformedMessage),
+        },
+        Ok(Err(e)) if e.kind() == ErrorKind::InvalidData => return node.misbehaving(ip, Misbehavior::OversizedFrame),
+        Ok(Err(e)) if e.kind() == ErrorKind::PermissionDenied => return node.misbehaving(ip, Misbehavior::MalformedMessage),
+        _ => return,
+    };
+
+    let allowed = node.peer_manager.lock().expect("Peer manager lock poisoned").allow(ip, message.rate_class());
+    if !allowed {
+        return node.misbehaving(ip, Misbehavior::Spam);
+    }
+
+    if let Message::CompactBlock(compact) = message {
+        receive_compact_block(channel, ip, compact, node).await;
+        return;
+    }
+    if let Message::Subscribe(topics) = message {
+        serve_subscription(channel, topics, closer, node).await;
+        return;
+    }
+
+    let response = match message {
+        Message::MineBlock(_block) => None,
+        Message::RequestChain => {
+            Some(Message::ResponseChain(node.blockchain.read().await.recent_blocks(MAX_RESPONSE_CHAIN_SIZE)))
+        }
+        Message::Connect(name) => {
+            println!("{} ({}, {}) connected on {}!", name, channel.remote_identity(), remote.software_version,
+                     node.listen_address);
+            None
+        }
+        Message::Disconnect(name) => {
+            println!("{} disconnected from the server!", name);
+            node.sessions.lock().expect("Sessions lock poisoned").disconnect(identity);
+            // A peer may only remove itself, so the address it leaves must be on its own IP
+            if name.parse::<SocketAddr>().is_ok_and(|listen| listen.ip() == ip) {
+                node.peers.lock().expect("Peers lock poisoned").retain(|peer| *peer != name);
+            }
+            None
+        }
+        Message::ResponseChain(_chain) => {
+            // Handle received chain
+            None
+        }
+        Message::NewTransaction(transaction) => {
+            node.blockchain.write().await.add_transaction(transaction);
+            None
+        }
+        Message::GetBlockTemplate(miner) => {
+            Some(Message::BlockTemplate(BlockTemplate::new(&*node.blockchain.read().await, &miner)))
+        }
+        Message::SubmitBlock(block) => {
+            let result = submit_shared_block(&node.blockchain, block.clone()).await;
+            match result {
+                Ok(()) => {
+                    tokio::spawn(relay_block(block, node.clone()));
+                }
+                Err(e) => {
+                    if let Some(misbehavior) = block_misbehavior(e) {
+                        node.misbehaving(ip, misbehavior);
+                    }
+                }
+            }
+            Some(Message::SubmitResult(result.map_err(|e| e.to_string())))
+        }
+        Message::GetHeaders(locator) => {
+            Some(Message::Headers(node.blockchain.read().await.headers_after(&locator, MAX_HEADERS)))
+        }
+        Message::GetBlocks(hashes) => Some(Message::Blocks(node.blockchain.read().await.blocks_by_hash(&hashes))),
+        Message::GetTransactions(wallet) => {
+            Some(Message::Transactions(node.blockchain.read().await.transactions_of(&wallet)))
+        }
+        Message::GetMerkleProof(transaction_hash) => {
+            Some(Message::MerkleProof(node.blockchain.read().await.merkle_proof(&transaction_hash)))
+        }
+        Message::GetAddr(advertised) => {
+            let mut address_book = node.address_book.lock().expect("Address book lock poisoned");
+            let reachable_from_peer = advertised
+                .parse::<SocketAddr>()
+                .is_ok_and(|advertised| advertised.ip() == ip);
+            if reachable_from_peer && advertised != node.listen_address {
+                address_book.add(&advertised, 0, unix_time());
+            }
+            Some(Message::Addr(address_book.recent(MAX_ADDR_REPLY)))
+        }
+        Message::GetNotarization(document_hash) => {
+            Some(Message::Notarization(node.blockchain.read().await.notarization_proof(&document_hash)))
+        }
+        Message::Ping(nonce) => Some(Message::Pong(nonce)),
+        Message::BlockTemplate(_) | Message::SubmitResult(_)
+        | Message::Headers(_) | Message::Blocks(_)
+        | Message::Transactions(_) | Message::MerkleProof(_)
+        | Message::GetJob(_) | Message::Job(_)
+        | Message::SubmitShare(_) | Message::ShareResult(_)
+        | Message::CompactBlock(_) | Message::GetBlockTxn(_, _) | Message::BlockTxn(_, _)
+        | Message::Addr(_) | Message::Version(_) | Message::Reject(_)
+        | Message::Subscribe(_) | Message::Notification(_) | Message::Pong(_)
+        | Message::Notarization(_) => None,
+    };
+
+    // Every lock is released before replying, so a slow peer cannot hold up other connections
+    if let Some(response) = response {
+        let _ = channel.send(&response).await;
+    }
 }
 
-async fn handle_connection(mut socket: TcpStream, blockchain: Arc<tokio::sync::Mutex<Blockchain>>) {
-    let mut buffer: [u8; 1024] = [0; 1024];
-    
-    if let Ok(size) = socket.read(&mut buffer).await {
-        if let Ok(message) = serde_json::from_slice::<Message>(&buffer[..size]) {
-            let blockchain = blockchain.lock().await;
-            match message {
-                Message::MineBlock(block) => {
-                    
+/// Keeps the connection of a subscribed client open, pushing a `Notification` for every
+/// `Event` matching one of its `Topic`s until the client disconnects
+///
+/// The client is pinged every `KEEPALIVE_INTERVAL` and the connection is closed once it stays
+/// silent for `IDLE_TIMEOUT`, when its peer sends a `Disconnect`, or when the node shuts down
+async fn serve_subscription(channel: SecureChannel, topics: Vec<Topic>, closer: Arc<Notify>, node: Arc<Node>) {
+    let mut events = node.blockchain.read().await.events.subscribe();
+    let (mut sender, mut receiver) = channel.split();
+
+    let (messages, mut incoming) = mpsc::channel(16);
+    let reader = tokio::spawn(async move {
+        while let Ok(message) = receiver.receive().await {
+            if messages.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut keepalive = interval(KEEPALIVE_INTERVAL);
+    keepalive.tick().await;
+    let mut last_activity = Instant::now();
+    let mut nonce = 0;
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    if topics.iter().any(|topic| topic.matches(&event))
+                        && sender.send(&Message::Notification(event)).await.is_err() {
+                        break;
+                    }
                 }
-                Message::RequestChain => {
-                    let chain = &blockchain.chain;
-                    let response = Message::ResponseChain(chain.deref().to_vec());
-                    if let Ok(response) = serde_json::to_vec(&response) {
-                        let _ = socket.write_all(&response).await;
+                Err(RecvError::Lagged(missed)) => eprintln!("Subscriber fell behind and missed {} events", missed),
+                Err(RecvError::Closed) => break,
+            },
+            message = incoming.recv() => match message {
+                Some(Message::Ping(received)) => {
+                    last_activity = Instant::now();
+                    if sender.send(&Message::Pong(received)).await.is_err() {
+                        break;
                     }
                 }
-                Message::Connect(name) => {
-                    println!("{} connected on {} on port {}!", name, *address, *port);
+                Some(Message::Disconnect(_)) | None => break,
+                Some(_) => last_activity = Instant::now(),
+            },
+            _ = keepalive.tick() => {
+                if last_activity.elapsed() > IDLE_TIMEOUT {
+                    break;
                 }
-                Message::Disconnect(name) => {
-                    println!("{} disconnected from the server!", name);
+                nonce += 1;
+                if sender.send(&Message::Ping(nonce)).await.is_err() {
+                    break;
                 }
-                Message::ResponseChain(chain) => {
-                    // Handle received chain
+            }
+            _ = closer.notified() => break,
+            _ = node.shutting_down() => {
+                let _ = sender.send(&Message::Disconnect(node.listen_address.clone())).await;
+                break;
+            }
+        }
+    }
+    reader.abort();
+}
+
+/// Tells the outbound peers of a node that it is going away, so they stop relaying to it
+///
+/// # Parameters
+/// - `node` - The `Node` shutting down
+pub async fn announce_shutdown(node: &Node) {
+    let local = node.version().await;
+    for peer in node.peers() {
+        let _ = timeout(CONNECT_TIMEOUT, async {
+            let (mut channel, _) = connect_peer(node, &peer, &local).await?;
+            channel.send(&Message::Disconnect(node.listen_address.clone())).await
+        }).await;
+    }
+}
+
+/// Reconstructs a `Block` announced by a peer with a `CompactBlock`, requesting the
+/// `Transaction`s missing from the `Mempool`, and relays it further if it is accepted
+async fn receive_compact_block(mut channel: SecureChannel, ip: IpAddr, compact: CompactBlock, node: Arc<Node>) {
+    let (known, mut transactions) = {
+        let blockchain = node.blockchain.read().await;
+        let known = blockchain.chain.iter().any(|block| block.hash == compact.header.hash);
+        (known, compact.match_transactions(&blockchain.mempool))
+    };
+    if known {
+        let _ = channel.send(&Message::SubmitResult(Ok(()))).await;
+        return;
+    }
+
+    let missing = CompactBlock::missing(&transactions);
+    if !missing.is_empty() {
+        let request = Message::GetBlockTxn(compact.header.hash.clone(), missing.clone());
+        if channel.send(&request).await.is_err() {
+            return;
+        }
+        match timeout(IDLE_TIMEOUT, channel.receive()).await {
+            Ok(Ok(Message::BlockTxn(hash, received))) if hash == compact.header.hash && received.len() == missing.len() => {
+                for (position, transaction) in missing.into_iter().zip(received) {
+                    transactions[position] = Some(transaction);
                 }
             }
+            _ => return,
+        }
+    }
+
+    let result = match compact.reconstruct(transactions) {
+        Ok(block) => {
+            let result = submit_shared_block(&node.blockchain, block.clone()).await;
+            if result.is_ok() {
+                println!("Received block #{} from a peer", block.index);
+                tokio::spawn(relay_block(block, node.clone()));
+            }
+            result
+        }
+        Err(e) => Err(BlockError::from(e)),
+    };
+    if let Some(misbehavior) = result.err().and_then(block_misbehavior) {
+        node.misbehaving(ip, misbehavior);
+    }
+    let _ = channel.send(&Message::SubmitResult(result.map_err(|e| e.to_string()))).await;
+}
+
+/// Gets the `Misbehavior` a peer is penalized for when a `Block` it sent is rejected
+///
+/// # Returns
+/// - `Option<Misbehavior>` - The `Misbehavior` to be recorded, or `None` if honest peers
+///   send such `Block`s too, like stale `Block`s of a lost race or orphans ahead of the tip
+fn block_misbehavior(error: BlockError) -> Option<Misbehavior> {
+    match error {
+        BlockError::Stale(_) | BlockError::Orphan(_) => None,
+        BlockError::BadSeal(_) => Some(Misbehavior::BadProofOfWork),
+        BlockError::Invalid(_) => Some(Misbehavior::InvalidBlock),
+    }
+}
+
+/// Announces a newly accepted `Block` to every peer of a `Node`, with a `CompactBlock`
+/// if the peer supports them and with the full `Block` otherwise
+///
+/// # Parameters
+/// - `block` - The accepted `Block`
+/// - `node` - The `Node` whose peers the `Block` is relayed to
+pub async fn relay_block(block: Block, node: Arc<Node>) {
+    let compact = CompactBlock::from_block(&block);
+    let local = node.version().await;
+    for peer in &node.peers() {
+        if let Err(e) = node.peer_manager.lock().expect("Peer manager lock poisoned").connect_outbound() {
+            eprintln!("Failed to relay block #{} to {}: {}", block.index, peer, e);
+            continue;
+        }
+        let result = announce_block(&node, peer, &local, &compact, &block).await;
+        node.peer_manager.lock().expect("Peer manager lock poisoned").release_outbound();
+        if let Err(e) = result {
+            eprintln!("Failed to relay block #{} to {}: {}", block.index, peer, e);
+        }
+    }
+}
+
+async fn announce_block(node: &Node, peer: &str, local: &Version, compact: &CompactBlock, block: &Block)
+                        -> Result<(), Error> {
+    let (mut channel, remote) = connect_peer(node, peer, local).await?;
+    if remote.supports(NODE_COMPACT_BLOCKS) {
+        channel.send(&Message::CompactBlock(compact.clone())).await?;
+    } else {
+        channel.send(&Message::SubmitBlock(block.clone())).await?;
+    }
+
+    loop {
+        let reply = timeout(IDLE_TIMEOUT, channel.receive())
+            .await
+            .map_err(|_| Error::new(ErrorKind::TimedOut, "Peer did not reply to the block"))??;
+        match reply {
+            Message::GetBlockTxn(hash, positions) if hash == block.hash => {
+                let transactions = positions
+                    .iter()
+                    .filter_map(|position| match position {
+                        0 => Some(block.transaction.clone()),
+                        i => block.coinbase.get(i - 1).cloned(),
+                    })
+                    .collect();
+                channel.send(&Message::BlockTxn(hash, transactions)).await?;
+            }
+            Message::SubmitResult(Ok(())) => return Ok(()),
+            Message::SubmitResult(Err(e)) => return Err(Error::other(e)),
+            _ => return Err(Error::new(ErrorKind::InvalidData, "Unexpected reply to a compact block")),
+        }
+    }
+}
+/// Refreshes the outbound peers of a `Node` every `interval`, so a network self-assembles
+/// from a few seeds
+pub async fn maintain_peers(node: Arc<Node>, interval: Duration) {
+    loop {
+        discover_peers(&node).await;
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Asks the known peers of a `Node` for the addresses they know, most recently seen first,
+/// until `PeerLimits::max_outbound` of them answered. The peers which answered become the
+/// outbound peers of the `Node` and the `AddressBook` is saved
+pub async fn discover_peers(node: &Node) {
+    let max_outbound = node.peer_manager.lock().expect("Peer manager lock poisoned").limits.max_outbound;
+    let candidates = node.address_book.lock().expect("Address book lock poisoned").candidates();
+    let local = node.version().await;
+
+    let mut peers = Vec::new();
+    for candidate in candidates {
+        if peers.len() >= max_outbound {
+            break;
+        }
+        let banned = candidate
+            .parse::<SocketAddr>()
+            .is_ok_and(|peer| node.peer_manager.lock().expect("Peer manager lock poisoned").is_banned(peer.ip()));
+        if candidate == node.listen_address || banned {
+            continue;
+        }
+        if node.peer_manager.lock().expect("Peer manager lock poisoned").connect_outbound().is_err() {
+            break;
+        }
+
+        let request = Message::GetAddr(node.listen_address.clone());
+        let response = timeout(CONNECT_TIMEOUT, request_addresses(node, &candidate, &local, &request)).await;
+        node.peer_manager.lock().expect("Peer manager lock poisoned").release_outbound();
+
+        let mut address_book = node.address_book.lock().expect("Address book lock poisoned");
+        match response {
+            Ok(Ok(addresses)) => {
+                let now = unix_time();
+                address_book.mark_seen(&candidate, now);
+                address_book.merge(addresses, now);
+                address_book.entries.remove(&node.listen_address);
+                peers.push(candidate);
+            }
+            _ => address_book.mark_failed(&candidate),
         }
     }
-}
\ No newline at end of file
+
+    println!("Connected to {} outbound peers", peers.len());
+    *node.peers.lock().expect("Peers lock poisoned") = peers;
+    if let Err(e) = node.address_book.lock().expect("Address book lock poisoned").save() {
+        eprintln!("Failed to save the address book: {}", e);
+    }
+}
+
+/// Requests the addresses known by a full node
+async fn request_addresses(node: &Node, target: &str, local: &Version, request: &Message)
+                           -> Result<Vec<(String, u64)>, Error> {
+    let (mut channel, remote) = connect_peer(node, target, local).await?;
+    if !remote.supports(NODE_NETWORK) {
+        return Err(Error::new(ErrorKind::Unsupported, "Peer is not a full node"));
+    }
+    channel.send(request).await?;
+    match channel.receive().await? {
+        Message::Addr(addresses) => Ok(addresses),
+        _ => Err(Error::new(ErrorKind::InvalidData, "Expected addresses")),
+    }
+}
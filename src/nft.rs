@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::block::Block;
+use crate::token::is_key_address;
+use crate::transaction::Transaction;
+
+/// The prefix of the id of an `Nft`
+pub const NFT_ID_PREFIX: &str = "nft:";
+
+/// The maximum length of the metadata URI of an `Nft`
+pub const MAX_METADATA_URI_LENGTH: usize = 256;
+
+/// The length in hex digits of the SHA-256 content hash of an `Nft`
+const CONTENT_HASH_LENGTH: usize = 64;
+
+/// An operation on an `Nft`, which must be signed with `Transaction::authorize` by the sender
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum NftOperation {
+    /// Creates a new `Nft` owned by the sender, identified by the `nft_id` of the `Transaction`
+    Mint {
+        /// The hex encoded SHA-256 hash of the content the `Nft` represents
+        content_hash: String,
+
+        /// Where the metadata of the `Nft` can be found
+        metadata_uri: String,
+    },
+
+    /// Transfers an `Nft` owned by the sender to the recipient of the `Transaction`
+    Transfer {
+        /// The id of the `Nft`
+        id: String,
+    },
+}
+
+/// A record of who owned an `Nft` from which `Block` on
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Ownership {
+    /// The address of the owner
+    pub owner: String,
+
+    /// The index of the `Block` which gave the owner the `Nft`
+    pub block_index: u32,
+
+    /// The timestamp of that `Block`
+    pub timestamp: u64,
+
+    /// The hash of the `Transaction` which gave the owner the `Nft`
+    pub transaction_hash: String,
+}
+
+/// A unique asset recorded on the `Blockchain`
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Nft {
+    /// The id of this `Nft`
+    pub id: String,
+
+    /// The hex encoded SHA-256 hash of the content this `Nft` represents
+    pub content_hash: String,
+
+    /// Where the metadata of this `Nft` can be found
+    pub metadata_uri: String,
+
+    /// Every owner of this `Nft`, oldest first, the last one being the current owner
+    pub history: Vec<Ownership>,
+}
+impl Nft {
+    /// Gets the address of the current owner of this `Nft`
+    pub fn owner(&self) -> &str {
+        &self.history.last().expect("An Nft always has an owner").owner
+    }
+}
+
+/// Gets the id of the `Nft` minted by a `Transaction`
+pub fn nft_id(transaction: &Transaction) -> String {
+    format!("{}{}", NFT_ID_PREFIX, transaction.calculate_hash())
+}
+
+/// An `NftRegistry` holds every `Nft`, derived by replaying the `Transaction`s of a chain
+#[derive(Clone, Debug, Default)]
+pub struct NftRegistry {
+    /// The `Nft`s, by id
+    pub nfts: BTreeMap<String, Nft>,
+}
+impl NftRegistry {
+    /// Builds the `NftRegistry` of a chain by replaying all of its `Block`s in order
+    pub fn from_chain(chain: &[Block]) -> Self {
+        let mut registry = NftRegistry::default();
+        for block in chain {
+            registry.apply(block);
+        }
+        registry
+    }
+
+    /// Applies the `NftOperation` of a `Block`'s `Transaction`, if it carries one
+    pub fn apply(&mut self, block: &Block) {
+        let transaction = &block.transaction;
+        let ownership = |owner: &str| Ownership {
+            owner: owner.to_string(),
+            block_index: block.index,
+            timestamp: block.timestamp,
+            transaction_hash: transaction.calculate_hash(),
+        };
+        match (&transaction.nft, &transaction.sender, &transaction.recipient) {
+            (Some(NftOperation::Mint { content_hash, metadata_uri }), Some(sender), _) => {
+                let id = nft_id(transaction);
+                self.nfts.insert(id.clone(), Nft {
+                    id,
+                    content_hash: content_hash.clone(),
+                    metadata_uri: metadata_uri.clone(),
+                    history: vec![ownership(sender)],
+                });
+            }
+            (Some(NftOperation::Transfer { id }), _, Some(recipient)) => {
+                if let Some(nft) = self.nfts.get_mut(id) {
+                    nft.history.push(ownership(recipient));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks that the `NftOperation` of a `Transaction` is valid against this `NftRegistry`
+    ///
+    /// The sender must be a key address which signed the `Transaction` with
+    /// `Transaction::authorize`, and only the current owner of an `Nft` may transfer it
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if the `Transaction` is valid, or an `Err(&str)`
+    ///   describing the broken rule
+    pub fn check(&self, transaction: &Transaction) -> Result<(), &'static str> {
+        let Some(operation) = &transaction.nft else {
+            return Ok(());
+        };
+        let sender = transaction.sender.as_deref().filter(|sender| is_key_address(sender))
+            .ok_or("NFT operations must be sent from a key address")?;
+        if !transaction.is_authorized_by(sender) {
+            return Err("NFT operation is not signed by the sender");
+        }
+
+        match operation {
+            NftOperation::Mint { content_hash, metadata_uri } => {
+                if content_hash.len() != CONTENT_HASH_LENGTH || !content_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err("NFT content hash must be a hex encoded SHA-256 hash");
+                }
+                if metadata_uri.is_empty() || metadata_uri.len() > MAX_METADATA_URI_LENGTH {
+                    return Err("Invalid NFT metadata URI");
+                }
+                if self.nfts.contains_key(&nft_id(transaction)) {
+                    return Err("NFT is already minted");
+                }
+            }
+            NftOperation::Transfer { id } => {
+                let nft = self.nfts.get(id).ok_or("Unknown NFT")?;
+                if nft.owner() != sender {
+                    return Err("Only the owner can transfer an NFT");
+                }
+                if transaction.recipient.is_none() {
+                    return Err("NFT transfer has no recipient");
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use crate::block::BlockHeader;
+use crate::merkle::MerkleProof;
+use crate::transaction::Transaction;
+
+/// The maximum size in bytes of a `Notarization`, its document hash and memo together
+pub const MAX_NOTARIZATION_SIZE: usize = 256;
+
+/// The minimum fee paid for each byte of a `Notarization`
+pub const NOTARIZATION_FEE_PER_BYTE: f64 = 0.01;
+
+/// The length in hex digits of the SHA-256 hash of a notarized document
+const DOCUMENT_HASH_LENGTH: usize = 64;
+
+/// A document hash anchored on the `Blockchain`, proving the document existed when the
+/// `Block` including it was produced
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Notarization {
+    /// The hex encoded SHA-256 hash of the document
+    pub document_hash: String,
+
+    /// An optional short note about the document, such as its title or reference number
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+impl Notarization {
+    /// Gets the size in bytes of this `Notarization`, which its fee is charged on
+    pub fn size(&self) -> usize {
+        self.document_hash.len() + self.memo.as_ref().map_or(0, String::len)
+    }
+
+    /// Gets the minimum fee of a `Transaction` carrying this `Notarization`
+    pub fn minimum_fee(&self) -> f64 {
+        self.size() as f64 * NOTARIZATION_FEE_PER_BYTE
+    }
+
+    /// Checks that the `Notarization` of a `Transaction`, if it carries one, is well formed
+    /// and paid for
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if the `Transaction` is valid, or an `Err(&str)`
+    ///   describing the broken rule
+    pub fn check(transaction: &Transaction) -> Result<(), &'static str> {
+        let Some(notarization) = &transaction.notarization else {
+            return Ok(());
+        };
+        let hash = &notarization.document_hash;
+        if hash.len() != DOCUMENT_HASH_LENGTH || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("Notarized document hash must be a hex encoded SHA-256 hash");
+        }
+        if notarization.size() > MAX_NOTARIZATION_SIZE {
+            return Err("Notarization exceeds the maximum size");
+        }
+        if transaction.fee.unwrap_or(0.0) < notarization.minimum_fee() {
+            return Err("Notarization fee is below the fee per byte");
+        }
+        Ok(())
+    }
+}
+
+/// The proof that a document hash was recorded on the `Blockchain` at a given height and time
+///
+/// Anyone holding the `BlockHeader`s of the chain, such as a `LightClient`, can check it
+/// without trusting the node which produced it
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct NotarizationProof {
+    /// The `Transaction` carrying the `Notarization`
+    pub transaction: Transaction,
+
+    /// The index of the `Block` including the `Transaction`
+    pub block_index: u32,
+
+    /// The timestamp of that `Block`
+    pub timestamp: u64,
+
+    /// The hash of that `Block`
+    pub block_hash: String,
+
+    /// The proof of inclusion of the `Transaction` against the Merkle root of that `Block`
+    pub merkle_proof: MerkleProof,
+}
+impl NotarizationProof {
+    /// Gets the document hash this proof is about
+    pub fn document_hash(&self) -> Option<&str> {
+        self.transaction.notarization.as_ref().map(|notarization| notarization.document_hash.as_str())
+    }
+
+    /// Verifies this proof against the `BlockHeader` at its `block_index`
+    ///
+    /// # Parameters
+    /// - `document_hash` - The hash of the document which should have been notarized
+    /// - `header` - The trusted `BlockHeader` of the `Block` including the `Transaction`
+    ///
+    /// # Returns
+    /// - `bool` - `true` if the `Transaction` notarizes `document_hash` and is included in
+    ///   the `Block` with the claimed index, timestamp and hash
+    pub fn verify(&self, document_hash: &str, header: &BlockHeader) -> bool {
+        self.document_hash() == Some(document_hash)
+            && header.index == self.block_index
+            && header.timestamp == self.timestamp
+            && header.hash == self.block_hash
+            && self.merkle_proof.leaf == self.transaction.calculate_hash()
+            && self.merkle_proof.verify(&header.merkle_root)
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// A kind of misbehavior by a peer, each of which adds to the peer's misbehavior score
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Misbehavior {
+    /// The peer sent a `Block` which failed validation
+    InvalidBlock,
+
+    /// The peer sent a `Block` which is not sealed according to the `Consensus` engine,
+    /// such as a `Block` whose hash does not meet the difficulty target
+    BadProofOfWork,
+
+    /// The peer sent a frame larger than `MAX_FRAME_SIZE`
+    OversizedFrame,
+
+    /// The peer sent data which is not a valid `Message`
+    MalformedMessage,
+
+    /// The peer exceeded the rate limit of a kind of `Message`
+    Spam,
+}
+impl Misbehavior {
+    /// The number of points added to a peer's misbehavior score
+    pub fn penalty(&self) -> u32 {
+        match self {
+            Misbehavior::InvalidBlock => 50,
+            Misbehavior::BadProofOfWork => 100,
+            Misbehavior::OversizedFrame => 100,
+            Misbehavior::MalformedMessage => 20,
+            Misbehavior::Spam => 10,
+        }
+    }
+}
+
+/// A [token bucket](https://en.wikipedia.org/wiki/Token_bucket) rate limit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// The number of `Message`s which may be sent in a burst
+    pub burst: f64,
+
+    /// The number of `Message`s per second allowed over time
+    pub per_second: f64,
+}
+
+/// The limits a node enforces on its peers
+#[derive(Clone, Debug)]
+pub struct PeerLimits {
+    /// The maximum number of simultaneous inbound connections
+    pub max_inbound: usize,
+
+    /// The maximum number of simultaneous outbound connections
+    pub max_outbound: usize,
+
+    /// The misbehavior score at which a peer is banned
+    pub ban_threshold: u32,
+
+    /// How long a banned peer is refused
+    pub ban_duration: Duration,
+
+    /// How long it takes for one point of a misbehavior score to be forgiven, so occasional
+    /// faults of a long-lived peer do not add up to a ban
+    pub score_decay: Duration,
+
+    /// The rate limit of each kind of `Message`, keyed by `Message::rate_class`
+    pub rate_limits: HashMap<&'static str, RateLimit>,
+}
+impl Default for PeerLimits {
+    fn default() -> Self {
+        PeerLimits {
+            max_inbound: 125,
+            max_outbound: 8,
+            ban_threshold: 100,
+            ban_duration: Duration::from_secs(24 * 60 * 60),
+            score_decay: Duration::from_secs(6 * 60),
+            rate_limits: HashMap::from([
+                ("block", RateLimit { burst: 10.0, per_second: 2.0 }),
+                ("transaction", RateLimit { burst: 50.0, per_second: 10.0 }),
+                ("query", RateLimit { burst: 50.0, per_second: 20.0 }),
+            ]),
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+struct Score {
+    points: u32,
+    updated: Instant,
+}
+impl Score {
+    /// Forgives the points which decayed since the score was last updated
+    fn decay(&mut self, score_decay: Duration, now: Instant) {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        let forgiven = (elapsed / score_decay.as_secs_f64()) as u32;
+        self.points = self.points.saturating_sub(forgiven);
+        self.updated = if self.points == 0 { now } else { self.updated + score_decay * forgiven };
+    }
+}
+
+/// A `PeerManager` keeps track of the peers of a node: their misbehavior scores,
+/// temporary bans by IP address, connection counts and per-peer rate limits
+pub struct PeerManager {
+    /// The limits enforced on peers
+    pub limits: PeerLimits,
+
+    /// The time each banned peer's ban expires
+    pub bans: HashMap<IpAddr, Instant>,
+
+    scores: HashMap<IpAddr, Score>,
+
+    inbound: usize,
+    outbound: usize,
+    buckets: HashMap<(IpAddr, &'static str), Bucket>,
+}
+impl PeerManager {
+    /// Constructs a new `PeerManager` enforcing the given limits
+    pub fn new(limits: PeerLimits) -> Self {
+        PeerManager {
+            limits,
+            bans: HashMap::new(),
+            scores: HashMap::new(),
+            inbound: 0,
+            outbound: 0,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Checks whether a peer is currently banned, forgetting its ban once expired
+    pub fn is_banned(&mut self, ip: IpAddr) -> bool {
+        match self.bans.get(&ip) {
+            Some(expiry) if *expiry > Instant::now() => true,
+            Some(_) => {
+                self.bans.remove(&ip);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Bans a peer for the configured ban duration
+    pub fn ban(&mut self, ip: IpAddr) {
+        self.scores.remove(&ip);
+        self.buckets.retain(|(peer, _), _| *peer != ip);
+        self.bans.insert(ip, Instant::now() + self.limits.ban_duration);
+    }
+
+    /// Gets the misbehavior score of a peer, after the points which decayed are forgiven
+    pub fn score(&mut self, ip: IpAddr) -> u32 {
+        let Some(score) = self.scores.get_mut(&ip) else {
+            return 0;
+        };
+        score.decay(self.limits.score_decay, Instant::now());
+        let points = score.points;
+        if points == 0 {
+            self.scores.remove(&ip);
+        }
+        points
+    }
+
+    /// Records a misbehavior of a peer, banning it once its score reaches the ban threshold.
+    /// Scores decay by one point every `score_decay`
+    ///
+    /// # Returns
+    /// - `bool` - `true` if the peer is now banned
+    pub fn misbehaving(&mut self, ip: IpAddr, misbehavior: Misbehavior) -> bool {
+        let now = Instant::now();
+        let score = self.scores.entry(ip).or_insert(Score { points: 0, updated: now });
+        score.decay(self.limits.score_decay, now);
+        score.points += misbehavior.penalty();
+        println!("Peer {} misbehaved ({:?}), score {}", ip, misbehavior, score.points);
+
+        if score.points >= self.limits.ban_threshold {
+            println!("Banning peer {}", ip);
+            self.ban(ip);
+            return true;
+        }
+        false
+    }
+
+    /// Checks whether a peer may send another `Message` of the given rate class
+    ///
+    /// # Returns
+    /// - `bool` - `true` if the `Message` is within the rate limit, `false` otherwise
+    pub fn allow(&mut self, ip: IpAddr, rate_class: &'static str) -> bool {
+        let Some(limit) = self.limits.rate_limits.get(rate_class).copied() else {
+            return true;
+        };
+        let now = Instant::now();
+        let bucket = self.buckets.entry((ip, rate_class)).or_insert(Bucket {
+            tokens: limit.burst,
+            updated: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.per_second).min(limit.burst);
+        bucket.updated = now;
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+
+    /// Reserves an inbound connection slot for a peer
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if the peer may connect, or an `Err(&str)` if it
+    ///   is banned or the maximum number of inbound connections is reached
+    pub fn accept_inbound(&mut self, ip: IpAddr) -> Result<(), &'static str> {
+        if self.is_banned(ip) {
+            return Err("Peer is banned");
+        }
+        if self.inbound >= self.limits.max_inbound {
+            return Err("Too many inbound connections");
+        }
+        self.inbound += 1;
+        Ok(())
+    }
+
+    /// Releases an inbound connection slot reserved by `accept_inbound`
+    pub fn release_inbound(&mut self) {
+        self.inbound = self.inbound.saturating_sub(1);
+    }
+
+    /// Reserves an outbound connection slot
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if a connection may be opened, or an `Err(&str)`
+    ///   if the maximum number of outbound connections is reached
+    pub fn connect_outbound(&mut self) -> Result<(), &'static str> {
+        if self.outbound >= self.limits.max_outbound {
+            return Err("Too many outbound connections");
+        }
+        self.outbound += 1;
+        Ok(())
+    }
+
+    /// Releases an outbound connection slot reserved by `connect_outbound`
+    pub fn release_outbound(&mut self) {
+        self.outbound = self.outbound.saturating_sub(1);
+    }
+
+    /// The number of open inbound connections
+    pub fn inbound(&self) -> usize {
+        self.inbound
+    }
+
+    /// The number of open outbound connections
+    pub fn outbound(&self) -> usize {
+        self.outbound
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::Error;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use serde::{Deserialize, Serialize};
+use crate::block::Block;
+use crate::network::{address, port, pool_port, send_request, Message};
+use crate::transport::SecureChannel;
+use crate::version::{accept_version, Version};
+use crate::template::BlockTemplate;
+use crate::transaction::Transaction;
+
+/// The number of recent `Job`s a `Pool` keeps, so shares for slightly stale jobs are still accepted
+const MAX_JOBS: usize = 16;
+
+/// How a `Pool` splits the reward of a found `Block` between its `Miner`s
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum PayoutScheme {
+    /// Every share submitted since the last found `Block` is worth the same
+    Proportional,
+
+    /// Pay Per Last N Shares - only the given number of most recent shares are paid
+    Pplns(usize),
+}
+
+/// A unit of work handed out by a `Pool` to a `Miner`
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Job {
+    /// The identifier the `Miner` refers to when submitting a `Share`
+    pub id: u64,
+
+    /// The identifier of the `Miner` this `Job` was handed out to, which is committed to
+    /// in the coinbase so that no two `Miner`s or `Job`s share the same work
+    pub miner: String,
+
+    /// The `Block` to be mined, whose `difficulty` is lowered to the share difficulty
+    pub template: BlockTemplate,
+
+    /// The difficulty a share must meet to be a valid `Block` of the `Blockchain`
+    pub network_difficulty: usize,
+}
+
+/// A `Job` handed out by a `Pool`, with the payouts which did not fit in its coinbase
+struct IssuedJob {
+    job: Job,
+
+    /// What the `Pool` owes each `Miner` once a `Block` is found from this `Job`
+    unpaid: BTreeMap<String, f64>,
+
+    /// The hashes of the `Block`s of the shares accepted for this `Job`
+    submitted: HashSet<String>,
+}
+
+/// A solution submitted by a `Miner` for a `Job`
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Share {
+    /// The identifier of the `Miner` who found this `Share`
+    pub miner: String,
+
+    /// The identifier of the `Job` this `Share` solves
+    pub job_id: u64,
+
+    /// The `nonce` which makes the `Job`'s `Block` hash meet the share difficulty
+    pub nonce: u32,
+}
+
+/// A mining pool, which combines the hash power of several `Miner`s
+///
+/// The `Pool` hands out `Job`s at a lower share difficulty than the `Blockchain`,
+/// so `Miner`s can prove their work frequently. Shares which also meet the network
+/// difficulty are full `Block`s and are submitted to the node, with a coinbase paying
+/// every `Miner` according to the `Pool`'s `PayoutScheme`
+pub struct Pool {
+    /// The identifier of the `Pool`, which is paid when no shares have been submitted
+    pub identifier: String,
+
+    /// The number of leading zeros a `Share`'s hash must have
+    pub share_difficulty: usize,
+
+    /// How the reward of a found `Block` is split between `Miner`s
+    pub scheme: PayoutScheme,
+
+    /// The accepted shares of the current round, oldest first
+    pub shares: VecDeque<String>,
+
+    /// The total number of accepted shares per `Miner`
+    pub accepted: HashMap<String, u64>,
+
+    /// The payouts owed to `Miner`s which did not fit in the coinbase of a found `Block`.
+    /// The `Pool` was paid them instead, and pays them out of the reward of later `Block`s
+    pub unpaid: BTreeMap<String, f64>,
+
+    jobs: VecDeque<IssuedJob>,
+    next_job_id: u64,
+}
+impl Pool {
+    /// Constructs a new `Pool` without any `Job`s or shares
+    ///
+    /// # Parameters
+    /// - `identifier` - The identifier of the `Pool`
+    /// - `share_difficulty` - The number of leading zeros a `Share`'s hash must have
+    /// - `scheme` - How the reward of a found `Block` is split between `Miner`s
+    pub fn new(identifier: &str, share_difficulty: usize, scheme: PayoutScheme) -> Self {
+        Pool {
+            identifier: identifier.to_string(),
+            share_difficulty,
+            scheme,
+            shares: VecDeque::new(),
+            accepted: HashMap::new(),
+            unpaid: BTreeMap::new(),
+            jobs: VecDeque::new(),
+            next_job_id: 0,
+        }
+    }
+
+    /// Computes how a reward is split between the `Miner`s of this `Pool`
+    ///
+    /// # Parameters
+    /// - `reward` - The total amount paid by the coinbase of a found `Block`
+    ///
+    /// # Returns
+    /// - `BTreeMap<String, f64>` - The amount paid to each `Miner`, or the whole
+    ///   reward paid to the `Pool` if no shares count towards the payout
+    pub fn payouts(&self, reward: f64) -> BTreeMap<String, f64> {
+        let window = match self.scheme {
+            PayoutScheme::Proportional => self.shares.len(),
+            PayoutScheme::Pplns(n) => n.min(self.shares.len()),
+        };
+        let mut payouts = BTreeMap::new();
+        if window == 0 {
+            payouts.insert(self.identifier.clone(), reward);
+            return payouts;
+        }
+
+        let per_share = reward / window as f64;
+        for miner in self.shares.iter().rev().take(window) {
+            *payouts.entry(miner.clone()).or_insert(0.0) += per_share;
+        }
+        payouts
+    }
+
+    /// Creates a new `Job` from a `BlockTemplate` handed out by the node
+    ///
+    /// The template's coinbase is replaced with the current payouts of this `Pool`, plus
+    /// what it still owes from earlier `Block`s, and its difficulty is lowered to the share
+    /// difficulty. The largest amounts owed are paid first. Whatever does not fit in the
+    /// reward or in the `BlockLimits` is paid to the `Pool` and stays `unpaid` until a later
+    /// `Block`
+    ///
+    /// # Parameters
+    /// - `template` - A `BlockTemplate` whose coinbase pays this `Pool`
+    /// - `miner` - The identifier of the `Miner` requesting the `Job`
+    ///
+    /// # Returns
+    /// - `Job` - The `Job` to be handed out to `miner`
+    pub fn create_job(&mut self, mut template: BlockTemplate, miner: &str) -> Job {
+        let reward: f64 = template.coinbase.iter().map(|tx| tx.amount).sum();
+        let mut owed = self.unpaid.clone();
+        for (miner, amount) in self.payouts(reward) {
+            *owed.entry(miner).or_insert(0.0) += amount;
+        }
+        owed.remove(&self.identifier);
+        let mut owed: Vec<(String, f64)> = owed.into_iter().collect();
+        owed.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        // One payout is kept for the `Pool` when some of the reward would be left over
+        let max_payouts = template.limits.max_block_transactions.saturating_sub(1).max(1);
+        let owed_total: f64 = owed.iter().map(|(_, amount)| amount).sum();
+        let slots = if owed.len() > max_payouts || owed_total < reward { max_payouts - 1 } else { max_payouts };
+
+        let mut remaining = reward;
+        let mut payouts = Vec::new();
+        let mut unpaid = BTreeMap::new();
+        for (miner, amount) in owed {
+            let paid = if payouts.len() < slots { amount.min(remaining) } else { 0.0 };
+            if paid > 0.0 {
+                payouts.push((miner.clone(), paid));
+                remaining -= paid;
+            }
+            if amount > paid {
+                unpaid.insert(miner, amount - paid);
+            }
+        }
+        if remaining > 0.0 || payouts.is_empty() {
+            payouts.push((self.identifier.clone(), remaining));
+        }
+        // The payouts are summed in the same order by the node, which rejects them if
+        // rounding makes them add up to even slightly more than the reward
+        let mut total: f64 = payouts.iter().map(|(_, amount)| amount).sum();
+        while total > reward {
+            let largest = payouts
+                .iter_mut()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .expect("At least one payout is made");
+            largest.1 -= (total - reward).max(largest.1 * f64::EPSILON);
+            total = payouts.iter().map(|(_, amount)| amount).sum();
+        }
+        template.coinbase = payouts
+            .into_iter()
+            .map(|(recipient, amount)| {
+                let mut payout = Transaction::new(None, Some(recipient), amount, None);
+                payout.timestamp = template.timestamp;
+                payout
+            })
+            .collect();
+        template.coinbase[0].metadata = Some(format!("Job #{} for {}", self.next_job_id, miner));
+
+        let network_difficulty = template.difficulty;
+        template.difficulty = self.share_difficulty;
+        template.target = "0".repeat(self.share_difficulty);
+
+        let job = Job {
+            id: self.next_job_id,
+            miner: miner.to_string(),
+            template,
+            network_difficulty,
+        };
+        self.next_job_id += 1;
+        // Shares for `Job`s built on an older tip could only be stale `Block`s
+        self.jobs.retain(|issued| issued.job.template.previous_hash == job.template.previous_hash);
+        self.jobs.push_back(IssuedJob { job: job.clone(), unpaid, submitted: HashSet::new() });
+        if self.jobs.len() > MAX_JOBS {
+            self.jobs.pop_front();
+        }
+        job
+    }
+
+    /// Validates a `Share` and records it for the submitting `Miner`
+    ///
+    /// # Parameters
+    /// - `share` - The `Share` submitted by a `Miner`
+    ///
+    /// # Returns
+    /// - `Result<Option<Block>, &str>` - `Ok(Some(block))` if the `Share` also meets the network
+    ///   difficulty and `block` should be submitted to the node, which is reported back with
+    ///   `Pool::block_accepted`, `Ok(None)` if it only meets the share difficulty, or an
+    ///   `Err(&str)` if the `Share` was rejected
+    pub fn submit_share(&mut self, share: &Share) -> Result<Option<Block>, &'static str> {
+        let issued = self
+            .jobs
+            .iter_mut()
+            .find(|issued| issued.job.id == share.job_id)
+            .ok_or("Unknown or stale job")?;
+        if issued.job.miner != share.miner {
+            return Err("Share is for a job handed out to another miner");
+        }
+
+        let mut block = issued.job.template.to_block();
+        block.nonce = share.nonce;
+        block.hash = Block::calculate_hash(&block);
+        if !block.hash.starts_with(&"0".repeat(self.share_difficulty)) {
+            return Err("Share does not meet the share difficulty");
+        }
+        if !issued.submitted.insert(block.hash.clone()) {
+            return Err("Duplicate share");
+        }
+        let network_difficulty = issued.job.network_difficulty;
+
+        self.shares.push_back(share.miner.clone());
+        *self.accepted.entry(share.miner.clone()).or_insert(0) += 1;
+        if let PayoutScheme::Pplns(n) = self.scheme {
+            while self.shares.len() > n {
+                self.shares.pop_front();
+            }
+        }
+
+        if block.hash.starts_with(&"0".repeat(network_difficulty)) {
+            Ok(Some(block))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Starts a new round once the node accepted the `Block` found from a `Job`
+    ///
+    /// What the `Job`'s coinbase could not pay is now owed by the `Pool`, the shares of a
+    /// `PayoutScheme::Proportional` round are paid, and every `Job` built on the old tip is dropped
+    ///
+    /// # Parameters
+    /// - `job_id` - The identifier of the `Job` the accepted `Block` was mined from
+    pub fn block_accepted(&mut self, job_id: u64) {
+        let Some(issued) = self.jobs.iter().find(|issued| issued.job.id == job_id) else {
+            return;
+        };
+        self.unpaid = issued.unpaid.clone();
+        if self.scheme == PayoutScheme::Proportional {
+            self.shares.clear();
+        }
+        self.jobs.clear();
+    }
+}
+
+/// Starts the mining pool server on `pool_port`, which relays `Job`s from the node
+/// listening on `port` to `Miner`s and submits found `Block`s back to it
+///
+/// # Parameters
+/// - `pool` - The `Pool` keeping track of `Job`s and shares
+pub async fn start_pool_server(pool: Arc<tokio::sync::Mutex<Pool>>) -> Result<(), Error> {
+    let listener = TcpListener::bind(format!("{}:{}", *address, *pool_port))
+        .await?;
+    println!("Mining pool running on {}:{}", *address, *pool_port);
+
+    loop {
+        let (socket, _) = listener.accept().await?;
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            handle_pool_connection(socket, pool).await;
+        });
+    }
+}
+
+async fn handle_pool_connection(socket: TcpStream, pool: Arc<tokio::sync::Mutex<Pool>>) {
+    let Ok(mut channel) = SecureChannel::accept(socket).await else {
+        return;
+    };
+    if accept_version(&mut channel, &Version::client()).await.is_err() {
+        return;
+    }
+    let node_address = format!("{}:{}", *address, *port);
+
+    let response = match channel.receive().await {
+        Ok(Message::GetJob(miner)) => {
+            let identifier = pool.lock().await.identifier.clone();
+            match send_request(&node_address, &Message::GetBlockTemplate(identifier)).await {
+                Ok(Message::BlockTemplate(template)) => {
+                    println!("Handing out a job to {}", miner);
+                    Message::Job(pool.lock().await.create_job(template, &miner))
+                }
+                _ => return,
+            }
+        }
+        Ok(Message::SubmitShare(share)) => {
+            let result = pool.lock().await.submit_share(&share);
+            match result {
+                Ok(Some(block)) => {
+                    println!("{} found block #{}!", share.miner, block.index);
+                    match send_request(&node_address, &Message::SubmitBlock(block)).await {
+                        Ok(Message::SubmitResult(Ok(()))) => {
+                            pool.lock().await.block_accepted(share.job_id);
+                            Message::ShareResult(Ok(true))
+                        }
+                        Ok(Message::SubmitResult(Err(e))) => Message::ShareResult(Err(e)),
+                        _ => Message::ShareResult(Err(String::from("The node did not accept the block"))),
+                    }
+                }
+                Ok(None) => Message::ShareResult(Ok(false)),
+                Err(e) => Message::ShareResult(Err(e.to_string())),
+            }
+        }
+        _ => return,
+    };
+
+    let _ = channel.send(&response).await;
+}
@@ -0,0 +1,308 @@
+use std::io::Error;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, RwLock};
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use crate::block::Block;
+use crate::blockchain::{submit_shared_block, Blockchain, SharedBlockchain};
+use crate::network::address;
+use crate::events::Topic;
+use crate::template::BlockTemplate;
+use crate::websocket;
+
+/// The maximum size, in bytes, of the request line and headers of an HTTP request
+pub const MAX_HEADER_SIZE: usize = 16 * 1024;
+
+/// The maximum size, in bytes, of the body of an HTTP request
+pub const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+/// A JSON-RPC request sent to the node over HTTP
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcRequest {
+    /// An identifier echoed back in the matching `RpcResponse`
+    #[serde(default)]
+    pub id: Value,
+
+    /// The name of the method to call, such as `getblocktemplate`
+    pub method: String,
+
+    /// The positional parameters of the method
+    #[serde(default)]
+    pub params: Vec<Value>,
+}
+
+/// A JSON-RPC response returned by the node
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcResponse {
+    /// The identifier of the `RpcRequest` being answered
+    pub id: Value,
+
+    /// The result of the call, or `null` if it failed
+    pub result: Value,
+
+    /// A description of why the call failed, or `null` if it succeeded
+    pub error: Value,
+}
+
+/// Starts the HTTP JSON-RPC server of the node, usually on `rpc_port`
+///
+/// Clients may also upgrade their connection to a WebSocket to subscribe to `Event`s
+///
+/// # Parameters
+/// - `blockchain` - The `Blockchain` shared with the peer-to-peer server
+/// - `listen_port` - The port the server listens on
+pub async fn start_rpc_server(blockchain: SharedBlockchain, listen_port: u16) -> Result<(), Error> {
+    let listener = TcpListener::bind(format!("{}:{}", *address, listen_port))
+        .await?;
+    println!("RPC server running on {}:{}", *address, listen_port);
+
+    loop {
+        let (socket, _) = listener.accept().await?;
+        let blockchain = blockchain.clone();
+        tokio::spawn(async move {
+            handle_rpc_connection(socket, blockchain).await;
+        });
+    }
+}
+
+/// Dispatches a single `RpcRequest` against the `Blockchain`
+///
+/// Supported methods:
+/// - `getblocktemplate [miner]` - returns a `BlockTemplate` paying its coinbase to `miner`
+/// - `submitblock [block]` - validates a mined `Block` and appends it to the `Blockchain`
+/// - `getcontract [address]` - returns the code and storage of a deployed `Contract`
+/// - `gettoken [symbol]` - returns the issuer and supply of a `Token`
+/// - `gettokenbalance [address, symbol]` - returns the balance of a `Token` held by an address
+/// - `getnft [id]` - returns an `Nft` with the history of its owners
+/// - `estimatefee` - returns the fee per byte a `Transaction` should pay to be included soon
+/// - `getnotarization [hash]` - returns the `NotarizationProof` of a document hash, with the
+///   height and time it was recorded at
+pub async fn dispatch(blockchain: &RwLock<Blockchain>, request: RpcRequest) -> RpcResponse {
+    let result = match request.method.as_str() {
+        "getblocktemplate" => {
+            let miner = request.params.first().and_then(Value::as_str).unwrap_or_default();
+            let template = BlockTemplate::new(&*blockchain.read().await, miner);
+            serde_json::to_value(template).map_err(|e| e.to_string())
+        }
+        "submitblock" => match request.params.into_iter().next().map(serde_json::from_value::<Block>) {
+            Some(Ok(block)) => submit_shared_block(blockchain, block)
+                .await
+                .map(|_| Value::Bool(true))
+                .map_err(|e| e.to_string()),
+            Some(Err(e)) => Err(e.to_string()),
+            None => Err(String::from("Missing block parameter")),
+        },
+        "getcontract" => {
+            let contract_address = request.params.first().and_then(Value::as_str).unwrap_or_default();
+            match blockchain.read().await.contracts.contracts.get(contract_address) {
+                Some(contract) => serde_json::to_value(contract).map_err(|e| e.to_string()),
+                None => Err(String::from("No contract at this address")),
+            }
+        }
+        "gettoken" => {
+            let symbol = request.params.first().and_then(Value::as_str).unwrap_or_default();
+            match blockchain.read().await.ledger().tokens.get(symbol) {
+                Some(token) => serde_json::to_value(token).map_err(|e| e.to_string()),
+                None => Err(String::from("Unknown token")),
+            }
+        }
+        "gettokenbalance" => {
+            let holder = request.params.first().and_then(Value::as_str).unwrap_or_default();
+            let symbol = request.params.get(1).and_then(Value::as_str).unwrap_or_default();
+            Ok(json!(blockchain.read().await.ledger().token_balance(holder, symbol)))
+        }
+        "getnft" => {
+            let id = request.params.first().and_then(Value::as_str).unwrap_or_default();
+            match blockchain.read().await.nfts().nfts.get(id) {
+                Some(nft) => serde_json::to_value(nft).map_err(|e| e.to_string()),
+                None => Err(String::from("Unknown NFT")),
+            }
+        }
+        "estimatefee" => Ok(json!(blockchain.read().await.estimate_fee_rate())),
+        "getnotarization" => {
+            let document_hash = request.params.first().and_then(Value::as_str).unwrap_or_default();
+            match blockchain.read().await.notarization_proof(document_hash) {
+                Some(proof) => serde_json::to_value(proof).map_err(|e| e.to_string()),
+                None => Err(String::from("Document hash was never notarized")),
+            }
+        }
+        method => Err(format!("Unknown method: {}", method)),
+    };
+
+    match result {
+        Ok(result) => RpcResponse { id: request.id, result, error: Value::Null },
+        Err(error) => RpcResponse { id: request.id, result: Value::Null, error: json!(error) },
+    }
+}
+
+/// Serves a single connection to the RPC server, which is either an HTTP JSON-RPC request
+/// or the upgrade of the connection to a WebSocket
+///
+/// # Parameters
+/// - `socket` - The connection accepted from the client
+/// - `blockchain` - The `Blockchain` requests are dispatched against
+pub async fn handle_rpc_connection(mut socket: TcpStream, blockchain: SharedBlockchain) {
+    let (headers, body) = match read_http_request(&mut socket).await {
+        Some(Ok(request)) => request,
+        Some(Err(status)) => {
+            let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+            let _ = socket.write_all(response.as_bytes()).await;
+            return;
+        }
+        None => return,
+    };
+    if header(&headers, "upgrade").is_some_and(|upgrade| upgrade.to_ascii_lowercase().contains("websocket")) {
+        let Some(key) = header(&headers, "sec-websocket-key") else {
+            return;
+        };
+        let response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            websocket::accept_key(key)
+        );
+        if socket.write_all(response.as_bytes()).await.is_ok() {
+            serve_websocket(socket, blockchain).await;
+        }
+        return;
+    }
+
+    let response = match serde_json::from_slice::<RpcRequest>(&body) {
+        Ok(request) => dispatch(&blockchain, request).await,
+        Err(e) => RpcResponse { id: Value::Null, result: Value::Null, error: json!(e.to_string()) },
+    };
+
+    if let Ok(body) = serde_json::to_vec(&response) {
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        let _ = socket.write_all(header.as_bytes()).await;
+        let _ = socket.write_all(&body).await;
+    }
+}
+
+/// Reads an HTTP request from `socket`
+///
+/// # Returns
+/// - `Option<Result<(String, Vec<u8>), &str>>` - The request line and headers, and the body,
+///   an `Err(&str)` holding the status the request is refused with if its headers exceed
+///   `MAX_HEADER_SIZE` or its body exceeds `MAX_BODY_SIZE`, or `None` if the connection closed
+async fn read_http_request(socket: &mut TcpStream) -> Option<Result<(String, Vec<u8>), &'static str>> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    let header_end = loop {
+        let size = socket.read(&mut buffer).await.ok()?;
+        if size == 0 {
+            return None;
+        }
+        request.extend_from_slice(&buffer[..size]);
+        if let Some(position) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position + 4;
+        }
+        if request.len() > MAX_HEADER_SIZE {
+            return Some(Err("431 Request Header Fields Too Large"));
+        }
+    };
+    if header_end > MAX_HEADER_SIZE {
+        return Some(Err("431 Request Header Fields Too Large"));
+    }
+
+    let headers = String::from_utf8_lossy(&request[..header_end]).to_string();
+    let content_length = header(&headers, "content-length")
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_SIZE {
+        return Some(Err("413 Payload Too Large"));
+    }
+
+    while request.len() < header_end + content_length {
+        let size = socket.read(&mut buffer).await.ok()?;
+        if size == 0 {
+            return None;
+        }
+        request.extend_from_slice(&buffer[..size]);
+    }
+    Some(Ok((headers, request[header_end..header_end + content_length].to_vec())))
+}
+
+/// Gets the value of an HTTP header
+///
+/// Header names are matched case-insensitively, while the value is returned as sent,
+/// since some values, such as `Sec-WebSocket-Key`, are case-sensitive
+fn header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    headers.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// Serves a WebSocket client, which sends JSON-RPC requests as text frames
+///
+/// Besides the methods of `dispatch`, a WebSocket client may call:
+/// - `subscribe [topic, ...]` - pushes a `notification` for every `Event` matching a `Topic`
+/// - `unsubscribe` - stops all notifications
+async fn serve_websocket(socket: TcpStream, blockchain: SharedBlockchain) {
+    let mut events = blockchain.read().await.events.subscribe();
+    let (mut reader, mut writer) = socket.into_split();
+
+    // Frames are read on their own task, since reading one is not cancel safe
+    let (frames_sender, mut frames) = mpsc::channel(16);
+    tokio::spawn(async move {
+        while let Ok(frame) = websocket::read_frame(&mut reader).await {
+            if frames_sender.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut topics: Vec<Topic> = Vec::new();
+    loop {
+        let reply = tokio::select! {
+            frame = frames.recv() => match frame {
+                Some((websocket::OPCODE_TEXT, payload)) => {
+                    let response = match serde_json::from_slice::<RpcRequest>(&payload) {
+                        Ok(request) if request.method == "subscribe" => {
+                            let subscribed: Result<Vec<Topic>, _> =
+                                request.params.into_iter().map(serde_json::from_value).collect();
+                            match subscribed {
+                                Ok(subscribed) => {
+                                    topics.extend(subscribed);
+                                    RpcResponse { id: request.id, result: Value::Bool(true), error: Value::Null }
+                                }
+                                Err(e) => RpcResponse { id: request.id, result: Value::Null, error: json!(e.to_string()) },
+                            }
+                        }
+                        Ok(request) if request.method == "unsubscribe" => {
+                            topics.clear();
+                            RpcResponse { id: request.id, result: Value::Bool(true), error: Value::Null }
+                        }
+                        Ok(request) => dispatch(&blockchain, request).await,
+                        Err(e) => RpcResponse { id: Value::Null, result: Value::Null, error: json!(e.to_string()) },
+                    };
+                    serde_json::to_vec(&response).ok().map(|body| (websocket::OPCODE_TEXT, body))
+                }
+                Some((websocket::OPCODE_PING, payload)) => Some((websocket::OPCODE_PONG, payload)),
+                Some((websocket::OPCODE_CLOSE, _)) | None => {
+                    let _ = websocket::write_frame(&mut writer, websocket::OPCODE_CLOSE, &[]).await;
+                    return;
+                }
+                Some(_) => None,
+            },
+            event = events.recv() => match event {
+                Ok(event) if topics.iter().any(|topic| topic.matches(&event)) => {
+                    let notification = json!({ "method": "notification", "params": [event] });
+                    serde_json::to_vec(&notification).ok().map(|body| (websocket::OPCODE_TEXT, body))
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => None,
+                Err(RecvError::Closed) => return,
+            },
+        };
+
+        if let Some((opcode, payload)) = reply {
+            if websocket::write_frame(&mut writer, opcode, &payload).await.is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::hex;
+
+/// The prefix of an address whose funds are locked by a `Script` rather than owned by a name or key
+pub const SCRIPT_ADDRESS_PREFIX: &str = "script:";
+
+/// The maximum number of operations a `Script` may contain
+pub const MAX_SCRIPT_OPS: usize = 201;
+
+/// The maximum number of items on the stack while `Script`s are evaluated
+pub const MAX_STACK_SIZE: usize = 1000;
+
+/// The maximum number of public keys of a `CheckMultiSig`
+pub const MAX_MULTISIG_KEYS: usize = 20;
+
+/// A single operation of a `Script`
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Op {
+    /// Pushes bytes, such as a signature or public key, onto the stack
+    Push(Vec<u8>),
+
+    /// Pushes a number onto the stack
+    Number(i64),
+
+    /// Duplicates the top item
+    Dup,
+
+    /// Removes the top item
+    Drop,
+
+    /// Replaces the top item by its SHA-256 hash
+    Hash256,
+
+    /// Pops two items and pushes whether they are equal
+    Equal,
+
+    /// Like `Equal`, but fails the `Script` instead of pushing `false`
+    EqualVerify,
+
+    /// Pops an item and fails the `Script` unless it is true
+    Verify,
+
+    /// Pops a public key and a signature and pushes whether the signature signs the `Transaction`
+    CheckSig,
+
+    /// Like `CheckSig`, but fails the `Script` instead of pushing `false`
+    CheckSigVerify,
+
+    /// Pops `n`, `n` public keys, `m` and `m` signatures, and pushes whether every signature was
+    /// made by a different one of the public keys, in the same order
+    CheckMultiSig,
+
+    /// Pops a block height and fails the `Script` unless the `Transaction` is included at or after it
+    CheckHeightVerify,
+
+    /// Pops a Unix timestamp and fails the `Script` unless the `Transaction` is included at or after it
+    CheckTimeVerify,
+}
+
+/// A `Script` is a program for a small stack machine deciding whether funds may be spent
+///
+/// Funds sent to `Script::address` are locked by the `Script`. Spending them requires a
+/// `Transaction` carrying the `Script` as its redeem script, and an unlocking `Script` which
+/// pushes the data, usually signatures, making it succeed
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct Script(pub Vec<Op>);
+impl Script {
+    /// Locks funds to the owner of a single Ed25519 key, only revealed when they are spent
+    ///
+    /// # Parameters
+    /// - `public_key` - The public key of the owner
+    pub fn pay_to_public_key_hash(public_key: &[u8]) -> Self {
+        Script(vec![Op::Dup, Op::Hash256, Op::Push(sha256(public_key)), Op::EqualVerify, Op::CheckSig])
+    }
+
+    /// Unlocks a `Script::pay_to_public_key_hash`
+    ///
+    /// # Parameters
+    /// - `signature` - The signature of the `Transaction` by the owner
+    /// - `public_key` - The public key of the owner
+    pub fn unlock_public_key_hash(signature: Vec<u8>, public_key: Vec<u8>) -> Self {
+        Script(vec![Op::Push(signature), Op::Push(public_key)])
+    }
+
+    /// Locks funds so that `threshold` of the `public_keys` have to sign to spend them
+    ///
+    /// # Parameters
+    /// - `threshold` - The number of signatures required
+    /// - `public_keys` - The public keys allowed to sign
+    pub fn multisig(threshold: usize, public_keys: &[Vec<u8>]) -> Self {
+        let mut ops = vec![Op::Number(threshold as i64)];
+        ops.extend(public_keys.iter().cloned().map(Op::Push));
+        ops.extend([Op::Number(public_keys.len() as i64), Op::CheckMultiSig]);
+        Script(ops)
+    }
+
+    /// Unlocks a `Script::multisig`
+    ///
+    /// # Parameters
+    /// - `signatures` - The signatures, in the order of the public keys which made them
+    pub fn unlock_multisig(signatures: Vec<Vec<u8>>) -> Self {
+        Script(signatures.into_iter().map(Op::Push).collect())
+    }
+
+    /// Prevents funds locked by `script` from being spent before a block height
+    pub fn after_height(height: u64, script: Script) -> Self {
+        let mut ops = vec![Op::Number(height as i64), Op::CheckHeightVerify];
+        ops.extend(script.0);
+        Script(ops)
+    }
+
+    /// Prevents funds locked by `script` from being spent before a Unix timestamp
+    pub fn after_time(time: u64, script: Script) -> Self {
+        let mut ops = vec![Op::Number(time as i64), Op::CheckTimeVerify];
+        ops.extend(script.0);
+        Script(ops)
+    }
+
+    /// Gets the address of the funds locked by this `Script`
+    pub fn address(&self) -> String {
+        let serialized = serde_json::to_vec(self).expect("Scripts are always serializable");
+        format!("{}{}", SCRIPT_ADDRESS_PREFIX, hex::encode(&sha256(&serialized)))
+    }
+
+    /// Checks whether this `Script` only pushes data, as unlocking `Script`s must
+    pub fn is_push_only(&self) -> bool {
+        self.0.iter().all(|op| matches!(op, Op::Push(_) | Op::Number(_)))
+    }
+}
+
+/// What a `Script` is evaluated against
+pub struct ScriptContext {
+    /// The message signatures are checked against, the `Transaction::signature_hash`
+    pub message: Vec<u8>,
+
+    /// The index of the `Block` including the `Transaction`
+    pub height: u64,
+
+    /// The median time past of the `Block` including the `Transaction`
+    pub time: u64,
+}
+
+fn sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(data).to_vec()
+}
+
+fn encode_number(number: i64) -> Vec<u8> {
+    number.to_le_bytes().to_vec()
+}
+
+fn decode_number(item: &[u8]) -> Result<i64, &'static str> {
+    if item.len() > 8 {
+        return Err("Script number is too long");
+    }
+    let mut bytes = [0; 8];
+    bytes[..item.len()].copy_from_slice(item);
+    Ok(i64::from_le_bytes(bytes))
+}
+
+fn is_true(item: &[u8]) -> bool {
+    item.iter().any(|byte| *byte != 0)
+}
+
+fn check_signature(public_key: &[u8], signature: &[u8], message: &[u8]) -> bool {
+    UnparsedPublicKey::new(&ED25519, public_key).verify(message, signature).is_ok()
+}
+
+/// The stack of a `Script` being evaluated
+struct Stack(Vec<Vec<u8>>);
+impl Stack {
+    fn push(&mut self, item: Vec<u8>) -> Result<(), &'static str> {
+        if self.0.len() >= MAX_STACK_SIZE {
+            return Err("Script stack overflow");
+        }
+        self.0.push(item);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<Vec<u8>, &'static str> {
+        self.0.pop().ok_or("Script stack underflow")
+    }
+
+    fn pop_number(&mut self) -> Result<i64, &'static str> {
+        decode_number(&self.pop()?)
+    }
+
+    fn pop_count(&mut self) -> Result<usize, &'static str> {
+        usize::try_from(self.pop_number()?)
+            .ok()
+            .filter(|count| *count <= MAX_MULTISIG_KEYS)
+            .ok_or("Invalid multisig key count")
+    }
+}
+
+fn execute(script: &Script, stack: &mut Stack, context: &ScriptContext) -> Result<(), &'static str> {
+    if script.0.len() > MAX_SCRIPT_OPS {
+        return Err("Script has too many operations");
+    }
+    for op in &script.0 {
+        match op {
+            Op::Push(data) => stack.push(data.clone())?,
+            Op::Number(number) => stack.push(encode_number(*number))?,
+            Op::Dup => {
+                let top = stack.0.last().ok_or("Script stack underflow")?.clone();
+                stack.push(top)?;
+            }
+            Op::Drop => {
+                stack.pop()?;
+            }
+            Op::Hash256 => {
+                let item = stack.pop()?;
+                stack.push(sha256(&item))?;
+            }
+            Op::Equal | Op::EqualVerify => {
+                let equal = stack.pop()? == stack.pop()?;
+                if *op == Op::EqualVerify {
+                    if !equal {
+                        return Err("Script EqualVerify failed");
+                    }
+                } else {
+                    stack.push(vec![equal as u8])?;
+                }
+            }
+            Op::Verify => {
+                if !is_true(&stack.pop()?) {
+                    return Err("Script Verify failed");
+                }
+            }
+            Op::CheckSig | Op::CheckSigVerify => {
+                let public_key = stack.pop()?;
+                let signature = stack.pop()?;
+                let valid = check_signature(&public_key, &signature, &context.message);
+                if *op == Op::CheckSigVerify {
+                    if !valid {
+                        return Err("Script signature is invalid");
+                    }
+                } else {
+                    stack.push(vec![valid as u8])?;
+                }
+            }
+            Op::CheckMultiSig => {
+                let key_count = stack.pop_count()?;
+                let public_keys = (0..key_count).map(|_| stack.pop()).collect::<Result<Vec<_>, _>>()?;
+                let threshold = stack.pop_count()?;
+                if threshold > key_count {
+                    return Err("Multisig threshold exceeds the number of keys");
+                }
+                let signatures = (0..threshold).map(|_| stack.pop()).collect::<Result<Vec<_>, _>>()?;
+
+                // Both were popped in reverse, so the signatures are matched to the keys in order
+                let mut keys = public_keys.iter();
+                let valid = signatures.iter().all(|signature| {
+                    keys.any(|public_key| check_signature(public_key, signature, &context.message))
+                });
+                stack.push(vec![valid as u8])?;
+            }
+            Op::CheckHeightVerify => {
+                if context.height < stack.pop_number()?.max(0) as u64 {
+                    return Err("Script is locked until a later block height");
+                }
+            }
+            Op::CheckTimeVerify => {
+                if context.time < stack.pop_number()?.max(0) as u64 {
+                    return Err("Script is locked until a later time");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Evaluates an unlocking `Script` followed by the locking `Script` it spends
+///
+/// # Parameters
+/// - `unlocking` - The unlocking `Script`, which may only push data
+/// - `locking` - The locking `Script` the funds were sent to
+/// - `context` - The `Transaction` and `Block` being validated
+///
+/// # Returns
+/// - `Result<(), &str>` - `Ok(())` if the `Script`s leave a true item on top of the stack, or
+///   an `Err(&str)` describing why they failed
+pub fn verify(unlocking: &Script, locking: &Script, context: &ScriptContext) -> Result<(), &'static str> {
+    if !unlocking.is_push_only() {
+        return Err("Unlocking script must only push data");
+    }
+    let mut stack = Stack(Vec::new());
+    execute(unlocking, &mut stack, context)?;
+    execute(locking, &mut stack, context)?;
+    match stack.0.last() {
+        Some(top) if is_true(top) => Ok(()),
+        _ => Err("Script evaluated to false"),
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Notify;
+
+/// An open connection of a peer or client to a node
+pub struct Session {
+    /// The IP address of the peer
+    pub ip: IpAddr,
+
+    /// The hex encoded Ed25519 identity the peer authenticated with
+    pub identity: String,
+
+    /// The software version the peer announced in its `Version`
+    pub software_version: String,
+
+    /// When the connection was opened
+    pub connected_at: Instant,
+
+    closer: Arc<Notify>,
+}
+
+/// A `SessionRegistry` tracks the open `Session`s of a node, so they can be listed
+/// and closed when their peer disconnects
+#[derive(Default)]
+pub struct SessionRegistry {
+    next_id: u64,
+    sessions: HashMap<u64, Session>,
+}
+impl SessionRegistry {
+    /// Constructs a new, empty `SessionRegistry`
+    pub fn new() -> Self {
+        SessionRegistry::default()
+    }
+
+    /// Registers a new `Session`
+    ///
+    /// # Returns
+    /// - `(u64, Arc<Notify>)` - The id of the `Session`, and a `Notify` which is notified
+    ///   when the `Session` should be closed
+    pub fn open(&mut self, ip: IpAddr, identity: String, software_version: String) -> (u64, Arc<Notify>) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let closer = Arc::new(Notify::new());
+        self.sessions.insert(id, Session {
+            ip,
+            identity,
+            software_version,
+            connected_at: Instant::now(),
+            closer: closer.clone(),
+        });
+        (id, closer)
+    }
+
+    /// Unregisters a `Session` once its connection is closed
+    pub fn close(&mut self, id: u64) {
+        self.sessions.remove(&id);
+    }
+
+    /// Asks every `Session` of the peer with the given identity to close
+    ///
+    /// # Returns
+    /// - `usize` - The number of `Session`s asked to close
+    pub fn disconnect(&mut self, identity: &str) -> usize {
+        let mut closed = 0;
+        for session in self.sessions.values().filter(|session| session.identity == identity) {
+            session.closer.notify_one();
+            closed += 1;
+        }
+        closed
+    }
+
+    /// Gets the open `Session`s
+    pub fn sessions(&self) -> impl Iterator<Item = &Session> {
+        self.sessions.values()
+    }
+
+    /// The number of open `Session`s
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Whether no `Session` is open
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
@@ -0,0 +1,110 @@
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+use crate::block::Block;
+use crate::blockchain::{Blockchain, SharedBlockchain};
+use crate::chain_spec::ChainSpec;
+use crate::events::Event;
+use crate::transaction::Transaction;
+
+/// The file in the data directory holding the `Block`s of the `Blockchain`
+pub const CHAIN_FILE: &str = "chain.json";
+
+/// The file in the data directory holding the `Transaction`s of the `Mempool`
+pub const MEMPOOL_FILE: &str = "mempool.json";
+
+/// Writes a value as JSON, replacing the file only once it is completely written
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Error> {
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, serde_json::to_vec(value)?)?;
+    fs::rename(temporary, path)
+}
+
+/// Reads a JSON file, or `None` if it does not exist
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, Error> {
+    match fs::read(path) {
+        Ok(contents) => serde_json::from_slice(&contents).map(Some).map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Saves the chain and `Mempool` of a `Blockchain` to a data directory
+///
+/// # Parameters
+/// - `blockchain` - The `Blockchain` to be saved
+/// - `data_dir` - The directory the files are written to, created if missing
+pub fn save(blockchain: &Blockchain, data_dir: impl AsRef<Path>) -> Result<(), Error> {
+    let data_dir = data_dir.as_ref();
+    fs::create_dir_all(data_dir)?;
+    write_json(&data_dir.join(CHAIN_FILE), &blockchain.chain)?;
+    write_json(&data_dir.join(MEMPOOL_FILE), &blockchain.mempool.transactions)
+}
+
+/// Saves a `SharedBlockchain` to a data directory every time a `Block` is appended, so a node
+/// which crashes or is killed does not lose the chain it synced
+///
+/// # Parameters
+/// - `blockchain` - The `SharedBlockchain` to be saved
+/// - `data_dir` - The directory the files are written to, created if missing
+pub async fn save_on_new_blocks(blockchain: SharedBlockchain, data_dir: PathBuf) {
+    let mut events = blockchain.read().await.events.subscribe();
+    loop {
+        match events.recv().await {
+            Ok(Event::NewBlock(_)) | Err(RecvError::Lagged(_)) => {}
+            Ok(_) => continue,
+            Err(RecvError::Closed) => return,
+        }
+        if let Err(e) = save(&*blockchain.read().await, &data_dir) {
+            eprintln!("Failed to save the blockchain to {}: {}", data_dir.display(), e);
+        }
+    }
+}
+
+/// Loads a `Blockchain` saved by `save`, starting from the genesis `Block` if nothing was saved
+///
+/// Every stored `Block` was validated when it was appended, so it is not validated again.
+/// Instead, a single pass checks that each `Block` extends the previous one and that its hash
+/// matches its contents, which rejects a corrupted or tampered data directory, while the
+/// `Contract`s are applied once and checked against the state root of the tip
+///
+/// # Parameters
+/// - `spec` - The `ChainSpec` of the network
+/// - `data_dir` - The directory the files are read from
+///
+/// # Returns
+/// - `Result<Blockchain, Error>` - The `Blockchain`, or an `Error` if the files could not be
+///   read or hold an invalid chain
+pub fn load(spec: ChainSpec, data_dir: impl AsRef<Path>) -> Result<Blockchain, Error> {
+    let data_dir = data_dir.as_ref();
+    let mut blockchain = Blockchain::from_spec(spec);
+
+    if let Some(chain) = read_json::<Vec<Block>>(&data_dir.join(CHAIN_FILE))? {
+        let mut blocks = chain.into_iter();
+        if blocks.next().map(|genesis| genesis.hash) != Some(blockchain.chain[0].hash.clone()) {
+            return Err(Error::new(ErrorKind::InvalidData, "Stored chain belongs to a different network"));
+        }
+        for block in blocks {
+            if block.index as usize != blockchain.chain.len() || block.previous_hash != blockchain.get_latest_block_hash() {
+                return Err(Error::new(ErrorKind::InvalidData, "Stored block does not extend the previous block"));
+            }
+            if block.hash != Block::calculate_hash(&block) {
+                return Err(Error::new(ErrorKind::InvalidData, "Stored block hash does not match its contents"));
+            }
+            blockchain.contracts.apply(&block.transaction);
+            blockchain.chain.push(block);
+        }
+        if blockchain.chain.len() > 1 && blockchain.chain[blockchain.chain.len() - 1].state_root != blockchain.contracts.root() {
+            return Err(Error::new(ErrorKind::InvalidData, "Stored chain does not match its contract state"));
+        }
+    }
+    if let Some(transactions) = read_json::<Vec<Transaction>>(&data_dir.join(MEMPOOL_FILE))? {
+        for transaction in transactions {
+            blockchain.add_transaction(transaction);
+        }
+    }
+    Ok(blockchain)
+}
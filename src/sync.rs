@@ -0,0 +1,160 @@
+use std::io::{Error, ErrorKind};
+use tokio::task::JoinSet;
+use crate::block::{Block, BlockHeader};
+use crate::blockchain::{submit_shared_block, Blockchain, SharedBlockchain};
+use crate::consensus::Consensus;
+use crate::network::{send_request_as, Message, MAX_HEADERS};
+use crate::version::Version;
+
+/// The number of `Block` bodies requested from a peer in a single `GetBlocks` request
+pub const BLOCKS_PER_REQUEST: usize = 16;
+
+/// Builds a block locator, a list of hashes describing a chain to a peer
+///
+/// The locator starts at the tip and steps back exponentially further, ending
+/// with the genesis `Block`, so a peer can find the most recent `Block` both
+/// chains share in a few hashes
+///
+/// # Parameters
+/// - `chain` - The `Block`s or `BlockHeader`s of the chain, oldest first, starting with the genesis
+/// - `hash` - Gets the hash of an element of `chain`
+///
+/// # Returns
+/// - `Vec<String>` - The hashes of the located `Block`s, newest first
+pub fn block_locator<T>(chain: &[T], hash: impl Fn(&T) -> &String) -> Vec<String> {
+    let mut locator = Vec::new();
+    let mut index = chain.len() as i64 - 1;
+    let mut step = 1;
+    while index > 0 {
+        locator.push(hash(&chain[index as usize]).clone());
+        if locator.len() >= 10 {
+            step *= 2;
+        }
+        index -= step;
+    }
+    if let Some(genesis) = chain.first() {
+        locator.push(hash(genesis).clone());
+    }
+    locator
+}
+
+/// Validates a batch of `BlockHeader`s received from a peer
+///
+/// Every header must follow the previous one, its hash must match its fields and it must
+/// be sealed according to the `Consensus` engine of the `Blockchain`
+///
+/// # Parameters
+/// - `tip` - The `BlockHeader` the batch is expected to extend
+/// - `headers` - The received `BlockHeader`s, oldest first
+/// - `consensus` - The `Consensus` engine of the `Blockchain`
+/// - `difficulty` - The difficulty target of the `Blockchain`
+/// - `blockchain` - The local `Blockchain`, if any, which the `Consensus` engine may check the
+///   header extending its tip against
+///
+/// # Returns
+/// - `Result<(), &str>` - `Ok(())` if the header chain is valid, or an `Err(&str)` otherwise
+pub fn validate_headers(tip: &BlockHeader, headers: &[BlockHeader], consensus: &dyn Consensus, difficulty: usize,
+                        blockchain: Option<&Blockchain>) -> Result<(), &'static str> {
+    let mut previous = tip;
+    for header in headers {
+        if header.index != previous.index + 1 {
+            return Err("Header index does not follow the previous header");
+        }
+        if header.previous_hash.as_ref().map(|hash| hash.0.as_str()) != Some(previous.hash.as_str()) {
+            return Err("Header does not reference the previous header");
+        }
+        if header.hash != header.calculate_hash() {
+            return Err("Header hash does not match its contents");
+        }
+        consensus.verify_header(header, previous, difficulty, blockchain)?;
+        previous = header;
+    }
+    Ok(())
+}
+
+/// Performs a headers-first initial block download from a set of peers
+///
+/// The header chain is requested from the first peer using a block locator and validated
+/// before any body is downloaded. `Block` bodies are then requested in batches spread
+/// across all peers in parallel, checked against their headers and appended in order
+///
+/// # Parameters
+/// - `blockchain` - The local `Blockchain` to be brought up to date
+/// - `peers` - The `address:port` of each peer to download from
+///
+/// # Returns
+/// - `Result<usize, Error>` - The number of `Block`s appended to `blockchain`
+pub async fn initial_block_download(blockchain: SharedBlockchain, peers: &[String])
+                                    -> Result<usize, Error> {
+    let source = peers.first().ok_or(Error::new(ErrorKind::InvalidInput, "No peers to sync from"))?;
+    let mut downloaded = 0;
+
+    loop {
+        let (locator, tip, consensus, difficulty, local) = {
+            let blockchain = blockchain.read().await;
+            let tip = blockchain.chain.last().map(Block::header).expect("Blockchain has a genesis block");
+            let local = Version::new(&blockchain.spec.network_id, tip.index as u64, 0);
+            (blockchain.block_locator(), tip, blockchain.consensus.clone(), blockchain.difficulty, local)
+        };
+
+        let headers = match send_request_as(source, &local, &Message::GetHeaders(locator)).await? {
+            Message::Headers(headers) => headers,
+            _ => return Err(Error::new(ErrorKind::InvalidData, "Expected headers")),
+        };
+        if headers.is_empty() {
+            return Ok(downloaded);
+        }
+        validate_headers(&tip, &headers, consensus.as_ref(), difficulty, Some(&*blockchain.read().await))
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        println!("Received headers up to #{}", headers.last().map(|header| header.index).unwrap_or(0));
+
+        let blocks = download_bodies(&headers, peers, &local).await?;
+        for block in blocks {
+            submit_shared_block(&blockchain, block).await.map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            downloaded += 1;
+        }
+        println!("Synced to block #{}", blockchain.read().await.chain.len() - 1);
+
+        if headers.len() < MAX_HEADERS {
+            return Ok(downloaded);
+        }
+    }
+}
+
+/// Downloads the `Block` bodies of `headers` in parallel, spreading batches across `peers`
+async fn download_bodies(headers: &[BlockHeader], peers: &[String], local: &Version) -> Result<Vec<Block>, Error> {
+    let mut tasks = JoinSet::new();
+    for (batch, chunk) in headers.chunks(BLOCKS_PER_REQUEST).enumerate() {
+        let peer = peers[batch % peers.len()].clone();
+        let hashes: Vec<String> = chunk.iter().map(|header| header.hash.clone()).collect();
+        let local = local.clone();
+        tasks.spawn(async move {
+            (batch, send_request_as(&peer, &local, &Message::GetBlocks(hashes)).await)
+        });
+    }
+
+    let batches = headers.len().div_ceil(BLOCKS_PER_REQUEST);
+    let mut received: Vec<Option<Vec<Block>>> = vec![None; batches];
+    let mut completed = 0;
+    while let Some(result) = tasks.join_next().await {
+        let (batch, response) = result.map_err(Error::other)?;
+        let blocks = match response? {
+            Message::Blocks(blocks) => blocks,
+            _ => return Err(Error::new(ErrorKind::InvalidData, "Expected blocks")),
+        };
+        completed += blocks.len();
+        println!("Downloaded {}/{} blocks ({}%)", completed, headers.len(), completed * 100 / headers.len());
+        received[batch] = Some(blocks);
+    }
+
+    let blocks: Vec<Block> = received.into_iter().flatten().flatten().collect();
+    if blocks.len() != headers.len() {
+        return Err(Error::new(ErrorKind::InvalidData, "Peers did not return every requested block"));
+    }
+    for (block, header) in blocks.iter().zip(headers) {
+        if block.hash != header.hash || Block::calculate_hash(block) != header.hash {
+            return Err(Error::new(ErrorKind::InvalidData, "Block body does not match its header"));
+        }
+    }
+    Ok(blocks)
+}
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use crate::arc_string::ArcString;
+use crate::block::Block;
+use crate::blockchain::Blockchain;
+use crate::chain_spec::BlockLimits;
+use crate::miner::Miner;
+use crate::transaction::Transaction;
+
+/// The length of the hex-encoded Ed25519 public key of the validator sealing a `Block`
+const SEAL_KEY_LENGTH: usize = 64;
+
+/// The length of the Ed25519 signature of the validator sealing a `Block`
+const SEAL_SIGNATURE_LENGTH: usize = 64;
+
+/// A `BlockTemplate` describes the next `Block` a `Miner` should work on
+///
+/// It is handed out by the node to external `Miner` processes, which only need to
+/// search for a `nonce` whose hash meets the `target` and submit the resulting `Block`
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BlockTemplate {
+    /// The position the mined `Block` will have in the `Blockchain`
+    pub index: u32,
+
+    /// The time, in seconds, the template was created
+    pub timestamp: u64,
+
+    /// The hash of the current tip of the `Blockchain`
+    pub previous_hash: Option<ArcString>,
+
+    /// The number of leading zeros a valid `Block` hash must have
+    pub difficulty: usize,
+
+    /// The hash prefix a valid `Block` hash must start with
+    pub target: String,
+
+    /// The `Transaction` selected from the `Mempool` to be included in the `Block`
+    pub transaction: Transaction,
+
+    /// The reward payouts for the `Miner` of the `Block`
+    pub coinbase: Vec<Transaction>,
+
+    /// The root of the `ContractState` once `transaction` is applied
+    #[serde(default)]
+    pub state_root: String,
+
+    /// The `BlockLimits` the mined `Block` must respect, such as when a pool replaces
+    /// the coinbase
+    #[serde(default)]
+    pub limits: BlockLimits,
+}
+impl BlockTemplate {
+    /// Builds a `BlockTemplate` on top of the current tip of the given `Blockchain`
+    ///
+    /// # Parameters
+    /// - `blockchain` - The `Blockchain` the mined `Block` will extend
+    /// - `miner` - The identifier of the `Miner` receiving the coinbase reward
+    ///
+    /// # Returns
+    /// - `Self` - A template containing the highest fee `Transaction` of the `Mempool` which
+    ///   keeps the `Block` within the `BlockLimits`, and a coinbase paying the block reward
+    ///   plus that fee to `miner`
+    pub fn new(blockchain: &Blockchain, miner: &str) -> Self {
+        let transaction = blockchain
+            .mempool
+            .select_within(transaction_budget(blockchain, miner))
+            .cloned()
+            .unwrap_or_else(|| Transaction::new(None, None, 0.0, None));
+        let reward = Miner::calculate_block_reward(blockchain) + transaction.total_fee();
+        let coinbase = Transaction::new(None, Some(miner.to_string()), reward, None);
+        let block = Block::new(
+            blockchain.chain.len() as u32,
+            transaction,
+            blockchain.get_latest_block_hash(),
+        );
+
+        BlockTemplate {
+            index: block.index,
+            timestamp: block.timestamp.max(blockchain.median_time_past() + 1),
+            previous_hash: block.previous_hash,
+            difficulty: blockchain.difficulty,
+            target: "0".repeat(blockchain.difficulty),
+            state_root: blockchain.next_state(&block.transaction).root(),
+            transaction: block.transaction,
+            coinbase: vec![coinbase],
+            limits: blockchain.spec.limits,
+        }
+    }
+
+    /// Converts this template into an unmined `Block`
+    pub fn to_block(&self) -> Block {
+        let mut block = Block::new(self.index, self.transaction.clone(), self.previous_hash.clone());
+        block.timestamp = self.timestamp;
+        block.coinbase = self.coinbase.clone();
+        block.state_root = self.state_root.clone();
+        block
+    }
+}
+
+/// Computes the largest `Transaction` a `Block` paying its coinbase to `miner` can include
+/// without exceeding the maximum block size, whatever `nonce` it is mined with
+///
+/// Room is also kept for the hash of the `Block`, and for the validator and signature a signed
+/// `Consensus` engine adds when it seals the `Block`
+fn transaction_budget(blockchain: &Blockchain, miner: &str) -> usize {
+    let mut block = Block::new(
+        blockchain.chain.len() as u32,
+        Transaction::new(None, None, 0.0, None),
+        blockchain.get_latest_block_hash(),
+    );
+    block.coinbase = vec![Transaction::new(None, Some(miner.to_string()), 0.0, None)];
+    block.state_root = blockchain.contracts.root();
+    block.nonce = u32::MAX;
+    block.validator = Some("0".repeat(SEAL_KEY_LENGTH));
+    block.signature = vec![0; SEAL_SIGNATURE_LENGTH];
+    block.hash = Block::calculate_hash(&block);
+    let overhead = block.size().saturating_sub(block.transaction.size());
+    blockchain.spec.limits.max_block_size.saturating_sub(overhead)
+}
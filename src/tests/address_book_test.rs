@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod address_book_test {
+    use std::env;
+    use std::fs;
+    use crate::address_book::{parse_peer, AddressBook, MAX_ADDRESSES, MAX_FAILURES};
+
+    #[test]
+    fn test_merge_keeps_latest_and_caps_future() {
+        let mut book = AddressBook::new();
+        book.add("127.0.0.1:9000", 100, 1000);
+        book.merge(vec![
+            ("127.0.0.1:9000".to_string(), 50),
+            ("127.0.0.1:9001".to_string(), 5000),
+            ("not an address".to_string(), 500),
+        ], 1000);
+
+        assert_eq!(book.len(), 2);
+        assert_eq!(book.entries["127.0.0.1:9000"].last_seen, 100);
+        assert_eq!(book.entries["127.0.0.1:9001"].last_seen, 1000);
+        assert_eq!(book.candidates(), vec!["127.0.0.1:9001", "127.0.0.1:9000"]);
+    }
+
+    #[test]
+    fn test_recent_skips_never_seen() {
+        let mut book = AddressBook::new();
+        book.add("127.0.0.1:9000", 0, 1000);
+        book.add("127.0.0.1:9001", 10, 1000);
+        assert_eq!(book.recent(10), vec![("127.0.0.1:9001".to_string(), 10)]);
+    }
+
+    #[test]
+    fn test_forget_after_failures() {
+        let mut book = AddressBook::new();
+        book.add("127.0.0.1:9000", 10, 1000);
+        for _ in 1..MAX_FAILURES {
+            book.mark_failed("127.0.0.1:9000");
+        }
+        book.mark_seen("127.0.0.1:9000", 20);
+        assert_eq!(book.entries["127.0.0.1:9000"].failures, 0);
+
+        for _ in 0..MAX_FAILURES {
+            book.mark_failed("127.0.0.1:9000");
+        }
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn test_configured_peers_pin_their_identity() {
+        let identity = "ab".repeat(32);
+        let mut book = AddressBook::new();
+        book.add_peer(&format!("{}@127.0.0.1:9000", identity.to_uppercase()));
+        book.add_peer("127.0.0.1:9001");
+        assert_eq!(parse_peer("127.0.0.1:9001"), ("127.0.0.1:9001", None));
+        assert_eq!(book.identity("127.0.0.1:9000"), Some(identity.as_str()));
+        assert_eq!(book.identity("127.0.0.1:9001"), None);
+
+        // Gossip cannot replace the pin, and a pinned peer is not forgotten
+        book.merge(vec![("127.0.0.1:9000".to_string(), 500)], 1000);
+        for _ in 0..MAX_FAILURES {
+            book.mark_failed("127.0.0.1:9000");
+            book.mark_failed("127.0.0.1:9001");
+        }
+        assert_eq!(book.identity("127.0.0.1:9000"), Some(identity.as_str()));
+        assert!(!book.entries.contains_key("127.0.0.1:9001"));
+    }
+
+    #[test]
+    fn test_evicts_oldest_when_full() {
+        let mut book = AddressBook::new();
+        for i in 0..MAX_ADDRESSES {
+            book.add(&format!("10.0.{}.{}:8080", i / 256, i % 256), i as u64 + 1, 10_000);
+        }
+        book.add("127.0.0.1:9000", 0, 10_000);
+        assert!(!book.entries.contains_key("127.0.0.1:9000"));
+
+        book.add("127.0.0.1:9001", 5000, 10_000);
+        assert_eq!(book.len(), MAX_ADDRESSES);
+        assert!(book.entries.contains_key("127.0.0.1:9001"));
+        assert!(!book.entries.contains_key("10.0.0.0:8080"));
+    }
+
+    #[test]
+    fn test_persistence() {
+        let path = env::temp_dir().join(format!("address_book_test_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut book = AddressBook::load(&path).unwrap();
+        assert!(book.is_empty());
+        book.mark_seen("127.0.0.1:9000", 42);
+        book.save().unwrap();
+
+        let loaded = AddressBook::load(&path).unwrap();
+        assert_eq!(loaded.entries, book.entries);
+        fs::remove_file(&path).unwrap();
+    }
+}
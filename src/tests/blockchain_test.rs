@@ -3,7 +3,10 @@ mod blockchain_test {
     use std::sync::Arc;
     use crate::arc_string::ArcString;
     use crate::block::Block;
-    use crate::blockchain::Blockchain;
+    use crate::blockchain::{submit_shared_block, BlockError, Blockchain, MAX_FUTURE_BLOCK_TIME, MEDIAN_TIME_SPAN};
+    use crate::miner::Miner;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{address, funded_blockchain, generate_key_pair, mine, signed};
     use crate::transaction::Transaction;
 
     fn sample_blockchain_instance() -> Blockchain {
@@ -105,4 +108,152 @@ mod blockchain_test {
         assert_eq!(blockchain.chain[3].previous_hash,
                    Some(ArcString::from(Arc::from(blockchain.chain[2].hash.clone()))));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_submit_shared_block_accepts_one_of_competing_blocks() {
+        let blockchain = tokio::sync::RwLock::new(Blockchain::new(1));
+        let (first, second) = {
+            let blockchain = blockchain.read().await;
+            (Miner::mine_template(&BlockTemplate::new(&blockchain, "Alice")),
+             Miner::mine_template(&BlockTemplate::new(&blockchain, "Bob")))
+        };
+
+        let (first, second) = tokio::join!(
+            submit_shared_block(&blockchain, first),
+            submit_shared_block(&blockchain, second)
+        );
+        assert!(first.is_ok() != second.is_ok());
+        assert_eq!(blockchain.read().await.chain.len(), 2);
+        assert!(blockchain.read().await.is_valid().unwrap());
+    }
+
+    #[test]
+    fn test_fee_estimation_and_transaction_id_reuse() {
+        let alice = generate_key_pair();
+        let mut blockchain = funded_blockchain(&[&alice]);
+        let minimum_rate = blockchain.mempool.min_relay_fee_per_byte;
+        assert_eq!(blockchain.estimate_fee_rate(), minimum_rate);
+
+        for fee in [1.0, 2.0, 3.0] {
+            let mut transaction = signed(&alice, "Bob", 1.0, fee);
+            transaction.transaction_id = Some(format!("payment-{fee}"));
+            transaction.authorize(&alice);
+            mine(&mut blockchain, transaction);
+        }
+        let median = &blockchain.chain[2].transaction;
+        assert_eq!(blockchain.estimate_fee_rate(), 2.0 / median.size() as f64);
+
+        // The fees are paid by the sender, not by the producer of each block
+        assert_eq!(blockchain.ledger().balance(&address(&alice)), 1000.0 - 9.0);
+
+        let mut reused = signed(&alice, "Carol", 1.0, 5.0);
+        reused.transaction_id = Some("payment-1".to_string());
+        reused.authorize(&alice);
+        assert!(!blockchain.add_transaction(reused.clone()));
+        let mut template = BlockTemplate::new(&blockchain, "Miner");
+        template.transaction = reused;
+        assert_eq!(blockchain.submit_block(Miner::mine_template(&template)),
+                   Err(BlockError::Invalid("Block includes a transaction id already used by its sender")));
+    }
+
+    #[test]
+    fn test_senders_must_authorize_and_fund_transactions() {
+        let (alice, mallory) = (generate_key_pair(), generate_key_pair());
+        let blockchain = funded_blockchain(&[&alice]);
+        let submit = |transaction: Transaction| {
+            let mut template = BlockTemplate::new(&blockchain, "Miner");
+            template.coinbase[0].amount += transaction.total_fee();
+            template.transaction = transaction;
+            blockchain.validate_block(&Miner::mine_template(&template))
+        };
+
+        // A producer cannot mint coins through the fee of a transaction it made up
+        let unsigned = Transaction::new(Some("Miner".to_string()), Some("Bob".to_string()), 0.0, Some(1000.0));
+        assert_eq!(submit(unsigned.clone()), Err(BlockError::Invalid("Transaction is not authorized by its sender")));
+        let mut forged = Transaction::new(Some(address(&alice)), Some("Bob".to_string()), 10.0, Some(1.0));
+        forged.authorize(&mallory);
+        assert_eq!(submit(forged.clone()), Err(BlockError::Invalid("Transaction is not authorized by its sender")));
+        assert_eq!(submit(signed(&mallory, "Bob", 10.0, 1.0)), Err(BlockError::Invalid("Insufficient balance to cover the amount and fees")));
+        assert_eq!(submit(signed(&alice, "Bob", 999.5, 1.0)), Err(BlockError::Invalid("Insufficient balance to cover the amount and fees")));
+        assert_eq!(submit(signed(&alice, "Bob", -10.0, 1.0)), Err(BlockError::Invalid("Transaction amount and fee must not be negative")));
+        let unfunded = Transaction::new(None, Some("Bob".to_string()), 0.0, Some(5.0));
+        assert_eq!(submit(unfunded), Err(BlockError::Invalid("Transaction without a sender cannot pay an amount or fee")));
+        assert!(submit(signed(&alice, "Bob", 998.0, 1.0)).is_ok());
+
+        let mut blockchain = funded_blockchain(&[&alice]);
+        assert!(!blockchain.add_transaction(unsigned));
+        assert!(!blockchain.add_transaction(forged));
+
+        // Only the sender can replace its own pending transaction
+        let mut pending = signed(&alice, "Bob", 10.0, 1.0);
+        pending.transaction_id = Some("payment".to_string());
+        pending.authorize(&alice);
+        assert!(blockchain.add_transaction(pending.clone()));
+        let mut replacement = Transaction::new(Some(address(&alice)), Some(address(&mallory)), 10.0, Some(5.0));
+        replacement.transaction_id = pending.transaction_id.clone();
+        replacement.authorize(&mallory);
+        assert!(!blockchain.add_transaction(replacement));
+        assert!(blockchain.mempool.contains(&pending.calculate_hash()));
+    }
+
+    #[test]
+    fn test_block_timestamp_rules() {
+        let mut blockchain = Blockchain::new(1);
+        for _ in 0..MEDIAN_TIME_SPAN {
+            let block = Miner::mine_template(&BlockTemplate::new(&blockchain, "Miner"));
+            blockchain.submit_block(block).unwrap();
+        }
+        let median_time_past = blockchain.median_time_past();
+        assert!(median_time_past > blockchain.chain[0].timestamp);
+
+        let mut template = BlockTemplate::new(&blockchain, "Miner");
+        template.timestamp = median_time_past;
+        assert_eq!(blockchain.validate_block(&Miner::mine_template(&template)),
+                   Err(BlockError::Invalid("Block timestamp is not after the median time of the previous blocks")));
+
+        template.timestamp = BlockTemplate::new(&blockchain, "Miner").timestamp + MAX_FUTURE_BLOCK_TIME + 60;
+        assert_eq!(blockchain.validate_block(&Miner::mine_template(&template)),
+                   Err(BlockError::Invalid("Block timestamp is too far in the future")));
+
+        template.timestamp = median_time_past + 1;
+        assert!(blockchain.validate_block(&Miner::mine_template(&template)).is_ok());
+    }
+
+    #[test]
+    fn test_blocks_off_the_tip_are_stale_or_orphans() {
+        let mut blockchain = Blockchain::new(1);
+        let competing = Miner::mine_template(&BlockTemplate::new(&blockchain, "Bob"));
+        let block = Miner::mine_template(&BlockTemplate::new(&blockchain, "Alice"));
+        blockchain.submit_block(block.clone()).unwrap();
+
+        // A peer which lost the race for a height, or relays a known block, is not at fault
+        assert!(matches!(blockchain.submit_block(block), Err(BlockError::Stale(_))));
+        assert!(matches!(blockchain.submit_block(competing), Err(BlockError::Stale(_))));
+
+        let mut ahead = BlockTemplate::new(&blockchain, "Alice");
+        ahead.index += 1;
+        assert!(matches!(blockchain.submit_block(Miner::mine_template(&ahead)), Err(BlockError::Orphan(_))));
+    }
+
+    #[test]
+    fn test_reject_invalid_coinbase_payouts() {
+        let alice = generate_key_pair();
+        let blockchain = funded_blockchain(&[&alice]);
+        let template = BlockTemplate::new(&blockchain, "Miner");
+        let invalid = Err(BlockError::Invalid("Block coinbase pays an invalid amount or no recipient"));
+
+        // The payouts still add up to the reward, but Alice would be debited
+        let mut debit = template.clone();
+        debit.coinbase[0].amount += 1000.0;
+        debit.coinbase.push(Transaction::new(None, Some(address(&alice)), -1000.0, None));
+        assert_eq!(blockchain.validate_block(&Miner::mine_template(&debit)), invalid);
+
+        let mut nan = template.clone();
+        nan.coinbase[0].amount = f64::NAN;
+        assert_eq!(blockchain.validate_block(&Miner::mine_template(&nan)), invalid);
+
+        let mut nowhere = template;
+        nowhere.coinbase[0].recipient = None;
+        assert_eq!(blockchain.validate_block(&Miner::mine_template(&nowhere)), invalid);
+    }
+}
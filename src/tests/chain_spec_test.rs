@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod chain_spec_test {
+    use std::collections::BTreeMap;
+    use crate::blockchain::Blockchain;
+    use crate::chain_spec::{BlockLimits, ChainSpec, ConsensusSpec, MAX_BLOCK_SIZE_LIMIT};
+
+    fn sample_spec() -> ChainSpec {
+        ChainSpec {
+            network_id: String::from("testnet"),
+            difficulty: 2,
+            allocations: BTreeMap::from([("Alice".to_string(), 100.0), ("Bob".to_string(), 50.0)]),
+            ..ChainSpec::default()
+        }
+    }
+
+    #[test]
+    fn test_genesis_is_deterministic() {
+        let first = Blockchain::from_spec(sample_spec());
+        let second = Blockchain::from_spec(sample_spec());
+        assert_eq!(first.chain[0].hash, second.chain[0].hash);
+        assert_eq!(first.chain[0].timestamp, 1700000000);
+
+        let other = Blockchain::from_spec(ChainSpec { network_id: String::from("mainnet"), ..sample_spec() });
+        assert_ne!(first.chain[0].hash, other.chain[0].hash);
+    }
+
+    #[test]
+    fn test_genesis_allocations() {
+        let blockchain = Blockchain::from_spec(sample_spec());
+        let ledger = blockchain.ledger();
+        assert_eq!(ledger.balance("Alice"), 100.0);
+        assert_eq!(ledger.balance("Bob"), 50.0);
+        assert!(blockchain.chain[0].hash.starts_with("00"));
+    }
+
+    #[test]
+    fn test_parse_chain_spec() {
+        let spec: ChainSpec = serde_json::from_str(r#"{
+            "network_id": "staging",
+            "genesis_timestamp": 1600000000,
+            "allocations": { "Alice": 1000.0 },
+            "difficulty": 1,
+            "reward": { "initial_reward": 10.0, "halving_interval": 100 },
+            "consensus": { "type": "proof_of_authority", "validators": ["00"], "block_interval": 2 }
+        }"#).unwrap();
+
+        assert_eq!(spec.reward.initial_reward, 10.0);
+        assert_eq!(spec.consensus, ConsensusSpec::ProofOfAuthority {
+            validators: vec![String::from("00")],
+            block_interval: 2,
+        });
+        assert_eq!(spec.consensus.block_interval(), Some(2));
+
+        let spec: ChainSpec = serde_json::from_str(r#"{
+            "network_id": "devnet",
+            "genesis_timestamp": 0,
+            "difficulty": 1,
+            "consensus": { "type": "proof_of_work" }
+        }"#).unwrap();
+        assert!(spec.allocations.is_empty());
+        assert_eq!(spec.consensus.block_interval(), None);
+    }
+
+    #[test]
+    fn test_reject_zero_halving_interval() {
+        assert_eq!(sample_spec().validate(), Ok(()));
+        let mut spec = sample_spec();
+        spec.reward.halving_interval = 0;
+        assert_eq!(spec.validate(), Err("The halving interval must be at least one block"));
+    }
+
+    #[test]
+    fn test_reject_invalid_allocations() {
+        for amount in [-1.0, f64::NAN, f64::INFINITY] {
+            let mut spec = sample_spec();
+            spec.allocations.insert(String::from("Mallory"), amount);
+            assert_eq!(spec.validate(), Err("Genesis allocations must be finite and not negative"));
+        }
+    }
+
+    #[test]
+    fn test_reject_unusable_block_limits() {
+        for max_block_size in [0, MAX_BLOCK_SIZE_LIMIT + 1] {
+            let spec = ChainSpec { limits: BlockLimits { max_block_size, ..BlockLimits::default() }, ..sample_spec() };
+            assert_eq!(spec.validate(), Err("The maximum block size must be positive and fit in a single frame"));
+        }
+        for max_block_transactions in [0, 1] {
+            let spec = ChainSpec { limits: BlockLimits { max_block_transactions, ..BlockLimits::default() }, ..sample_spec() };
+            assert_eq!(spec.validate(),
+                       Err("The maximum block transactions must leave room for a transaction and a coinbase payout"));
+        }
+        assert_eq!(sample_spec().validate(), Ok(()));
+    }
+}
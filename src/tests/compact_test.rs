@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod compact_test {
+    use crate::block::Block;
+    use crate::blockchain::Blockchain;
+    use crate::compact::CompactBlock;
+    use crate::mempool::Mempool;
+    use crate::miner::Miner;
+    use crate::template::BlockTemplate;
+    use crate::transaction::Transaction;
+
+    fn sample_block() -> (Block, Transaction) {
+        let mut blockchain = Blockchain::new(1);
+        let transaction = Transaction::new(Some("Alice".to_string()), 
+                                           Some("Bob".to_string()), 10.0, Some(1.0));
+        blockchain.mempool.add(transaction.clone());
+        let template = BlockTemplate::new(&blockchain, "Anirudh");
+        (Miner::mine_template(&template), transaction)
+    }
+
+    #[test]
+    fn test_reconstruct_from_mempool() {
+        let (block, transaction) = sample_block();
+        let compact = CompactBlock::from_block(&block);
+        assert_eq!(compact.short_ids.len(), 2);
+        assert_eq!(compact.prefilled.len(), 1);
+
+        let mut mempool = Mempool::new();
+        mempool.add(Transaction::new(Some("Charlie".to_string()), Some("Dave".to_string()), 5.0, None));
+        mempool.add(transaction);
+
+        let transactions = compact.match_transactions(&mempool);
+        assert!(CompactBlock::missing(&transactions).is_empty());
+
+        let reconstructed = compact.reconstruct(transactions).unwrap();
+        assert_eq!(reconstructed.hash, block.hash);
+        assert_eq!(Block::calculate_hash(&reconstructed), block.hash);
+    }
+
+    #[test]
+    fn test_request_missing_transactions() {
+        let (block, transaction) = sample_block();
+        let compact = CompactBlock::from_block(&block);
+
+        let mut transactions = compact.match_transactions(&Mempool::new());
+        assert_eq!(CompactBlock::missing(&transactions), vec![0]);
+        assert!(compact.reconstruct(transactions.clone()).is_err());
+
+        let mut forged = transaction.clone();
+        forged.amount = 1000.0;
+        let mut tampered = transactions.clone();
+        tampered[0] = Some(forged);
+        assert!(compact.reconstruct(tampered).is_err());
+
+        transactions[0] = Some(transaction);
+        assert_eq!(compact.reconstruct(transactions).unwrap().hash, block.hash);
+    }
+}
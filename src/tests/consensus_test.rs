@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod consensus_test {
+    use std::sync::Arc;
+    use ring::signature::Ed25519KeyPair;
+    use crate::block::Block;
+    use crate::blockchain::{BlockError, Blockchain};
+    use crate::chain_spec::{ChainSpec, ConsensusSpec};
+    use crate::consensus::{Consensus, ProofOfAuthority, ProofOfStake, ProofOfWork, StakeRegistry, TAKEOVER_INTERVALS};
+    use crate::hex;
+    use crate::ledger::Ledger;
+    use crate::sync::validate_headers;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{address, generate_key_pair};
+
+    fn staked_blockchain(validators: &[&Ed25519KeyPair]) -> Blockchain {
+        let allocations = validators
+            .iter()
+            .enumerate()
+            .map(|(i, key_pair)| (address(key_pair), 100.0 * (i + 1) as f64))
+            .collect();
+        Blockchain::from_spec(ChainSpec {
+            difficulty: 1,
+            allocations,
+            consensus: ConsensusSpec::ProofOfStake { minimum_stake: 10.0, block_interval: 5 },
+            ..ChainSpec::default()
+        })
+    }
+
+    #[test]
+    fn test_proof_of_work_seal_and_verify() {
+        let mut blockchain = Blockchain::new(2);
+        let mut block = BlockTemplate::new(&blockchain, "Anirudh").to_block();
+        ProofOfWork.seal(&mut block, &blockchain, None).unwrap();
+
+        assert!(ProofOfWork.verify(&block, &blockchain).is_ok());
+        assert!(blockchain.submit_block(block).is_ok());
+    }
+
+    #[test]
+    fn test_stake_registry_selection_is_deterministic() {
+        let mut ledger = Ledger::default();
+        ledger.balances.insert(hex::encode(&[1; 32]), 300.0);
+        ledger.balances.insert(hex::encode(&[2; 32]), 100.0);
+        ledger.balances.insert(hex::encode(&[3; 32]), 5.0);
+        ledger.balances.insert(String::from("Alice"), 1000.0);
+
+        let registry = StakeRegistry::from_ledger(&ledger, 10.0);
+        assert_eq!(registry.stakes.len(), 2);
+        assert_eq!(registry.select_proposer("seed", 1), registry.select_proposer("seed", 1));
+
+        let heavy = hex::encode(&[1; 32]);
+        let selected = (0..1000)
+            .filter(|slot| registry.select_proposer("seed", *slot) == Some(heavy.as_str()))
+            .count();
+        assert!(selected > 650 && selected < 850, "Selected {} times", selected);
+    }
+
+    #[test]
+    fn test_proof_of_stake_scheduled_proposer() {
+        let (alice, bob) = (generate_key_pair(), generate_key_pair());
+        let mut blockchain = staked_blockchain(&[&alice, &bob]);
+        let consensus = ProofOfStake { minimum_stake: 10.0, block_interval: 5 };
+
+        let template = BlockTemplate::new(&blockchain, "Anirudh");
+        let proposer = consensus.next_proposer(&blockchain, template.timestamp).unwrap();
+        let (scheduled, other) = if proposer == address(&alice) {
+            (&alice, &bob)
+        } else {
+            (&bob, &alice)
+        };
+
+        let mut block = template.to_block();
+        assert!(consensus.seal(&mut block, &blockchain, Some(other)).is_err());
+        assert!(consensus.seal(&mut block, &blockchain, None).is_err());
+
+        consensus.seal(&mut block, &blockchain, Some(scheduled)).unwrap();
+        let mut forged = block.clone();
+        forged.signature[0] ^= 1;
+        assert!(blockchain.validate_block(&forged).is_err());
+
+        assert!(blockchain.submit_block(block).is_ok());
+        assert_eq!(blockchain.chain.len(), 2);
+    }
+
+    #[test]
+    fn test_proof_of_stake_verifies_header_proposer() {
+        let (alice, bob) = (generate_key_pair(), generate_key_pair());
+        let blockchain = staked_blockchain(&[&alice, &bob]);
+        let consensus = ProofOfStake { minimum_stake: 10.0, block_interval: 5 };
+        let tip = blockchain.chain[0].header();
+
+        let mut block = BlockTemplate::new(&blockchain, "Anirudh").to_block();
+        let proposer = consensus.next_proposer(&blockchain, block.timestamp).unwrap();
+        let (scheduled, other) = if proposer == address(&alice) { (&alice, &bob) } else { (&bob, &alice) };
+        consensus.seal(&mut block, &blockchain, Some(scheduled)).unwrap();
+        let header = block.header();
+        assert!(validate_headers(&tip, std::slice::from_ref(&header), &consensus, 1, Some(&blockchain)).is_ok());
+
+        // Another staker signing the slot is only caught with the stakes of the local blockchain
+        let mut impostor = header.clone();
+        impostor.validator = Some(address(other));
+        impostor.hash = impostor.calculate_hash();
+        impostor.signature = other.sign(impostor.hash.as_bytes()).as_ref().to_vec();
+        assert!(validate_headers(&tip, std::slice::from_ref(&impostor), &consensus, 1, None).is_ok());
+        assert_eq!(validate_headers(&tip, &[impostor], &consensus, 1, Some(&blockchain)),
+                   Err("Block was not proposed by the scheduled proposer of this slot"));
+
+        let mut early = header;
+        early.timestamp = tip.timestamp + 1;
+        early.hash = early.calculate_hash();
+        early.signature = scheduled.sign(early.hash.as_bytes()).as_ref().to_vec();
+        assert_eq!(validate_headers(&tip, &[early], &consensus, 1, None),
+                   Err("Block was produced before the end of the block interval"));
+    }
+
+    #[test]
+    fn test_proof_of_stake_rejects_unsigned_block() {
+        let alice = generate_key_pair();
+        let mut blockchain = staked_blockchain(&[&alice]);
+        let mut block = BlockTemplate::new(&blockchain, "Anirudh").to_block();
+        ProofOfWork.seal(&mut block, &blockchain, None).unwrap();
+
+        assert!(blockchain.submit_block(block).is_err());
+    }
+
+    #[test]
+    fn test_proof_of_authority_round_robin() {
+        let (alice, bob) = (generate_key_pair(), generate_key_pair());
+        let validators = vec![
+            address(&alice),
+            address(&bob),
+        ];
+        let consensus = Arc::new(ProofOfAuthority { validators, block_interval: 5 });
+        let mut blockchain = Blockchain::with_consensus(ChainSpec { difficulty: 1, ..ChainSpec::default() }, consensus.clone());
+
+        for (index, (scheduled, other)) in [(&bob, &alice), (&alice, &bob), (&bob, &alice)].into_iter().enumerate() {
+            let mut block = BlockTemplate::new(&blockchain, "Anirudh").to_block();
+            block.timestamp = blockchain.chain[index].timestamp + 5;
+            assert!(consensus.seal(&mut block, &blockchain, Some(other)).is_err());
+
+            consensus.seal(&mut block, &blockchain, Some(scheduled)).unwrap();
+            assert!(blockchain.submit_block(block).is_ok());
+            assert_eq!(blockchain.chain.len(), index + 2);
+        }
+    }
+
+    #[test]
+    fn test_proof_of_authority_enforces_interval() {
+        let alice = generate_key_pair();
+        let validators = vec![address(&alice)];
+        let consensus = Arc::new(ProofOfAuthority { validators, block_interval: 60 });
+        let mut blockchain = Blockchain::with_consensus(ChainSpec { difficulty: 1, ..ChainSpec::default() }, consensus.clone());
+
+        let mut block = BlockTemplate::new(&blockchain, "Anirudh").to_block();
+        block.timestamp = blockchain.chain[0].timestamp + 30;
+        assert!(consensus.seal(&mut block, &blockchain, Some(&alice)).is_err());
+
+        block.timestamp = blockchain.chain[0].timestamp + 60;
+        consensus.seal(&mut block, &blockchain, Some(&alice)).unwrap();
+        let mut early = block.clone();
+        early.timestamp -= 1;
+        early.hash = Block::calculate_hash(&early);
+        early.signature = alice.sign(early.hash.as_bytes()).as_ref().to_vec();
+        assert!(blockchain.submit_block(early).is_err());
+        assert!(blockchain.submit_block(block).is_ok());
+    }
+
+    #[test]
+    fn test_proof_of_authority_takeover_after_missed_turn() {
+        let (alice, bob) = (generate_key_pair(), generate_key_pair());
+        let validators = vec![address(&alice), address(&bob)];
+        let consensus = Arc::new(ProofOfAuthority { validators, block_interval: 5 });
+        let mut blockchain = Blockchain::with_consensus(ChainSpec { difficulty: 1, ..ChainSpec::default() }, consensus.clone());
+        let genesis_time = blockchain.chain[0].timestamp;
+
+        // Bob is scheduled for block #1, so Alice must wait until Bob misses his turn
+        let mut block = BlockTemplate::new(&blockchain, "Anirudh").to_block();
+        block.timestamp = genesis_time + 5 + 5 * TAKEOVER_INTERVALS - 1;
+        assert!(consensus.seal(&mut block, &blockchain, Some(&alice)).is_err());
+
+        block.timestamp += 1;
+        assert!(consensus.seal(&mut block, &blockchain, Some(&bob)).is_err());
+        consensus.seal(&mut block, &blockchain, Some(&alice)).unwrap();
+        assert!(blockchain.submit_block(block).is_ok());
+
+        // A takeover cannot be claimed ahead of time
+        let mut early = BlockTemplate::new(&blockchain, "Anirudh").to_block();
+        early.timestamp = BlockTemplate::new(&blockchain, "Anirudh").timestamp + 600;
+        let previous = blockchain.chain[1].timestamp;
+        let turn = (early.timestamp - previous - 5) / (5 * TAKEOVER_INTERVALS);
+        let signer = if turn.is_multiple_of(2) { &alice } else { &bob };
+        consensus.seal(&mut early, &blockchain, Some(signer)).unwrap();
+        assert_eq!(blockchain.submit_block(early), Err(BlockError::BadSeal("Block takes over a turn which was not missed yet")));
+    }
+}
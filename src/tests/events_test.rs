@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod events_test {
+    use crate::events::{Event, Topic};
+    use crate::miner::Miner;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{funded_blockchain, generate_key_pair, signed};
+    use crate::transaction::Transaction;
+
+    #[test]
+    fn test_topic_matches() {
+        let transaction = Transaction::new(Some("Alice".to_string()), Some("Bob".to_string()), 10.0, None);
+        let event = Event::NewTransaction(transaction);
+        assert!(Topic::NewTransactions.matches(&event));
+        assert!(!Topic::NewBlocks.matches(&event));
+        assert!(Topic::Address("Bob".to_string()).matches(&event));
+        assert!(!Topic::Address("Charlie".to_string()).matches(&event));
+    }
+
+    #[test]
+    fn test_blockchain_publishes_events() {
+        let alice = generate_key_pair();
+        let mut blockchain = funded_blockchain(&[&alice]);
+        let mut events = blockchain.events.subscribe();
+
+        let transaction = signed(&alice, "Bob", 10.0, 1.0);
+        assert!(blockchain.add_transaction(transaction.clone()));
+        assert!(!blockchain.add_transaction(transaction));
+        assert!(matches!(events.try_recv(), Ok(Event::NewTransaction(tx)) if tx.recipient.as_deref() == Some("Bob")));
+        assert!(events.try_recv().is_err());
+
+        let block = Miner::mine_template(&BlockTemplate::new(&blockchain, "Anirudh"));
+        blockchain.submit_block(block.clone()).unwrap();
+        let event = events.try_recv().unwrap();
+        assert!(matches!(&event, Event::NewBlock(new) if new.hash == block.hash));
+        assert!(Topic::Address("Anirudh".to_string()).matches(&event));
+    }
+}
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod ledger_test {
+    use std::collections::BTreeMap;
+    use crate::blockchain::Blockchain;
+    use crate::chain_spec::ChainSpec;
+    use crate::miner::Miner;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{address, generate_key_pair, signed};
+
+    #[test]
+    fn test_ledger_replays_chain() {
+        let alice = generate_key_pair();
+        let spec = ChainSpec {
+            difficulty: 2,
+            allocations: BTreeMap::from([(address(&alice), 100.0)]),
+            ..ChainSpec::default()
+        };
+        let mut blockchain = Blockchain::from_spec(spec);
+        assert!(blockchain.add_transaction(signed(&alice, "Bob", 30.0, 1.0)));
+
+        let template = BlockTemplate::new(&blockchain, "Anirudh");
+        let reward: f64 = template.coinbase.iter().map(|tx| tx.amount).sum();
+        blockchain.submit_block(Miner::mine_template(&template)).unwrap();
+
+        let ledger = blockchain.ledger();
+        assert_eq!(ledger.balance(&address(&alice)), 69.0);
+        assert_eq!(ledger.balance("Bob"), 30.0);
+        assert_eq!(ledger.balance("Anirudh"), reward);
+        assert_eq!(ledger.balance("Charlie"), 0.0);
+    }
+
+}
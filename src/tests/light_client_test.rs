@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod light_client_test {
+    use std::collections::BTreeMap;
+    use std::io::ErrorKind;
+    use ring::signature::Ed25519KeyPair;
+    use tokio::net::TcpListener;
+    use crate::blockchain::Blockchain;
+    use crate::chain_spec::ChainSpec;
+    use crate::light_client::LightClient;
+    use crate::miner::Miner;
+    use crate::network::Message;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{address, generate_key_pair, signed};
+    use crate::transport::SecureChannel;
+    use crate::version::{accept_version, Version, FULL_NODE_CAPABILITIES};
+
+    fn sample_spec(sender: &Ed25519KeyPair) -> ChainSpec {
+        let allocations = BTreeMap::from([(address(sender), 100.0)]);
+        ChainSpec { difficulty: 1, allocations, ..ChainSpec::default() }
+    }
+
+    fn sample_blockchain_instance(sender: &Ed25519KeyPair) -> Blockchain {
+        let mut blockchain = Blockchain::from_spec(sample_spec(sender));
+        for x in 1..5 {
+            assert!(blockchain.add_transaction(signed(sender, "Alice", x as f64, 1.0)));
+            let template = BlockTemplate::new(&blockchain, "Anirudh");
+            blockchain.submit_block(Miner::mine_template(&template)).unwrap();
+        }
+        blockchain
+    }
+
+    #[test]
+    fn test_verify_wallet_transactions() {
+        let sender = generate_key_pair();
+        let blockchain = sample_blockchain_instance(&sender);
+        let mut light_client = LightClient::new(&sample_spec(&sender));
+        assert_eq!(light_client.headers[0], blockchain.chain[0].header());
+
+        let headers = blockchain.headers_after(&light_client.block_locator(), 100);
+        light_client.append_headers(headers).unwrap();
+        assert_eq!(light_client.headers.len(), 5);
+
+        let transactions = blockchain.transactions_of("Alice");
+        assert_eq!(transactions.len(), 4);
+        assert!(transactions.iter().all(|transaction| transaction.involves("Alice")));
+        for hash in transactions.iter().map(|transaction| transaction.calculate_hash()) {
+            let (index, proof) = blockchain.merkle_proof(&hash).unwrap();
+            assert!(light_client.verify_inclusion(index, &proof));
+            assert!(!light_client.verify_inclusion(index + 1, &proof));
+        }
+        assert!(blockchain.merkle_proof("unknown").is_none());
+    }
+
+    #[test]
+    fn test_reject_headers_from_other_network() {
+        let sender = generate_key_pair();
+        let blockchain = sample_blockchain_instance(&sender);
+        let other = ChainSpec { network_id: String::from("other"), ..sample_spec(&sender) };
+        let mut light_client = LightClient::new(&other);
+
+        let headers = blockchain.headers_after(&light_client.block_locator(), 100);
+        assert!(light_client.append_headers(headers).is_err());
+        assert_eq!(light_client.headers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reject_transactions_of_other_wallets() {
+        let sender = generate_key_pair();
+        let blockchain = sample_blockchain_instance(&sender);
+        let mut light_client = LightClient::new(&sample_spec(&sender));
+        light_client.append_headers(blockchain.headers_after(&light_client.block_locator(), 100)).unwrap();
+
+        // The node proves a real transaction, but it was sent to Anirudh, not to Bob
+        let unrelated = blockchain.transactions_of("Anirudh");
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut channel = SecureChannel::accept(socket).await.unwrap();
+            accept_version(&mut channel, &Version::new("devnet", 4, FULL_NODE_CAPABILITIES)).await.unwrap();
+            let _ = channel.receive().await;
+            channel.send(&Message::Transactions(unrelated)).await.unwrap();
+        });
+
+        let result = light_client.verify_transactions(&target, "Bob").await;
+        assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidData));
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod mempool_test {
+    use crate::mempool::Mempool;
+    use crate::transaction::Transaction;
+
+    #[test]
+    fn test_add_duplicate_transaction() {
+        let mut mempool = Mempool::new();
+        let transaction = Transaction::new(Some("Alice".to_string()), 
+                                           Some("Bob".to_string()), 10.0, Some(1.0));
+
+        assert!(mempool.add(transaction.clone()));
+        assert!(!mempool.add(transaction));
+        assert_eq!(mempool.len(), 1);
+    }
+
+    #[test]
+    fn test_select_highest_fee() {
+        let mut mempool = Mempool::new();
+        mempool.add(Transaction::new(Some("Alice".to_string()), Some("Bob".to_string()), 10.0, Some(1.0)));
+        mempool.add(Transaction::new(Some("Bob".to_string()), Some("Charlie".to_string()), 5.0, Some(3.0)));
+        mempool.add(Transaction::new(Some("Charlie".to_string()), Some("Dave".to_string()), 20.0, Some(0.5)));
+
+        let selected = mempool.select().unwrap();
+        assert_eq!(selected.sender, Some("Bob".to_string()));
+
+        let hash = selected.calculate_hash();
+        mempool.remove(&hash);
+        assert_eq!(mempool.len(), 2);
+        assert!(!mempool.contains(&hash));
+    }
+
+    #[test]
+    fn test_minimum_relay_fee() {
+        let mut mempool = Mempool::new();
+        let unpaid = Transaction::new(Some("Alice".to_string()), Some("Bob".to_string()), 10.0, None);
+        assert!(mempool.minimum_fee(&unpaid) > 0.0);
+        assert!(!mempool.add(unpaid.clone()));
+
+        let underpaid = Transaction::new(Some("Alice".to_string()), Some("Bob".to_string()), 10.0,
+                                         Some(mempool.minimum_fee(&unpaid) / 2.0));
+        assert!(!mempool.add(underpaid));
+        assert!(mempool.is_empty());
+    }
+
+    #[test]
+    fn test_replace_by_fee() {
+        let mut mempool = Mempool::new();
+        let payment = |fee: f64| {
+            let mut transaction = Transaction::new(Some("Alice".to_string()), Some("Bob".to_string()), 10.0, Some(fee));
+            transaction.transaction_id = Some("invoice-7".to_string());
+            transaction
+        };
+        assert!(mempool.add(payment(0.5)));
+        assert!(!mempool.add(payment(0.5001)));
+        assert!(mempool.add(payment(1.0)));
+        assert_eq!(mempool.len(), 1);
+        assert_eq!(mempool.transactions[0].fee, Some(1.0));
+
+        let mut unrelated = payment(0.5);
+        unrelated.transaction_id = Some("invoice-8".to_string());
+        assert!(mempool.add(unrelated));
+        assert_eq!(mempool.len(), 2);
+    }
+
+    #[test]
+    fn test_evict_lowest_fee_rate_when_full() {
+        let mut mempool = Mempool::new();
+        mempool.max_transactions = 2;
+        let payment = |sender: &str, fee: f64| Transaction::new(Some(sender.to_string()), Some("Bob".to_string()), 10.0, Some(fee));
+        let evicted = payment("Alice", 1.0);
+        assert!(mempool.add(evicted.clone()));
+        assert!(mempool.add(payment("Carol", 2.0)));
+
+        assert!(!mempool.add(payment("David", 0.5)));
+        assert!(!mempool.add(payment("Ellen", 1.0)));
+        let better = payment("Frank", 3.0);
+        assert!(mempool.add(better.clone()));
+        assert_eq!(mempool.len(), 2);
+        assert!(!mempool.contains(&evicted.calculate_hash()));
+        assert!(mempool.contains(&better.calculate_hash()));
+    }
+}
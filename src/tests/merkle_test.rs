@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod merkle_test {
+    use crate::merkle::{hash_leaf, hash_pair, merkle_root, MerkleProof};
+
+    #[test]
+    fn test_merkle_root() {
+        let hashes: Vec<String> = ["a", "b", "c"].iter().map(|hash| hash.to_string()).collect();
+
+        let (a, b, c) = (hash_leaf("a"), hash_leaf("b"), hash_leaf("c"));
+        assert_eq!(merkle_root(&hashes[..1]), a);
+        assert_eq!(merkle_root(&hashes[..2]), hash_pair(&a, &b));
+        assert_eq!(merkle_root(&hashes), hash_pair(&hash_pair(&a, &b), &c));
+
+        // Repeating the last leaf changes the root
+        let repeated: Vec<String> = ["a", "b", "c", "c"].iter().map(|hash| hash.to_string()).collect();
+        assert_ne!(merkle_root(&repeated), merkle_root(&hashes));
+    }
+
+    #[test]
+    fn test_internal_node_is_not_a_leaf() {
+        let hashes: Vec<String> = (0..4).map(|i| format!("hash #{i}")).collect();
+        let root = merkle_root(&hashes);
+
+        // The parent of the first two leaves, proven as if it were a leaf of a smaller tree
+        let parent = hash_pair(&hash_leaf(&hashes[0]), &hash_leaf(&hashes[1]));
+        let sibling = hash_pair(&hash_leaf(&hashes[2]), &hash_leaf(&hashes[3]));
+        let forged = MerkleProof { leaf: parent, index: 0, siblings: vec![Some(sibling)] };
+        assert!(!forged.verify(&root));
+    }
+
+    #[test]
+    fn test_merkle_proofs() {
+        let hashes: Vec<String> = (0..5).map(|i| format!("hash #{i}")).collect();
+        let root = merkle_root(&hashes);
+
+        for index in 0..hashes.len() {
+            let proof = MerkleProof::new(&hashes, index).unwrap();
+            assert_eq!(proof.leaf, hashes[index]);
+            assert!(proof.verify(&root));
+        }
+        assert!(MerkleProof::new(&hashes, 5).is_none());
+
+        let mut tampered = MerkleProof::new(&hashes, 2).unwrap();
+        tampered.index = 3;
+        assert!(!tampered.verify(&root));
+    }
+}
@@ -3,20 +3,23 @@ mod miner_test {
     use std::sync::Arc;
     use crate::block::Block;
     use crate::blockchain::Blockchain;
+    use crate::chain_spec::{ChainSpec, RewardSchedule};
     use crate::miner::Miner;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{funded_blockchain, generate_key_pair, signed};
     use crate::transaction::Transaction;
 
     #[test]
     fn test_mine_block_with_sufficient_balance() {
         let mut miner = Miner { balance: 100.0, identifier: Arc::from("Anirudh") };
-        let mut blockchain = Blockchain::new(4);
-        let transaction = Transaction::new(Some("sender".to_string()), 
-                                           Some("receiver".to_string()), 10.0, None);
-        let block = Block::new(1, transaction, None);
+        let sender = generate_key_pair();
+        let mut blockchain = funded_blockchain(&[&sender]);
+        let block = Block::new(1, signed(&sender, "receiver", 10.0, 5.0), None);
 
         let result = miner.mine_block(&mut blockchain, block);
         assert!(result.is_ok());
-        assert_eq!(miner.balance, 210.0);
+        assert_eq!(miner.balance, 155.0);
+        assert_eq!(blockchain.ledger().balance("receiver"), 10.0);
     }
 
     #[test]
@@ -27,7 +30,9 @@ mod miner_test {
         let block = Block::new(1, transaction, None);
 
         let result = miner.mine_block(&mut blockchain, block);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
+        assert_eq!(miner.balance, 100.0);
+        assert_eq!(blockchain.chain.len(), 1);
     }
     
     #[test]
@@ -37,12 +42,28 @@ mod miner_test {
             balance: 100.0,
             identifier: Arc::from("Anirudh")
         };
-        let transaction = Transaction::new(Some("sender".to_string()), 
-                                           Some("receiver".to_string()), 80.0, None);
+        let transaction = Transaction::new(None, None, 0.0, None);
         let block = Block::new(1, transaction, None);
         
         let result = miner.mine_block(&mut blockchain, block);
         assert!(result.is_ok());
-        assert_eq!(miner.balance, 305.0);
+        assert_eq!(miner.balance, 350.0);
+    }
+
+    #[test]
+    fn test_block_reward_halves_every_interval() {
+        let spec = ChainSpec {
+            difficulty: 1,
+            reward: RewardSchedule { initial_reward: 50.0, halving_interval: 2 },
+            ..ChainSpec::default()
+        };
+        let mut blockchain = Blockchain::from_spec(spec);
+        let mut rewards = Vec::new();
+        for _ in 0..5 {
+            rewards.push(Miner::calculate_block_reward(&blockchain));
+            let block = Miner::mine_template(&BlockTemplate::new(&blockchain, "Miner"));
+            blockchain.submit_block(block).unwrap();
+        }
+        assert_eq!(rewards, vec![50.0, 50.0, 25.0, 25.0, 12.5]);
     }
-}
\ No newline at end of file
+}
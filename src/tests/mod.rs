@@ -1,4 +1,36 @@
+#![allow(clippy::module_inception)]
+
+/// Fixtures shared by the test modules
+#[cfg(test)]
+mod util;
+
 mod block_test;
 mod blockchain_test;
 mod transaction_test;
 mod miner_test;
+mod mempool_test;
+mod template_test;
+mod pool_test;
+mod consensus_test;
+mod ledger_test;
+mod chain_spec_test;
+mod merkle_test;
+mod sync_test;
+mod light_client_test;
+mod compact_test;
+mod peer_test;
+mod transport_test;
+mod address_book_test;
+mod wire_test;
+mod version_test;
+mod events_test;
+mod websocket_test;
+mod session_test;
+mod storage_test;
+mod script_test;
+mod multisig_test;
+mod vm_test;
+mod token_test;
+mod nft_test;
+mod notary_test;
+mod rpc_test;
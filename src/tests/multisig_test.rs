@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod multisig_test {
+    use crate::tests::util::{address, funded_addresses, generate_key_pair};
+    use std::env;
+    use std::fs;
+    use ring::signature::Ed25519KeyPair;
+    use crate::multisig::{MultisigAccount, PartiallySignedTransaction};
+    use crate::script::MAX_MULTISIG_KEYS;
+
+    fn account(keys: &[Ed25519KeyPair], threshold: usize) -> MultisigAccount {
+        let public_keys = keys.iter().map(address).collect();
+        MultisigAccount::new(threshold, public_keys).unwrap()
+    }
+
+    #[test]
+    fn test_account_validation() {
+        let key = address(&generate_key_pair());
+        assert!(MultisigAccount::new(1, vec![key.clone()]).is_ok());
+        assert!(MultisigAccount::new(0, vec![key.clone()]).is_err());
+        assert!(MultisigAccount::new(2, vec![key.clone()]).is_err());
+        assert!(MultisigAccount::new(1, vec![key.clone(), key.clone()]).is_err());
+        assert!(MultisigAccount::new(1, vec![String::from("abcd")]).is_err());
+        assert_eq!(MultisigAccount::new(1, Vec::new()),
+                   Err("A multisig account needs at least one and at most MAX_MULTISIG_KEYS public keys"));
+        assert_eq!(MultisigAccount::new(1, vec![key.clone(), key.to_uppercase()]), Err("Duplicate multisig public key"));
+        let too_many = (0..=MAX_MULTISIG_KEYS).map(|_| address(&generate_key_pair())).collect();
+        assert!(MultisigAccount::new(1, too_many).is_err());
+
+        // A stored account goes through the same validation
+        let stored = format!(r#"{{"threshold": 1, "public_keys": ["{}"]}}"#, key);
+        assert_eq!(serde_json::from_str::<MultisigAccount>(&stored).unwrap().public_keys(), std::slice::from_ref(&key));
+        let invalid = r#"{"threshold": 1, "public_keys": ["not hex"]}"#;
+        assert!(serde_json::from_str::<MultisigAccount>(invalid).is_err());
+        let unreachable = format!(r#"{{"threshold": 2, "public_keys": ["{}"]}}"#, key);
+        assert!(serde_json::from_str::<MultisigAccount>(&unreachable).is_err());
+    }
+
+    #[test]
+    fn test_sign_with_uppercase_account_keys() {
+        let key = generate_key_pair();
+        let treasury = MultisigAccount::new(1, vec![address(&key).to_uppercase()]).unwrap();
+        assert_eq!(treasury.public_keys(), &[address(&key)]);
+
+        let mut unsigned = PartiallySignedTransaction::new(treasury, "Bob", 10.0, Some(1.0));
+        assert!(unsigned.sign(&key).is_ok());
+        assert!(unsigned.is_complete());
+    }
+
+    #[test]
+    fn test_signers_pass_transaction_around() {
+        let keys: Vec<Ed25519KeyPair> = (0..3).map(|_| generate_key_pair()).collect();
+        let treasury = account(&keys, 2);
+        let unsigned = PartiallySignedTransaction::new(treasury.clone(), "Bob", 10.0, Some(1.0));
+        assert_eq!(unsigned.transaction.sender, Some(treasury.address()));
+
+        let path = env::temp_dir().join(format!("multisig_test_{}.json", std::process::id()));
+        unsigned.save(&path).unwrap();
+
+        let mut first = PartiallySignedTransaction::load(&path).unwrap();
+        first.sign(&keys[2]).unwrap();
+        assert!(!first.is_complete());
+        assert!(first.finalize().is_err());
+        assert_eq!(first.sign(&generate_key_pair()), Err("Key is not part of the multisig account"));
+
+        let mut second = PartiallySignedTransaction::load(&path).unwrap();
+        second.sign(&keys[0]).unwrap();
+        second.combine(first).unwrap();
+        assert!(second.is_complete());
+        let _ = fs::remove_file(&path);
+
+        let transaction = second.finalize().unwrap();
+        assert_eq!(transaction.verify_script(1, 0), Ok(()));
+        assert!(funded_addresses(&[treasury.address()]).add_transaction(transaction));
+    }
+
+    #[test]
+    fn test_combine_rejects_other_transaction() {
+        let keys: Vec<Ed25519KeyPair> = (0..2).map(|_| generate_key_pair()).collect();
+        let treasury = account(&keys, 2);
+        let mut payment = PartiallySignedTransaction::new(treasury.clone(), "Bob", 10.0, Some(1.0));
+        let mut other = PartiallySignedTransaction::new(treasury, "Mallory", 10.0, Some(1.0));
+        other.sign(&keys[1]).unwrap();
+        assert_eq!(payment.combine(other), Err("Partially signed transactions differ"));
+    }
+
+    #[test]
+    fn test_reject_invalid_signatures() {
+        let keys: Vec<Ed25519KeyPair> = (0..3).map(|_| generate_key_pair()).collect();
+        let treasury = account(&keys, 2);
+        let mut payment = PartiallySignedTransaction::new(treasury.clone(), "Bob", 10.0, Some(1.0));
+
+        // A signature over another transaction is filed under a key of the account
+        let mut forged = payment.clone();
+        let mut other = PartiallySignedTransaction::new(treasury, "Mallory", 10.0, Some(1.0));
+        other.sign(&keys[1]).unwrap();
+        forged.signatures = other.signatures;
+        assert_eq!(payment.combine(forged.clone()), Err("Invalid multisig signature"));
+        assert!(payment.signatures.is_empty());
+        assert_eq!(forged.sign(&keys[0]), Err("Invalid multisig signature"));
+
+        let mut garbage = payment.clone();
+        garbage.signatures.insert(address(&keys[2]), String::from("not hex"));
+        assert_eq!(payment.combine(garbage), Err("Invalid multisig signature"));
+        let mut outsider = payment.clone();
+        outsider.signatures.insert(address(&generate_key_pair()), String::from("00"));
+        assert_eq!(payment.combine(outsider), Err("Key is not part of the multisig account"));
+    }
+}
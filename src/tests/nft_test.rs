@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod nft_test {
+    use ring::signature::Ed25519KeyPair;
+    use crate::blockchain::BlockError;
+    use crate::miner::Miner;
+    use crate::nft::{nft_id, NftOperation};
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{address, funded_blockchain, generate_key_pair, mine};
+    use crate::transaction::Transaction;
+
+    const CONTENT_HASH: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    fn operation(sender: &Ed25519KeyPair, recipient: Option<String>, nft: NftOperation) -> Transaction {
+        let mut transaction = Transaction::new(Some(address(sender)), recipient, 0.0, Some(1.0));
+        transaction.nft = Some(nft);
+        transaction.authorize(sender);
+        transaction
+    }
+
+    fn mint(owner: &Ed25519KeyPair) -> Transaction {
+        operation(owner, None, NftOperation::Mint {
+            content_hash: CONTENT_HASH.to_string(),
+            metadata_uri: "ipfs://artwork.json".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_mint_and_transfer_history() {
+        let (alice, bob) = (generate_key_pair(), generate_key_pair());
+        let mut blockchain = funded_blockchain(&[&alice, &bob]);
+        let minting = mint(&alice);
+        let id = nft_id(&minting);
+        mine(&mut blockchain, minting);
+
+        let transfer = operation(&alice, Some(address(&bob)), NftOperation::Transfer { id: id.clone() });
+        mine(&mut blockchain, transfer);
+        let transfer = operation(&bob, Some("Carol".to_string()), NftOperation::Transfer { id: id.clone() });
+        mine(&mut blockchain, transfer.clone());
+
+        let nft = &blockchain.nfts().nfts[&id];
+        assert_eq!(nft.content_hash, CONTENT_HASH);
+        assert_eq!(nft.owner(), "Carol");
+        let owners: Vec<&str> = nft.history.iter().map(|ownership| ownership.owner.as_str()).collect();
+        assert_eq!(owners, vec![address(&alice).as_str(), address(&bob).as_str(), "Carol"]);
+        assert_eq!(nft.history[2].block_index, 3);
+        assert_eq!(nft.history[2].transaction_hash, transfer.calculate_hash());
+    }
+
+    #[test]
+    fn test_nft_rules() {
+        let (alice, mallory) = (generate_key_pair(), generate_key_pair());
+        let mut blockchain = funded_blockchain(&[&alice]);
+        let minting = mint(&alice);
+        let id = nft_id(&minting);
+        mine(&mut blockchain, minting.clone());
+        let registry = blockchain.nfts();
+
+        assert_eq!(registry.check(&minting), Err("NFT is already minted"));
+        let stolen = operation(&mallory, Some(address(&mallory)), NftOperation::Transfer { id: id.clone() });
+        assert_eq!(registry.check(&stolen), Err("Only the owner can transfer an NFT"));
+
+        let mut forged = operation(&alice, Some(address(&mallory)), NftOperation::Transfer { id });
+        forged.authorize(&mallory);
+        assert_eq!(registry.check(&forged), Err("NFT operation is not signed by the sender"));
+
+        let invalid_hash = operation(&alice, None, NftOperation::Mint {
+            content_hash: "abc".to_string(),
+            metadata_uri: "ipfs://artwork.json".to_string(),
+        });
+        assert_eq!(registry.check(&invalid_hash), Err("NFT content hash must be a hex encoded SHA-256 hash"));
+
+        let mut named = Transaction::new(Some("Alice".to_string()), None, 0.0, None);
+        named.nft = minting.nft.clone();
+        assert_eq!(registry.check(&named), Err("NFT operations must be sent from a key address"));
+        assert!(!blockchain.add_transaction(stolen));
+    }
+
+    #[test]
+    fn test_reject_replayed_transfer_after_round_trip() {
+        let (alice, bob) = (generate_key_pair(), generate_key_pair());
+        let mut blockchain = funded_blockchain(&[&alice, &bob]);
+        let minting = mint(&alice);
+        let id = nft_id(&minting);
+        mine(&mut blockchain, minting);
+
+        let to_bob = operation(&alice, Some(address(&bob)), NftOperation::Transfer { id: id.clone() });
+        mine(&mut blockchain, to_bob.clone());
+        mine(&mut blockchain, operation(&bob, Some(address(&alice)), NftOperation::Transfer { id: id.clone() }));
+        assert_eq!(blockchain.nfts().nfts[&id].owner(), address(&alice));
+
+        // Alice owns the NFT again, so only the replay rule stops her old transfer to Bob
+        assert_eq!(blockchain.nfts().check(&to_bob), Ok(()));
+        assert!(!blockchain.add_transaction(to_bob.clone()));
+        let mut template = BlockTemplate::new(&blockchain, "Miner");
+        template.transaction = to_bob;
+        assert_eq!(blockchain.submit_block(Miner::mine_template(&template)),
+                   Err(BlockError::Invalid("Block includes a transaction already on the blockchain")));
+        assert_eq!(blockchain.nfts().nfts[&id].owner(), address(&alice));
+    }
+}
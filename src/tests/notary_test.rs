@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod notary_test {
+    use ring::signature::Ed25519KeyPair;
+    use crate::notary::{Notarization, MAX_NOTARIZATION_SIZE};
+    use crate::tests::util::{address, funded_blockchain, generate_key_pair, mine, signed};
+    use crate::transaction::Transaction;
+
+    const DOCUMENT_HASH: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    fn notarize(sender: &Ed25519KeyPair, document_hash: &str, memo: Option<&str>, fee: f64) -> Transaction {
+        let mut transaction = Transaction::new(Some(address(sender)), None, 0.0, Some(fee));
+        transaction.notarization = Some(Notarization {
+            document_hash: document_hash.to_string(),
+            memo: memo.map(String::from),
+        });
+        transaction.authorize(sender);
+        transaction
+    }
+
+    #[test]
+    fn test_notarization_rules() {
+        let alice = generate_key_pair();
+        let valid = notarize(&alice, DOCUMENT_HASH, Some("Contract #42"), 1.0);
+        assert_eq!(valid.notarization.as_ref().unwrap().size(), 76);
+        assert_eq!(Notarization::check(&valid), Ok(()));
+
+        assert_eq!(Notarization::check(&notarize(&alice, "abc", None, 1.0)),
+                   Err("Notarized document hash must be a hex encoded SHA-256 hash"));
+        let memo = "x".repeat(MAX_NOTARIZATION_SIZE);
+        assert_eq!(Notarization::check(&notarize(&alice, DOCUMENT_HASH, Some(&memo), 100.0)),
+                   Err("Notarization exceeds the maximum size"));
+        let underpaid = notarize(&alice, DOCUMENT_HASH, None, 0.5);
+        assert_eq!(Notarization::check(&underpaid), Err("Notarization fee is below the fee per byte"));
+
+        let mut blockchain = funded_blockchain(&[&alice]);
+        assert!(!blockchain.add_transaction(underpaid));
+    }
+
+    #[test]
+    fn test_proof_of_notarization() {
+        let alice = generate_key_pair();
+        let mut blockchain = funded_blockchain(&[&alice]);
+        assert!(blockchain.notarization_proof(DOCUMENT_HASH).is_none());
+        mine(&mut blockchain, signed(&alice, "Bob", 1.0, 1.0));
+        mine(&mut blockchain, notarize(&alice, DOCUMENT_HASH, None, 1.0));
+        mine(&mut blockchain, notarize(&alice, DOCUMENT_HASH, Some("Again"), 1.0));
+
+        let proof = blockchain.notarization_proof(DOCUMENT_HASH).unwrap();
+        let header = blockchain.chain[2].header();
+        assert_eq!(proof.block_index, 2);
+        assert_eq!(proof.timestamp, header.timestamp);
+        assert!(proof.verify(DOCUMENT_HASH, &header));
+
+        assert!(!proof.verify(DOCUMENT_HASH, &blockchain.chain[3].header()));
+        let other_hash = DOCUMENT_HASH.replace('9', "8");
+        assert!(!proof.verify(&other_hash, &header));
+        let mut forged = proof.clone();
+        forged.timestamp -= 3600;
+        assert!(!forged.verify(DOCUMENT_HASH, &header));
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod peer_test {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::Duration;
+    use crate::network::Message;
+    use crate::peer::{Misbehavior, PeerLimits, PeerManager};
+
+    const PEER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    const OTHER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    #[test]
+    fn test_ban_at_threshold() {
+        let mut peers = PeerManager::new(PeerLimits::default());
+        assert!(!peers.misbehaving(PEER, Misbehavior::InvalidBlock));
+        assert!(peers.accept_inbound(PEER).is_ok());
+        assert!(peers.misbehaving(PEER, Misbehavior::InvalidBlock));
+
+        assert!(peers.is_banned(PEER));
+        assert_eq!(peers.accept_inbound(PEER), Err("Peer is banned"));
+        assert!(!peers.is_banned(OTHER));
+    }
+
+    #[test]
+    fn test_bad_proof_of_work_bans_immediately() {
+        let mut peers = PeerManager::new(PeerLimits::default());
+        assert!(peers.misbehaving(PEER, Misbehavior::BadProofOfWork));
+        assert!(peers.is_banned(PEER));
+    }
+
+    #[test]
+    fn test_ban_expires() {
+        let limits = PeerLimits {
+            ban_duration: Duration::from_millis(20),
+            ..PeerLimits::default()
+        };
+        let mut peers = PeerManager::new(limits);
+        peers.ban(PEER);
+        assert!(peers.is_banned(PEER));
+
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(!peers.is_banned(PEER));
+        assert!(peers.accept_inbound(PEER).is_ok());
+    }
+
+    #[test]
+    fn test_scores_decay() {
+        let limits = PeerLimits {
+            score_decay: Duration::from_millis(1),
+            ..PeerLimits::default()
+        };
+        let mut peers = PeerManager::new(limits);
+        assert!(!peers.misbehaving(PEER, Misbehavior::InvalidBlock));
+        assert!(peers.score(PEER) > 0);
+
+        std::thread::sleep(Duration::from_millis(80));
+        assert_eq!(peers.score(PEER), 0);
+        assert!(!peers.misbehaving(PEER, Misbehavior::InvalidBlock));
+        assert!(!peers.is_banned(PEER));
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut peers = PeerManager::new(PeerLimits::default());
+        let burst = peers.limits.rate_limits["block"].burst as usize;
+        for _ in 0..burst {
+            assert!(peers.allow(PEER, "block"));
+        }
+        assert!(!peers.allow(PEER, "block"));
+        assert!(peers.allow(PEER, "query"));
+        assert!(peers.allow(OTHER, "block"));
+    }
+
+    #[test]
+    fn test_rate_class() {
+        assert_eq!(Message::RequestChain.rate_class(), "query");
+        assert_eq!(Message::GetHeaders(Vec::new()).rate_class(), "query");
+    }
+
+    #[test]
+    fn test_connection_limits() {
+        let limits = PeerLimits {
+            max_inbound: 1,
+            max_outbound: 1,
+            ..PeerLimits::default()
+        };
+        let mut peers = PeerManager::new(limits);
+        assert!(peers.accept_inbound(PEER).is_ok());
+        assert_eq!(peers.accept_inbound(OTHER), Err("Too many inbound connections"));
+        peers.release_inbound();
+        assert!(peers.accept_inbound(OTHER).is_ok());
+
+        assert!(peers.connect_outbound().is_ok());
+        assert!(peers.connect_outbound().is_err());
+        peers.release_outbound();
+        assert_eq!(peers.outbound(), 0);
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod pool_test {
+    use crate::blockchain::Blockchain;
+    use crate::chain_spec::{BlockLimits, ChainSpec};
+    use crate::miner::Miner;
+    use crate::pool::{PayoutScheme, Pool, Share};
+    use crate::template::BlockTemplate;
+
+    fn share_for(pool: &mut Pool, miner: &str, template: &BlockTemplate) -> Share {
+        let job = pool.create_job(template.clone(), miner);
+        let block = Miner::mine_template(&job.template);
+        Share { miner: miner.to_string(), job_id: job.id, nonce: block.nonce }
+    }
+
+    #[test]
+    fn test_job_lowers_difficulty_and_pays_pool() {
+        let blockchain = Blockchain::new(3);
+        let template = BlockTemplate::new(&blockchain, "pool");
+        let mut pool = Pool::new("pool", 1, PayoutScheme::Proportional);
+
+        let job = pool.create_job(template.clone(), "Alice");
+        assert_eq!(job.template.difficulty, 1);
+        assert_eq!(job.network_difficulty, 3);
+        assert_eq!(job.template.coinbase.len(), 1);
+        assert_eq!(job.template.coinbase[0].recipient, Some("pool".to_string()));
+        assert_eq!(job.template.coinbase[0].amount, template.coinbase[0].amount);
+    }
+
+    #[test]
+    fn test_reject_duplicate_and_unknown_shares() {
+        let blockchain = Blockchain::new(3);
+        let template = BlockTemplate::new(&blockchain, "pool");
+        let mut pool = Pool::new("pool", 1, PayoutScheme::Proportional);
+
+        let share = share_for(&mut pool, "Alice", &template);
+        assert!(pool.submit_share(&share).is_ok());
+        assert!(pool.submit_share(&share).is_err());
+
+        let unknown = Share { job_id: 42, ..share.clone() };
+        assert!(pool.submit_share(&unknown).is_err());
+        let stolen = Share { miner: "Mallory".to_string(), ..share };
+        assert_eq!(pool.submit_share(&stolen).err(), Some("Share is for a job handed out to another miner"));
+        assert_eq!(pool.accepted.get("Alice"), Some(&1));
+    }
+
+    #[test]
+    fn test_jobs_never_share_work() {
+        let blockchain = Blockchain::new(3);
+        let template = BlockTemplate::new(&blockchain, "pool");
+        let mut pool = Pool::new("pool", 1, PayoutScheme::Proportional);
+
+        let first = Miner::mine_template(&pool.create_job(template.clone(), "Alice").template);
+        let again = Miner::mine_template(&pool.create_job(template.clone(), "Alice").template);
+        let other = Miner::mine_template(&pool.create_job(template, "Bob").template);
+        assert_ne!(first.hash, again.hash);
+        assert_ne!(first.hash, other.hash);
+        assert_ne!(again.hash, other.hash);
+    }
+
+    #[test]
+    fn test_pplns_payouts() {
+        let blockchain = Blockchain::new(3);
+        let template = BlockTemplate::new(&blockchain, "pool");
+        let mut pool = Pool::new("pool", 1, PayoutScheme::Pplns(4));
+
+        for miner in ["Alice", "Alice", "Bob", "Alice", "Bob", "Bob"] {
+            let share = share_for(&mut pool, miner, &template);
+            pool.submit_share(&share).unwrap();
+        }
+
+        let payouts = pool.payouts(100.0);
+        assert_eq!(payouts.get("Alice"), Some(&25.0));
+        assert_eq!(payouts.get("Bob"), Some(&75.0));
+
+        let job = pool.create_job(template, "Alice");
+        let total: f64 = job.template.coinbase.iter().map(|tx| tx.amount).sum();
+        assert_eq!(job.template.coinbase.len(), 2);
+        assert_eq!(total, Miner::calculate_block_reward(&blockchain));
+    }
+
+    #[test]
+    fn test_full_difficulty_share_is_accepted_by_node() {
+        let mut blockchain = Blockchain::new(2);
+        let template = BlockTemplate::new(&blockchain, "pool");
+        let mut pool = Pool::new("pool", 2, PayoutScheme::Proportional);
+
+        let share = share_for(&mut pool, "Alice", &template);
+        let block = pool.submit_share(&share).unwrap().expect("share should solve the block");
+        assert_eq!(pool.shares.len(), 1);
+        assert!(blockchain.submit_block(block).is_ok());
+        pool.block_accepted(share.job_id);
+        assert!(pool.shares.is_empty());
+    }
+
+    #[test]
+    fn test_rejected_block_keeps_the_round() {
+        let mut blockchain = Blockchain::new(2);
+        let template = BlockTemplate::new(&blockchain, "pool");
+        let mut pool = Pool::new("pool", 2, PayoutScheme::Proportional);
+        pool.unpaid.insert("Carol".to_string(), 1.0);
+
+        // Another miner extends the chain first, so the pool's block is stale
+        let share = share_for(&mut pool, "Alice", &template);
+        assert!(pool.submit_share(&share).unwrap().is_some());
+        blockchain.submit_block(Miner::mine_template(&BlockTemplate::new(&blockchain, "Bob"))).unwrap();
+        assert_eq!(pool.shares.len(), 1);
+        assert_eq!(pool.unpaid.get("Carol"), Some(&1.0));
+
+        // Jobs built on the old tip are dropped once a job for the new tip is handed out
+        let stale = share_for(&mut pool, "Alice", &template);
+        pool.create_job(BlockTemplate::new(&blockchain, "pool"), "Alice");
+        assert_eq!(pool.submit_share(&stale).err(), Some("Unknown or stale job"));
+    }
+
+    #[test]
+    fn test_payouts_never_exceed_reward() {
+        let blockchain = Blockchain::new(3);
+        let mut template = BlockTemplate::new(&blockchain, "pool");
+        template.coinbase[0].amount = 0.7;
+        let mut pool = Pool::new("pool", 1, PayoutScheme::Proportional);
+        for share in 0..30 {
+            pool.shares.push_back(format!("Miner {}", share % 7));
+            let job = pool.create_job(template.clone(), "Alice");
+            let total: f64 = job.template.coinbase.iter().map(|payout| payout.amount).sum();
+            assert!(total <= 0.7);
+            assert!(job.template.coinbase.iter().all(|payout| payout.amount >= 0.0));
+        }
+    }
+
+    #[test]
+    fn test_payouts_respect_block_limits() {
+        let limits = BlockLimits { max_block_transactions: 3, ..BlockLimits::default() };
+        let blockchain = Blockchain::from_spec(ChainSpec { difficulty: 3, limits, ..ChainSpec::default() });
+        let template = BlockTemplate::new(&blockchain, "pool");
+        let mut pool = Pool::new("pool", 1, PayoutScheme::Proportional);
+        for miner in ["Alice", "Alice", "Alice", "Bob", "Bob", "Carol"] {
+            pool.shares.push_back(miner.to_string());
+        }
+
+        let job = pool.create_job(template.clone(), "Alice");
+        let recipients: Vec<_> = job.template.coinbase.iter().map(|payout| payout.recipient.clone().unwrap()).collect();
+        assert_eq!(recipients, vec!["Alice", "pool"]);
+        let total: f64 = job.template.coinbase.iter().map(|payout| payout.amount).sum();
+        assert!((total - template.coinbase[0].amount).abs() < 1e-9);
+        assert!(pool.unpaid.is_empty());
+
+        // Once the job finds a block, what Bob and Carol were not paid carries over
+        let mut network = job.template.clone();
+        network.difficulty = job.network_difficulty;
+        let share = Share { miner: "Alice".to_string(), job_id: job.id, nonce: Miner::mine_template(&network).nonce };
+        assert!(pool.submit_share(&share).unwrap().is_some());
+        assert!(pool.unpaid.is_empty());
+        pool.block_accepted(job.id);
+        let reward = template.coinbase[0].amount;
+        assert_eq!(pool.unpaid.keys().collect::<Vec<_>>(), vec!["Bob", "Carol"]);
+        assert!((pool.unpaid["Bob"] - reward / 3.0).abs() < 1e-9);
+        assert!((pool.unpaid["Carol"] - reward / 6.0).abs() < 1e-9);
+
+        // The next block pays them first, and the pool only keeps what is left
+        let job = pool.create_job(template.clone(), "Alice");
+        let recipients: Vec<_> = job.template.coinbase.iter().map(|payout| payout.recipient.clone().unwrap()).collect();
+        assert_eq!(recipients, vec!["Bob", "pool"]);
+        assert!((job.template.coinbase[0].amount - reward / 3.0).abs() < 1e-9);
+        assert!((job.template.coinbase[1].amount - reward * 2.0 / 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod rpc_test {
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::RwLock;
+    use crate::blockchain::Blockchain;
+    use crate::rpc::{handle_rpc_connection, MAX_BODY_SIZE, MAX_HEADER_SIZE};
+
+    async fn send(request: &[u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_address = listener.local_addr().unwrap();
+        let blockchain = Arc::new(RwLock::new(Blockchain::new(1)));
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            handle_rpc_connection(socket, blockchain).await;
+        });
+
+        let mut client = TcpStream::connect(server_address).await.unwrap();
+        let _ = client.write_all(request).await;
+        let mut response = Vec::new();
+        let _ = client.read_to_end(&mut response).await;
+        String::from_utf8_lossy(&response).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_json_rpc_request() {
+        let body = r#"{"id": 1, "method": "estimatefee"}"#;
+        let request = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+        let response = send(request.as_bytes()).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains(r#""error":null"#));
+    }
+
+    #[tokio::test]
+    async fn test_reject_oversized_requests() {
+        let request = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_SIZE + 1);
+        assert!(send(request.as_bytes()).await.starts_with("HTTP/1.1 413 Payload Too Large"));
+
+        let request = format!("POST / HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_HEADER_SIZE));
+        assert!(send(request.as_bytes()).await.starts_with("HTTP/1.1 431 Request Header Fields Too Large"));
+    }
+}
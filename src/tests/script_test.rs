@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod script_test {
+    use crate::blockchain::BlockError;
+    use crate::tests::util::{funded_addresses, generate_key_pair};
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use crate::miner::Miner;
+    use crate::script::{verify, Op, Script, ScriptContext};
+    use crate::template::BlockTemplate;
+    use crate::transaction::Transaction;
+
+    fn public_key(key_pair: &Ed25519KeyPair) -> Vec<u8> {
+        key_pair.public_key().as_ref().to_vec()
+    }
+
+    fn spend(redeem_script: Script) -> Transaction {
+        let mut transaction = Transaction::new(Some(redeem_script.address()), Some("Bob".to_string()), 5.0, Some(1.0));
+        transaction.redeem_script = Some(redeem_script);
+        transaction
+    }
+
+    #[test]
+    fn test_pay_to_public_key_hash() {
+        let owner = generate_key_pair();
+        let mut transaction = spend(Script::pay_to_public_key_hash(&public_key(&owner)));
+        transaction.unlocking_script = Some(Script::unlock_public_key_hash(transaction.sign_script(&owner), public_key(&owner)));
+        assert_eq!(transaction.verify_script(1, 0), Ok(()));
+
+        let thief = generate_key_pair();
+        transaction.unlocking_script = Some(Script::unlock_public_key_hash(transaction.sign_script(&thief), public_key(&thief)));
+        assert_eq!(transaction.verify_script(1, 0), Err("Script EqualVerify failed"));
+
+        transaction.unlocking_script = Some(Script::unlock_public_key_hash(transaction.sign_script(&owner), public_key(&owner)));
+        transaction.amount = 50.0;
+        assert_eq!(transaction.verify_script(1, 0), Err("Script evaluated to false"));
+    }
+
+    #[test]
+    fn test_multisig() {
+        let keys: Vec<Ed25519KeyPair> = (0..3).map(|_| generate_key_pair()).collect();
+        let public_keys: Vec<Vec<u8>> = keys.iter().map(public_key).collect();
+        let mut transaction = spend(Script::multisig(2, &public_keys));
+
+        let signatures = vec![transaction.sign_script(&keys[0]), transaction.sign_script(&keys[2])];
+        transaction.unlocking_script = Some(Script::unlock_multisig(signatures));
+        assert_eq!(transaction.verify_script(1, 0), Ok(()));
+
+        let out_of_order = vec![transaction.sign_script(&keys[2]), transaction.sign_script(&keys[0])];
+        transaction.unlocking_script = Some(Script::unlock_multisig(out_of_order));
+        assert!(transaction.verify_script(1, 0).is_err());
+
+        let duplicated = vec![transaction.sign_script(&keys[1]), transaction.sign_script(&keys[1])];
+        transaction.unlocking_script = Some(Script::unlock_multisig(duplicated));
+        assert!(transaction.verify_script(1, 0).is_err());
+    }
+
+    #[test]
+    fn test_timelocks() {
+        let owner = generate_key_pair();
+        let locked = Script::after_height(10, Script::pay_to_public_key_hash(&public_key(&owner)));
+        let mut transaction = spend(Script::after_time(1000, locked));
+        transaction.unlocking_script = Some(Script::unlock_public_key_hash(transaction.sign_script(&owner), public_key(&owner)));
+
+        assert_eq!(transaction.verify_script(9, 1000), Err("Script is locked until a later block height"));
+        assert_eq!(transaction.verify_script(10, 999), Err("Script is locked until a later time"));
+        assert_eq!(transaction.verify_script(10, 1000), Ok(()));
+    }
+
+    #[test]
+    fn test_script_rules() {
+        let context = ScriptContext { message: Vec::new(), height: 0, time: 0 };
+        let always = Script(vec![Op::Number(1)]);
+        assert_eq!(verify(&Script::default(), &always, &context), Ok(()));
+        assert_eq!(verify(&Script(vec![Op::Dup]), &always, &context), Err("Unlocking script must only push data"));
+        assert_eq!(verify(&Script::default(), &Script(vec![Op::Drop]), &context), Err("Script stack underflow"));
+        assert_eq!(verify(&Script::default(), &Script(vec![Op::Number(0)]), &context), Err("Script evaluated to false"));
+
+        let mut transaction = spend(always.clone());
+        transaction.unlocking_script = Some(Script::default());
+        assert_eq!(transaction.verify_script(0, 0), Ok(()));
+        transaction.sender = Some(Script(vec![Op::Number(2)]).address());
+        assert_eq!(transaction.verify_script(0, 0), Err("Redeem script does not match the sender address"));
+        transaction.sender = Some("Alice".to_string());
+        assert_eq!(transaction.verify_script(0, 0), Err("Only script addresses can carry scripts"));
+        transaction.redeem_script = None;
+        transaction.unlocking_script = None;
+        assert_eq!(transaction.verify_script(0, 0), Ok(()));
+    }
+
+    #[test]
+    fn test_blockchain_enforces_scripts() {
+        let owner = generate_key_pair();
+        let redeem_script = Script::pay_to_public_key_hash(&public_key(&owner));
+        let mut blockchain = funded_addresses(&[redeem_script.address()]);
+        let mut transaction = spend(redeem_script);
+        assert!(!blockchain.add_transaction(transaction.clone()));
+
+        let mut template = BlockTemplate::new(&blockchain, "Miner");
+        template.transaction = transaction.clone();
+        assert_eq!(blockchain.submit_block(Miner::mine_template(&template)), Err(BlockError::Invalid("Script address spent without its scripts")));
+
+        transaction.unlocking_script = Some(Script::unlock_public_key_hash(transaction.sign_script(&owner), public_key(&owner)));
+        assert!(blockchain.add_transaction(transaction));
+        let block = Miner::mine_template(&BlockTemplate::new(&blockchain, "Miner"));
+        assert_eq!(blockchain.submit_block(block), Ok(()));
+    }
+}
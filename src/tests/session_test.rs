@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod session_test {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::Duration;
+    use tokio::time::timeout;
+    use crate::session::SessionRegistry;
+
+    const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    #[test]
+    fn test_open_and_close() {
+        let mut registry = SessionRegistry::new();
+        let (first, _) = registry.open(LOCALHOST, "alice".to_string(), "node/1".to_string());
+        let (second, _) = registry.open(LOCALHOST, "bob".to_string(), "node/1".to_string());
+        assert_ne!(first, second);
+        assert_eq!(registry.len(), 2);
+
+        registry.close(first);
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.sessions().next().unwrap().identity, "bob");
+        registry.close(second);
+        assert!(registry.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_notifies_sessions_of_identity() {
+        let mut registry = SessionRegistry::new();
+        let (_, alice) = registry.open(LOCALHOST, "alice".to_string(), "node/1".to_string());
+        let (_, bob) = registry.open(LOCALHOST, "bob".to_string(), "node/1".to_string());
+
+        assert_eq!(registry.disconnect("alice"), 1);
+        assert_eq!(registry.disconnect("carol"), 0);
+        assert!(timeout(Duration::from_secs(1), alice.notified()).await.is_ok());
+        assert!(timeout(Duration::from_millis(50), bob.notified()).await.is_err());
+    }
+}
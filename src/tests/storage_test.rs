@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod storage_test {
+    use std::env;
+    use std::fs;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::RwLock;
+    use crate::blockchain::{submit_shared_block, Blockchain};
+    use crate::chain_spec::ChainSpec;
+    use crate::miner::Miner;
+    use crate::storage;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{address, generate_key_pair, signed};
+
+    fn spec() -> ChainSpec {
+        ChainSpec {
+            difficulty: 1,
+            ..ChainSpec::default()
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let data_dir = env::temp_dir().join(format!("storage_test_roundtrip_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let empty = storage::load(spec(), &data_dir).unwrap();
+        assert_eq!(empty.chain.len(), 1);
+
+        let miner = generate_key_pair();
+        let mut blockchain = Blockchain::from_spec(spec());
+        let block = Miner::mine_template(&BlockTemplate::new(&blockchain, &address(&miner)));
+        blockchain.submit_block(block.clone()).unwrap();
+        assert!(blockchain.add_transaction(signed(&miner, "Bob", 1.0, 1.0)));
+        storage::save(&blockchain, &data_dir).unwrap();
+
+        let loaded = storage::load(spec(), &data_dir).unwrap();
+        assert_eq!(loaded.chain.len(), 2);
+        assert_eq!(loaded.chain[1].hash, block.hash);
+        assert_eq!(loaded.mempool.transactions.len(), 1);
+
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn test_load_rejects_tampered_chain() {
+        let data_dir = env::temp_dir().join(format!("storage_test_tampered_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&data_dir);
+        let mut blockchain = Blockchain::from_spec(spec());
+        for _ in 0..2 {
+            let block = Miner::mine_template(&BlockTemplate::new(&blockchain, "Miner"));
+            blockchain.submit_block(block).unwrap();
+        }
+        storage::save(&blockchain, &data_dir).unwrap();
+        assert_eq!(storage::load(spec(), &data_dir).unwrap().chain.len(), 3);
+
+        let mut inflated = blockchain.chain.clone();
+        inflated[1].coinbase[0].amount = 1000.0;
+        fs::write(data_dir.join(storage::CHAIN_FILE), serde_json::to_vec(&inflated).unwrap()).unwrap();
+        assert_eq!(storage::load(spec(), &data_dir).err().map(|e| e.to_string()),
+                   Some(String::from("Stored block hash does not match its contents")));
+
+        let mut reordered = blockchain.chain.clone();
+        reordered.swap(1, 2);
+        fs::write(data_dir.join(storage::CHAIN_FILE), serde_json::to_vec(&reordered).unwrap()).unwrap();
+        assert_eq!(storage::load(spec(), &data_dir).err().map(|e| e.to_string()),
+                   Some(String::from("Stored block does not extend the previous block")));
+
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn test_load_rejects_other_network() {
+        let data_dir = env::temp_dir().join(format!("storage_test_network_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&data_dir);
+        storage::save(&Blockchain::from_spec(spec()), &data_dir).unwrap();
+
+        let other = ChainSpec {
+            genesis_timestamp: spec().genesis_timestamp + 1,
+            ..spec()
+        };
+        assert!(storage::load(other, &data_dir).is_err());
+
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn test_save_on_new_blocks() {
+        let data_dir = env::temp_dir().join(format!("storage_test_new_blocks_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&data_dir);
+        let blockchain = Arc::new(RwLock::new(Blockchain::from_spec(spec())));
+        tokio::spawn(storage::save_on_new_blocks(blockchain.clone(), data_dir.clone()));
+        tokio::task::yield_now().await;
+
+        let block = Miner::mine_template(&BlockTemplate::new(&*blockchain.read().await, "Miner"));
+        submit_shared_block(&blockchain, block.clone()).await.unwrap();
+        let mut saved = storage::load(spec(), &data_dir).map(|loaded| loaded.chain.len()).unwrap_or(0);
+        for _ in 0..100 {
+            if saved == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            saved = storage::load(spec(), &data_dir).map(|loaded| loaded.chain.len()).unwrap_or(0);
+        }
+        assert_eq!(saved, 2);
+        assert_eq!(storage::load(spec(), &data_dir).unwrap().chain[1].hash, block.hash);
+
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+}
@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod sync_test {
+    use std::sync::Arc;
+    use crate::blockchain::Blockchain;
+    use crate::chain_spec::ChainSpec;
+    use crate::consensus::{Consensus, ProofOfAuthority, ProofOfWork};
+    use crate::miner::Miner;
+    use crate::sync::{block_locator, validate_headers};
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{address, generate_key_pair};
+
+    fn sample_blockchain_instance(length: usize) -> Blockchain {
+        let mut blockchain = Blockchain::new(1);
+        for _ in 1..length {
+            let template = BlockTemplate::new(&blockchain, "Anirudh");
+            blockchain.submit_block(Miner::mine_template(&template)).unwrap();
+        }
+        blockchain
+    }
+
+    #[test]
+    fn test_block_locator() {
+        let blockchain = sample_blockchain_instance(40);
+        let locator = blockchain.block_locator();
+
+        assert_eq!(locator.first(), Some(&blockchain.chain[39].hash));
+        assert_eq!(locator.last(), Some(&blockchain.chain[0].hash));
+        assert!(locator.len() < 20);
+
+        let headers: Vec<_> = blockchain.chain.iter().map(|block| block.header()).collect();
+        assert_eq!(block_locator(&headers, |header| &header.hash), locator);
+        assert_eq!(block_locator(&headers[..1], |header| &header.hash), vec![headers[0].hash.clone()]);
+    }
+
+    #[test]
+    fn test_headers_after_locator() {
+        let full = sample_blockchain_instance(10);
+        let mut partial = Blockchain::new(1);
+        partial.chain = full.chain[..4].to_vec();
+
+        let headers = full.headers_after(&partial.block_locator(), 100);
+        assert_eq!(headers.len(), 6);
+        assert_eq!(headers[0].index, 4);
+        assert!(validate_headers(&partial.chain[3].header(), &headers, &ProofOfWork, 1, None).is_ok());
+
+        let limited = full.headers_after(&partial.block_locator(), 2);
+        assert_eq!(limited.len(), 2);
+
+        let blocks = full.blocks_by_hash(&[headers[0].hash.clone(), String::from("unknown")]);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].index, 4);
+    }
+
+    #[test]
+    fn test_reject_invalid_headers() {
+        let blockchain = sample_blockchain_instance(5);
+        let tip = blockchain.chain[0].header();
+        let headers: Vec<_> = blockchain.chain[1..].iter().map(|block| block.header()).collect();
+        assert!(validate_headers(&tip, &headers, &ProofOfWork, 1, None).is_ok());
+
+        let mut tampered = headers.clone();
+        tampered[1].merkle_root = String::from("tampered");
+        assert!(validate_headers(&tip, &tampered, &ProofOfWork, 1, None).is_err());
+
+        let mut unlinked = headers.clone();
+        unlinked.remove(1);
+        assert!(validate_headers(&tip, &unlinked, &ProofOfWork, 1, None).is_err());
+
+        assert!(validate_headers(&tip, &headers, &ProofOfWork, 10, None).is_err());
+    }
+
+    #[test]
+    fn test_headers_are_verified_with_the_chain_consensus() {
+        let mallory = generate_key_pair();
+        let blockchain = sample_blockchain_instance(2);
+        let tip = blockchain.chain[0].header();
+
+        // Claiming a validator does not exempt a header from proof of work
+        let mut forged = blockchain.chain[1].header();
+        forged.validator = Some(address(&mallory));
+        forged.hash = forged.calculate_hash();
+        while forged.hash.starts_with('0') {
+            forged.nonce += 1;
+            forged.hash = forged.calculate_hash();
+        }
+        forged.signature = mallory.sign(forged.hash.as_bytes()).as_ref().to_vec();
+        assert_eq!(validate_headers(&tip, &[forged], &ProofOfWork, 1, None),
+                   Err("Block hash does not meet the difficulty target"));
+
+        let alice = generate_key_pair();
+        let consensus = Arc::new(ProofOfAuthority { validators: vec![address(&alice)], block_interval: 0 });
+        let mut blockchain = Blockchain::with_consensus(ChainSpec { difficulty: 1, ..ChainSpec::default() }, consensus.clone());
+        let mut block = BlockTemplate::new(&blockchain, "Anirudh").to_block();
+        consensus.seal(&mut block, &blockchain, Some(&alice)).unwrap();
+        blockchain.submit_block(block).unwrap();
+        let tip = blockchain.chain[0].header();
+        let header = blockchain.chain[1].header();
+        assert!(validate_headers(&tip, std::slice::from_ref(&header), consensus.as_ref(), 1, None).is_ok());
+
+        let mut tampered = header.clone();
+        tampered.signature[0] ^= 1;
+        assert_eq!(validate_headers(&tip, &[tampered], consensus.as_ref(), 1, None), Err("Block signature is invalid"));
+
+        let mut impostor = header;
+        impostor.validator = Some(address(&mallory));
+        impostor.hash = impostor.calculate_hash();
+        impostor.signature = mallory.sign(impostor.hash.as_bytes()).as_ref().to_vec();
+        assert_eq!(validate_headers(&tip, &[impostor], consensus.as_ref(), 1, None),
+                   Err("Block was not signed by the scheduled authority"));
+    }
+}
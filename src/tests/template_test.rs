@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod template_test {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use crate::blockchain::{BlockError, Blockchain};
+    use crate::chain_spec::{BlockLimits, ChainSpec};
+    use crate::consensus::{Consensus, ProofOfAuthority};
+    use crate::miner::Miner;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{address, generate_key_pair, signed};
+    use crate::transaction::Transaction;
+
+    #[test]
+    fn test_template_includes_mempool_transaction() {
+        let mut blockchain = Blockchain::new(2);
+        let transaction = Transaction::new(Some("Alice".to_string()), 
+                                           Some("Bob".to_string()), 10.0, Some(2.0));
+        blockchain.mempool.add(transaction.clone());
+
+        let template = BlockTemplate::new(&blockchain, "Anirudh");
+        assert_eq!(template.index, 1);
+        assert_eq!(template.previous_hash, blockchain.get_latest_block_hash());
+        assert_eq!(template.target, "00");
+        assert_eq!(template.transaction.calculate_hash(), transaction.calculate_hash());
+        assert_eq!(template.coinbase[0].recipient, Some("Anirudh".to_string()));
+        assert_eq!(template.coinbase[0].amount, Miner::calculate_block_reward(&blockchain) + 2.0);
+    }
+
+    #[test]
+    fn test_submit_mined_template() {
+        let mut blockchain = Blockchain::new(2);
+        blockchain.mempool.add(Transaction::new(Some("Alice".to_string()), 
+                                                Some("Bob".to_string()), 10.0, None));
+
+        let template = BlockTemplate::new(&blockchain, "Anirudh");
+        let block = Miner::mine_template(&template);
+
+        assert!(blockchain.submit_block(block).is_ok());
+        assert_eq!(blockchain.chain.len(), 2);
+        assert!(blockchain.mempool.is_empty());
+        assert_eq!(blockchain.is_valid(), Ok(true));
+    }
+
+    #[test]
+    fn test_reject_invalid_submission() {
+        let mut blockchain = Blockchain::new(2);
+        let template = BlockTemplate::new(&blockchain, "Anirudh");
+
+        let unmined = template.to_block();
+        assert!(blockchain.submit_block(unmined).is_err());
+
+        let mut overpaying = template.clone();
+        overpaying.coinbase[0].amount += 1000.0;
+        let block = Miner::mine_template(&overpaying);
+        assert!(blockchain.submit_block(block).is_err());
+
+        let mut block = Miner::mine_template(&template);
+        block.index = 5;
+        assert!(blockchain.submit_block(block).is_err());
+        assert_eq!(blockchain.chain.len(), 1);
+    }
+
+    #[test]
+    fn test_block_limits() {
+        let (alice, carol) = (generate_key_pair(), generate_key_pair());
+        let limits = BlockLimits { max_block_size: 1024, max_block_transactions: 2 };
+        let allocations = BTreeMap::from([(address(&alice), 100.0), (address(&carol), 100.0)]);
+        let mut blockchain = Blockchain::from_spec(ChainSpec { difficulty: 1, limits, allocations, ..ChainSpec::default() });
+        let mut large = signed(&alice, "Bob", 1.0, 5.0);
+        large.metadata = Some("x".repeat(1024));
+        large.authorize(&alice);
+        let small = signed(&carol, "Dave", 1.0, 1.0);
+        assert!(blockchain.add_transaction(large.clone()));
+        assert!(blockchain.add_transaction(small.clone()));
+
+        // The higher fee transaction does not fit, so the template falls back to the other one
+        let template = BlockTemplate::new(&blockchain, "Miner");
+        assert_eq!(template.limits, limits);
+        assert_eq!(template.transaction.calculate_hash(), small.calculate_hash());
+        let block = Miner::mine_template(&template);
+        assert!(block.size() <= limits.max_block_size);
+
+        let mut oversized = template.clone();
+        oversized.transaction = large;
+        assert_eq!(blockchain.submit_block(Miner::mine_template(&oversized)),
+                   Err(BlockError::Invalid("Block exceeds the maximum block size")));
+
+        let mut crowded = template.clone();
+        crowded.coinbase.push(Transaction::new(None, Some("Eve".to_string()), 0.0, None));
+        assert_eq!(blockchain.submit_block(Miner::mine_template(&crowded)),
+                   Err(BlockError::Invalid("Block has too many transactions")));
+
+        assert!(blockchain.submit_block(block).is_ok());
+        assert_eq!(blockchain.recent_blocks(usize::MAX).len(), 2);
+        let recent = blockchain.recent_blocks(blockchain.chain[1].size());
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].hash, blockchain.chain[1].hash);
+    }
+
+    #[test]
+    fn test_block_limits_leave_room_for_the_seal() {
+        let alice = generate_key_pair();
+        let limits = BlockLimits { max_block_size: 1024, max_block_transactions: 2 };
+        let spec = ChainSpec {
+            difficulty: 1,
+            limits,
+            allocations: BTreeMap::from([(address(&alice), 100.0)]),
+            ..ChainSpec::default()
+        };
+        let consensus = Arc::new(ProofOfAuthority { validators: vec![address(&alice)], block_interval: 0 });
+        let mut blockchain = Blockchain::with_consensus(spec, consensus.clone());
+
+        // Find the largest transaction the template still includes
+        let template = (0..limits.max_block_size).rev().find_map(|length| {
+            let mut transaction = signed(&alice, "Bob", 1.0, 5.0);
+            transaction.metadata = Some("x".repeat(length));
+            transaction.authorize(&alice);
+            blockchain.mempool.transactions.clear();
+            assert!(blockchain.add_transaction(transaction.clone()));
+            let template = BlockTemplate::new(&blockchain, &address(&alice));
+            (template.transaction.calculate_hash() == transaction.calculate_hash()).then_some(template)
+        }).unwrap();
+
+        let mut block = template.to_block();
+        consensus.seal(&mut block, &blockchain, Some(&alice)).unwrap();
+        assert!(block.size() <= limits.max_block_size);
+        assert!(blockchain.submit_block(block).is_ok());
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod token_test {
+    use ring::signature::Ed25519KeyPair;
+    use crate::blockchain::{BlockError, Blockchain};
+    use crate::miner::Miner;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{address, funded_blockchain, generate_key_pair, mine};
+    use crate::token::{is_valid_symbol, TokenOperation};
+    use crate::transaction::Transaction;
+
+    fn operation(sender: &str, recipient: Option<String>, token: TokenOperation, key_pair: &Ed25519KeyPair) -> Transaction {
+        let mut transaction = Transaction::new(Some(sender.to_string()), recipient, 0.0, Some(1.0));
+        transaction.token = Some(token);
+        transaction.authorize(key_pair);
+        transaction
+    }
+
+    fn transfer(sender: &Ed25519KeyPair, recipient: &str, amount: f64) -> Transaction {
+        let mut transaction = Transaction::new(Some(address(sender)), Some(recipient.to_string()), amount, Some(1.0));
+        transaction.asset = Some("GOLD".to_string());
+        transaction.authorize(sender);
+        transaction
+    }
+
+    fn issued_blockchain(issuer: &Ed25519KeyPair) -> Blockchain {
+        let mut blockchain = funded_blockchain(&[issuer]);
+        let issue = TokenOperation::Issue { symbol: "GOLD".to_string(), supply: 100.0, issuer: address(issuer) };
+        mine(&mut blockchain, operation(&address(issuer), None, issue, issuer));
+        blockchain
+    }
+
+    #[test]
+    fn test_symbols() {
+        assert!(is_valid_symbol("GOLD"));
+        assert!(is_valid_symbol("USD1"));
+        assert!(!is_valid_symbol(""));
+        assert!(!is_valid_symbol("gold"));
+        assert!(!is_valid_symbol("TOOLONGSYMBOL"));
+    }
+
+    #[test]
+    fn test_issue_transfer_mint_burn() {
+        let issuer = generate_key_pair();
+        let mut blockchain = issued_blockchain(&issuer);
+        let ledger = blockchain.ledger();
+        assert_eq!(ledger.tokens["GOLD"].supply, 100.0);
+        assert_eq!(ledger.token_balance(&address(&issuer), "GOLD"), 100.0);
+
+        mine(&mut blockchain, transfer(&issuer, "Bob", 30.0));
+        let mint = TokenOperation::Mint { symbol: "GOLD".to_string(), amount: 5.0 };
+        mine(&mut blockchain, operation(&address(&issuer), Some("Bob".to_string()), mint, &issuer));
+        let burn = TokenOperation::Burn { symbol: "GOLD".to_string(), amount: 20.0 };
+        mine(&mut blockchain, operation(&address(&issuer), None, burn, &issuer));
+
+        let ledger = blockchain.ledger();
+        assert_eq!(ledger.token_balance(&address(&issuer), "GOLD"), 50.0);
+        assert_eq!(ledger.token_balance("Bob", "GOLD"), 35.0);
+        assert_eq!(ledger.tokens["GOLD"].supply, 85.0);
+        assert_eq!(ledger.balance("Bob"), 0.0);
+        assert_eq!(ledger.balance(&address(&issuer)), 1000.0 - 4.0);
+    }
+
+    #[test]
+    fn test_token_rules() {
+        let issuer = generate_key_pair();
+        let blockchain = issued_blockchain(&issuer);
+        let ledger = blockchain.ledger();
+        let thief = generate_key_pair();
+
+        let reissue = TokenOperation::Issue { symbol: "GOLD".to_string(), supply: 1.0, issuer: address(&thief) };
+        assert_eq!(ledger.check_tokens(&operation(&address(&thief), None, reissue, &thief)),
+                   Err("Token symbol is already issued"));
+
+        let mint = TokenOperation::Mint { symbol: "GOLD".to_string(), amount: 5.0 };
+        assert_eq!(ledger.check_tokens(&operation(&address(&thief), None, mint, &thief)),
+                   Err("Token operation is not signed by the issuer"));
+
+        assert_eq!(ledger.check_tokens(&transfer(&issuer, "Bob", 101.0)), Err("Insufficient token balance"));
+
+        let mut forged = transfer(&issuer, "Bob", 10.0);
+        forged.authorize(&thief);
+        assert_eq!(ledger.check_tokens(&forged), Err("Token transfer is not authorized by the sender"));
+
+        let mut named = Transaction::new(Some("Alice".to_string()), Some("Bob".to_string()), 1.0, None);
+        named.asset = Some("GOLD".to_string());
+        assert_eq!(ledger.check_tokens(&named), Err("Token transfer is not authorized by the sender"));
+        named.asset = Some("SILVER".to_string());
+        assert_eq!(ledger.check_tokens(&named), Err("Unknown token"));
+    }
+
+    #[test]
+    fn test_mempool_drops_double_spent_tokens() {
+        let issuer = generate_key_pair();
+        let mut blockchain = issued_blockchain(&issuer);
+        assert!(blockchain.add_transaction(transfer(&issuer, "Bob", 80.0)));
+        assert!(blockchain.add_transaction(transfer(&issuer, "Carol", 80.0)));
+
+        let block = Miner::mine_template(&BlockTemplate::new(&blockchain, "Miner"));
+        blockchain.submit_block(block).unwrap();
+        assert!(blockchain.mempool.is_empty());
+    }
+
+    #[test]
+    fn test_reject_replayed_token_operations() {
+        let issuer = generate_key_pair();
+        let mut blockchain = issued_blockchain(&issuer);
+        let mint = TokenOperation::Mint { symbol: "GOLD".to_string(), amount: 5.0 };
+        let minting = operation(&address(&issuer), Some("Bob".to_string()), mint, &issuer);
+        let transfer = transfer(&issuer, "Carol", 10.0);
+        mine(&mut blockchain, minting.clone());
+        mine(&mut blockchain, transfer.clone());
+
+        for replayed in [minting, transfer] {
+            assert!(!blockchain.add_transaction(replayed.clone()));
+            let mut template = BlockTemplate::new(&blockchain, "Miner");
+            template.transaction = replayed;
+            assert_eq!(blockchain.submit_block(Miner::mine_template(&template)),
+                       Err(BlockError::Invalid("Block includes a transaction already on the blockchain")));
+        }
+        let ledger = blockchain.ledger();
+        assert_eq!(ledger.tokens["GOLD"].supply, 105.0);
+        assert_eq!(ledger.token_balance("Bob", "GOLD"), 5.0);
+        assert_eq!(ledger.token_balance("Carol", "GOLD"), 10.0);
+    }
+}
@@ -2,7 +2,11 @@
 mod transaction_tests {
     use ring::signature::{Ed25519KeyPair, KeyPair};
     use ring::rand::SystemRandom;
-    use crate::transaction::Transaction;
+    use crate::blockchain::BlockError;
+    use crate::miner::Miner;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{funded_blockchain, generate_key_pair, signed};
+    use crate::transaction::{LockTime, Transaction};
 
     #[test]
     fn test_sign_and_verify_transaction() {
@@ -41,4 +45,63 @@ mod transaction_tests {
         let public_key = key_pair.public_key().as_ref();
         assert!(!transaction.verify_signature(public_key));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_lock_time_finality() {
+        let mut transaction = Transaction::new(Some(String::from("Alice")), Some(String::from("Bob")), 5.0, None);
+        assert!(transaction.is_final(0, 0));
+
+        transaction.lock_time = Some(LockTime::Height(3));
+        assert!(!transaction.is_final(2, u64::MAX));
+        assert!(transaction.is_final(3, 0));
+
+        transaction.lock_time = Some(LockTime::Time(1000));
+        assert!(!transaction.is_final(u64::MAX, 999));
+        assert!(transaction.is_final(0, 1000));
+    }
+
+    #[test]
+    fn test_height_locked_transaction_waits_for_height() {
+        let alice = generate_key_pair();
+        let mut blockchain = funded_blockchain(&[&alice]);
+        let mut transaction = signed(&alice, "Bob", 5.0, 1.0);
+        transaction.lock_time = Some(LockTime::Height(2));
+        transaction.authorize(&alice);
+        assert!(!blockchain.add_transaction(transaction.clone()));
+
+        let mut template = BlockTemplate::new(&blockchain, "Miner");
+        template.transaction = transaction.clone();
+        assert_eq!(blockchain.submit_block(Miner::mine_template(&template)),
+                   Err(BlockError::Invalid("Block includes a transaction before its lock time")));
+
+        let block = Miner::mine_template(&BlockTemplate::new(&blockchain, "Miner"));
+        blockchain.submit_block(block).unwrap();
+        assert!(blockchain.add_transaction(transaction.clone()));
+        let block = Miner::mine_template(&BlockTemplate::new(&blockchain, "Miner"));
+        assert_eq!(block.transaction.calculate_hash(), transaction.calculate_hash());
+        assert_eq!(blockchain.submit_block(block), Ok(()));
+    }
+
+    #[test]
+    fn test_time_lock_waits_for_median_time_past() {
+        let alice = generate_key_pair();
+        let mut blockchain = funded_blockchain(&[&alice]);
+        let genesis_time = blockchain.chain[0].timestamp;
+        let mut transaction = signed(&alice, "Bob", 5.0, 1.0);
+        transaction.lock_time = Some(LockTime::Time(genesis_time + 600));
+        transaction.authorize(&alice);
+        assert!(!blockchain.add_transaction(transaction.clone()));
+
+        // A producer cannot unlock the transaction early by picking its own block timestamp
+        let mut template = BlockTemplate::new(&blockchain, "Miner");
+        template.transaction = transaction;
+        template.timestamp = genesis_time + 601;
+        assert_eq!(blockchain.submit_block(Miner::mine_template(&template)),
+                   Err(BlockError::Invalid("Block includes a transaction before its lock time")));
+
+        let mut unlocked = signed(&alice, "Bob", 5.0, 1.0);
+        unlocked.lock_time = Some(LockTime::Time(genesis_time));
+        unlocked.authorize(&alice);
+        assert!(blockchain.add_transaction(unlocked));
+    }
+}
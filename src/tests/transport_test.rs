@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod transport_test {
+    use std::io::ErrorKind;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use tokio::net::{TcpListener, TcpStream};
+    use crate::hex;
+    use crate::network::Message;
+    use crate::transport::{read_frame, write_frame, SecureChannel};
+    use crate::wire::Encoding;
+
+    fn key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_handshake_authenticates_both_sides() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap();
+        let (alice, bob) = (key_pair(), key_pair());
+        let alice_public = hex::encode(alice.public_key().as_ref());
+        let bob_public = hex::encode(bob.public_key().as_ref());
+
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut channel = SecureChannel::handshake(socket, &bob, false).await.unwrap();
+            let message = channel.receive().await.unwrap();
+            channel.send(&Message::Disconnect(String::from("Bob"))).await.unwrap();
+            (channel.remote_identity(), message)
+        });
+
+        let socket = TcpStream::connect(target).await.unwrap();
+        let mut channel = SecureChannel::handshake(socket, &alice, true).await.unwrap();
+        assert_eq!(channel.remote_identity(), bob_public);
+        assert_eq!(channel.encoding(), Encoding::Binary);
+        channel.send(&Message::Connect(String::from("Alice"))).await.unwrap();
+        assert!(matches!(channel.receive().await.unwrap(), Message::Disconnect(name) if name == "Bob"));
+
+        let (identity, message) = server.await.unwrap();
+        assert_eq!(identity, alice_public);
+        assert!(matches!(message, Message::Connect(name) if name == "Alice"));
+    }
+
+    #[tokio::test]
+    async fn test_handshake_rejects_forged_identity() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let _ = read_frame(&mut socket).await;
+            write_frame(&mut socket, &[7; 32]).await.unwrap();
+            write_frame(&mut socket, &[0; 112]).await.unwrap();
+            let _ = read_frame(&mut socket).await;
+        });
+
+        let socket = TcpStream::connect(target).await.unwrap();
+        let result = SecureChannel::handshake(socket, &key_pair(), true).await;
+        assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::PermissionDenied));
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejects_unexpected_identity() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap().to_string();
+        let (bob, mallory) = (key_pair(), key_pair());
+        let bob_public = hex::encode(bob.public_key().as_ref());
+
+        tokio::spawn(async move {
+            for key_pair in [bob, mallory] {
+                let (socket, _) = listener.accept().await.unwrap();
+                let mut channel = SecureChannel::handshake(socket, &key_pair, false).await.unwrap();
+                let _ = channel.receive().await;
+            }
+        });
+
+        let channel = SecureChannel::connect(&target, Some(&bob_public.to_uppercase())).await.unwrap();
+        assert_eq!(channel.remote_identity(), bob_public);
+        drop(channel);
+        let result = SecureChannel::connect(&target, Some(&bob_public)).await;
+        assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::PermissionDenied));
+    }
+
+    #[tokio::test]
+    async fn test_handshake_rejects_oversized_ephemeral_key() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let _ = read_frame(&mut socket).await;
+            let _ = write_frame(&mut socket, &[7; 4096]).await;
+        });
+
+        let socket = TcpStream::connect(target).await.unwrap();
+        let result = SecureChannel::handshake(socket, &key_pair(), true).await;
+        assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::PermissionDenied));
+    }
+}
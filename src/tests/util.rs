@@ -0,0 +1,46 @@
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use crate::blockchain::Blockchain;
+use crate::chain_spec::ChainSpec;
+use crate::hex;
+use crate::miner::Miner;
+use crate::template::BlockTemplate;
+use crate::transaction::Transaction;
+
+/// Generates a new random Ed25519 key pair
+pub fn generate_key_pair() -> Ed25519KeyPair {
+    let rng = SystemRandom::new();
+    let key_pair = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    Ed25519KeyPair::from_pkcs8(key_pair.as_ref()).unwrap()
+}
+
+/// Gets the key address of a key pair, its hex encoded public key
+pub fn address(key_pair: &Ed25519KeyPair) -> String {
+    hex::encode(key_pair.public_key().as_ref())
+}
+
+/// Adds a `Transaction` to the `Mempool`, then mines and submits a `Block` including it
+pub fn mine(blockchain: &mut Blockchain, transaction: Transaction) {
+    assert!(blockchain.add_transaction(transaction));
+    let block = Miner::mine_template(&BlockTemplate::new(blockchain, "Miner"));
+    blockchain.submit_block(block).unwrap();
+}
+
+/// Builds a `Blockchain` of difficulty 1 whose genesis `Block` allocates 1000 cryptos to
+/// each address
+pub fn funded_addresses(addresses: &[String]) -> Blockchain {
+    let allocations = addresses.iter().map(|address| (address.clone(), 1000.0)).collect();
+    Blockchain::from_spec(ChainSpec { difficulty: 1, allocations, ..ChainSpec::default() })
+}
+
+/// Builds a `Blockchain` like `funded_addresses` for the key address of each key pair
+pub fn funded_blockchain(key_pairs: &[&Ed25519KeyPair]) -> Blockchain {
+    funded_addresses(&key_pairs.iter().map(|key_pair| address(key_pair)).collect::<Vec<_>>())
+}
+
+/// Creates a `Transaction` from the key address of `sender`, signed with `Transaction::authorize`
+pub fn signed(sender: &Ed25519KeyPair, recipient: &str, amount: f64, fee: f64) -> Transaction {
+    let mut transaction = Transaction::new(Some(address(sender)), Some(recipient.to_string()), amount, Some(fee));
+    transaction.authorize(sender);
+    transaction
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod version_test {
+    use std::io::ErrorKind;
+    use tokio::net::{TcpListener, TcpStream};
+    use crate::transport::{identity, SecureChannel};
+    use crate::version::{accept_version, initiate_version, Version, FULL_NODE_CAPABILITIES,
+                         MIN_PROTOCOL_VERSION, NODE_COMPACT_BLOCKS, NODE_NETWORK};
+
+    #[test]
+    fn test_check() {
+        let local = Version::new("devnet", 10, FULL_NODE_CAPABILITIES);
+        assert!(Version::new("devnet", 3, 0).check(&local).is_ok());
+        assert_eq!(Version::new("mainnet", 3, 0).check(&local), Err("Peer belongs to a different network"));
+
+        let old = Version { protocol_version: MIN_PROTOCOL_VERSION - 1, ..Version::new("devnet", 3, 0) };
+        assert_eq!(old.check(&local), Err("Peer protocol version is too old"));
+    }
+
+    #[test]
+    fn test_supports() {
+        let version = Version::new("devnet", 0, NODE_NETWORK);
+        assert!(version.supports(NODE_NETWORK));
+        assert!(!version.supports(NODE_COMPACT_BLOCKS));
+        assert!(!version.supports(NODE_NETWORK | NODE_COMPACT_BLOCKS));
+        assert!(Version::new("devnet", 0, FULL_NODE_CAPABILITIES).supports(NODE_NETWORK | NODE_COMPACT_BLOCKS));
+    }
+
+    async fn handshake(server: Version, client: Version) -> (Result<Version, ErrorKind>, Result<Version, ErrorKind>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap();
+        let responder = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut channel = SecureChannel::handshake(socket, identity(), false).await.unwrap();
+            accept_version(&mut channel, &server).await.map_err(|e| e.kind())
+        });
+
+        let socket = TcpStream::connect(target).await.unwrap();
+        let mut channel = SecureChannel::handshake(socket, identity(), true).await.unwrap();
+        let initiated = initiate_version(&mut channel, &client).await.map_err(|e| e.kind());
+        (initiated, responder.await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_handshake() {
+        let server = Version::new("devnet", 42, FULL_NODE_CAPABILITIES);
+        let client = Version::new("devnet", 0, 0);
+        let (initiated, accepted) = handshake(server.clone(), client.clone()).await;
+        assert_eq!(initiated, Ok(server));
+        assert_eq!(accepted, Ok(client));
+    }
+
+    #[tokio::test]
+    async fn test_handshake_refuses_other_network() {
+        let server = Version::new("devnet", 42, FULL_NODE_CAPABILITIES);
+        let client = Version::new("mainnet", 0, 0);
+        let (initiated, accepted) = handshake(server, client).await;
+        assert_eq!(initiated, Err(ErrorKind::ConnectionRefused));
+        assert_eq!(accepted, Err(ErrorKind::ConnectionRefused));
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod vm_test {
+    use std::collections::BTreeMap;
+    use ring::signature::Ed25519KeyPair;
+    use crate::blockchain::BlockError;
+    use crate::contract::{contract_address, ContractAction, ContractCall, MAX_GAS_LIMIT};
+    use crate::miner::Miner;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{address, funded_blockchain, generate_key_pair, mine};
+    use crate::transaction::Transaction;
+    use crate::vm::{execute, Instruction};
+
+    /// Adds the first argument to the counter stored under key `0` and returns the new total
+    fn counter() -> Vec<Instruction> {
+        vec![
+            Instruction::Push(0),
+            Instruction::Push(0),
+            Instruction::Load,
+            Instruction::Input(0),
+            Instruction::Add,
+            Instruction::Store,
+            Instruction::Push(0),
+            Instruction::Load,
+            Instruction::Return,
+        ]
+    }
+
+    fn contract_transaction(sender: &Ed25519KeyPair, action: ContractAction, gas_limit: u64) -> Transaction {
+        let mut transaction = Transaction::new(Some(address(sender)), None, 0.0, Some(1.0));
+        transaction.contract = Some(ContractCall { action, gas_limit, gas_price: 0.01 });
+        transaction.authorize(sender);
+        transaction
+    }
+
+    #[test]
+    fn test_execute_counter() {
+        let mut storage = BTreeMap::new();
+        let execution = execute(&counter(), &mut storage, &[5], 1000);
+        assert_eq!(execution.result, Ok(Some(5)));
+        assert_eq!(execution.gas_used, 146);
+        assert_eq!(execute(&counter(), &mut storage, &[2], 1000).result, Ok(Some(7)));
+        assert_eq!(storage[&0], 7);
+    }
+
+    #[test]
+    fn test_failures_discard_storage() {
+        let mut storage = BTreeMap::new();
+        let execution = execute(&counter(), &mut storage, &[5], 100);
+        assert_eq!(execution.result, Err(String::from("Out of gas")));
+        assert_eq!(execution.gas_used, 100);
+        assert!(storage.is_empty());
+
+        let reverting = vec![Instruction::Push(1), Instruction::Push(2), Instruction::Store, Instruction::Revert];
+        assert_eq!(execute(&reverting, &mut storage, &[], 1000).result, Err(String::from("Reverted")));
+        assert!(storage.is_empty());
+
+        let dividing = vec![Instruction::Push(1), Instruction::Push(0), Instruction::Div];
+        assert_eq!(execute(&dividing, &mut storage, &[], 1000).result, Err(String::from("Arithmetic error")));
+        assert_eq!(execute(&[Instruction::Add], &mut storage, &[], 1000).result, Err(String::from("Stack underflow")));
+    }
+
+    #[test]
+    fn test_infinite_loop_runs_out_of_gas() {
+        let looping = vec![Instruction::Jump(0)];
+        let execution = execute(&looping, &mut BTreeMap::new(), &[], 10_000);
+        assert_eq!(execution.result, Err(String::from("Out of gas")));
+        assert_eq!(execution.gas_used, 10_000);
+    }
+
+    #[test]
+    fn test_deploy_and_call_on_chain() {
+        let alice = generate_key_pair();
+        let mut blockchain = funded_blockchain(&[&alice]);
+        let genesis_root = blockchain.chain[0].state_root.clone();
+
+        let deployment = contract_transaction(&alice, ContractAction::Deploy(counter()), 1000);
+        let contract = contract_address(&deployment);
+        mine(&mut blockchain, deployment);
+        assert_ne!(blockchain.chain[1].state_root, genesis_root);
+
+        let call = contract_transaction(&alice, ContractAction::Call { contract: contract.clone(), input: vec![42] }, 1000);
+        mine(&mut blockchain, call);
+        assert_eq!(blockchain.contracts.contracts[&contract].storage[&0], 42);
+        assert_eq!(blockchain.chain[2].state_root, blockchain.contracts.root());
+
+        // The fee and the whole gas limit are charged to the sender
+        assert_eq!(blockchain.ledger().balance(&address(&alice)), 1000.0 - 2.0 * (1.0 + 1000.0 * 0.01));
+    }
+
+    #[test]
+    fn test_validation_checks_state_root_and_gas_limit() {
+        let alice = generate_key_pair();
+        let mut blockchain = funded_blockchain(&[&alice]);
+        let deployment = contract_transaction(&alice, ContractAction::Deploy(counter()), 1000);
+        let mut template = BlockTemplate::new(&blockchain, "Miner");
+        template.transaction = deployment;
+        template.coinbase[0].amount += 10.0;
+        assert_eq!(blockchain.submit_block(Miner::mine_template(&template)),
+                   Err(BlockError::Invalid("Block state root does not match its contracts")));
+
+        let oversized = contract_transaction(&alice, ContractAction::Deploy(counter()), MAX_GAS_LIMIT + 1);
+        assert!(!blockchain.add_transaction(oversized));
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod websocket_test {
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::RwLock;
+    use crate::blockchain::Blockchain;
+    use crate::rpc::handle_rpc_connection;
+    use crate::websocket::{accept_key, read_frame, write_frame, OPCODE_TEXT};
+
+    #[test]
+    fn test_accept_key() {
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[tokio::test]
+    async fn test_read_masked_frame() {
+        let payload = b"{\"method\":\"subscribe\",\"params\":[\"NewBlocks\"]}";
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![0x80 | OPCODE_TEXT, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        client.write_all(&frame).await.unwrap();
+        assert_eq!(read_frame(&mut server).await.unwrap(), (OPCODE_TEXT, payload.to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unmasked_frame() {
+        let (mut server, mut client) = tokio::io::duplex(1024);
+        write_frame(&mut server, OPCODE_TEXT, b"hello").await.unwrap();
+        assert!(read_frame(&mut client).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_keeps_the_case_of_the_key() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_address = listener.local_addr().unwrap();
+        let blockchain = Arc::new(RwLock::new(Blockchain::new(1)));
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            handle_rpc_connection(socket, blockchain).await;
+        });
+
+        let mut client = TcpStream::connect(server_address).await.unwrap();
+        let request = "GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: WebSocket\r\nConnection: Upgrade\r\n\
+                       Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n";
+        client.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = Vec::new();
+        let mut buffer = [0; 256];
+        while !response.ends_with(b"\r\n\r\n") {
+            let size = client.read(&mut buffer).await.unwrap();
+            assert!(size > 0, "Connection closed before the handshake completed");
+            response.extend_from_slice(&buffer[..size]);
+        }
+        let response = String::from_utf8(response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 101 Switching Protocols"));
+        assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod wire_test {
+    use crate::compact::CompactBlock;
+    use crate::miner::Miner;
+    use crate::network::Message;
+    use crate::template::BlockTemplate;
+    use crate::tests::util::{funded_blockchain, generate_key_pair, signed};
+    use crate::wire::{from_bytes, to_bytes, Encoding, MAGIC};
+
+    fn sample_messages() -> Vec<Message> {
+        let alice = generate_key_pair();
+        let mut blockchain = funded_blockchain(&[&alice]);
+        blockchain.mempool.add(signed(&alice, "Bob", 10.0, 1.5));
+        let template = BlockTemplate::new(&blockchain, "Anirudh");
+        let mut block = Miner::mine_template(&template);
+        block.signature = vec![7; 64];
+        blockchain.submit_block(block.clone()).unwrap();
+
+        vec![
+            Message::RequestChain,
+            Message::SubmitResult(Err(String::from("Invalid block"))),
+            Message::CompactBlock(CompactBlock::from_block(&block)),
+            Message::MerkleProof(blockchain.merkle_proof(&block.transaction.calculate_hash())),
+            Message::Headers(vec![block.header()]),
+            Message::SubmitBlock(block),
+            Message::Addr(vec![(String::from("127.0.0.1:9000"), 1700000000)]),
+        ]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for message in sample_messages() {
+            let decoded: Message = from_bytes(&to_bytes(&message).unwrap()).unwrap();
+            assert_eq!(serde_json::to_string(&decoded).unwrap(), serde_json::to_string(&message).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_binary_is_smaller() {
+        for message in sample_messages() {
+            let binary = Encoding::Binary.encode(&message).unwrap();
+            let json = Encoding::Json.encode(&message).unwrap();
+            assert!(binary.len() < json.len(), "{:?}", message);
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_data() {
+        let mut data = to_bytes(&Message::GetAddr(String::from("127.0.0.1:9000"))).unwrap();
+        assert!(from_bytes::<Message>(&data[..data.len() - 1]).is_err());
+        assert!(from_bytes::<Message>(&data[MAGIC.len()..]).is_err());
+
+        data[MAGIC.len()] += 1;
+        assert!(from_bytes::<Message>(&data).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use crate::hex;
+
+/// The maximum length of a `Token` symbol
+pub const MAX_SYMBOL_LENGTH: usize = 12;
+
+/// The length in hex digits of an address which is an Ed25519 public key
+const KEY_ADDRESS_LENGTH: usize = 64;
+
+/// A fungible asset issued on top of the native coin
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Token {
+    /// The unique symbol identifying this `Token`, such as `USD`
+    pub symbol: String,
+
+    /// The hex encoded Ed25519 public key allowed to mint and burn this `Token`
+    pub issuer: String,
+
+    /// The amount of this `Token` in circulation
+    pub supply: f64,
+}
+
+/// An operation on a `Token`, which must be signed by its issuer
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum TokenOperation {
+    /// Creates a new `Token`, crediting its initial supply to the issuer
+    Issue {
+        /// The symbol of the new `Token`
+        symbol: String,
+
+        /// The initial supply, credited to the issuer's address
+        supply: f64,
+
+        /// The hex encoded Ed25519 public key of the issuer, which is also its address
+        issuer: String,
+    },
+
+    /// Creates new units of a `Token`, credited to the recipient of the `Transaction`
+    Mint {
+        /// The symbol of the `Token`
+        symbol: String,
+
+        /// The amount created
+        amount: f64,
+    },
+
+    /// Destroys units of a `Token` held by its issuer
+    Burn {
+        /// The symbol of the `Token`
+        symbol: String,
+
+        /// The amount destroyed
+        amount: f64,
+    },
+}
+
+/// Checks whether an address is a hex encoded Ed25519 public key, whose owner can sign for it
+pub fn is_key_address(address: &str) -> bool {
+    address.len() == KEY_ADDRESS_LENGTH && hex::decode(address).is_some()
+}
+
+/// Checks whether a `Token` symbol is 1 to `MAX_SYMBOL_LENGTH` uppercase letters or digits
+pub fn is_valid_symbol(symbol: &str) -> bool {
+    !symbol.is_empty()
+        && symbol.len() <= MAX_SYMBOL_LENGTH
+        && symbol.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// Checks whether a `Token` amount is finite and positive
+pub fn is_valid_amount(amount: f64) -> bool {
+    amount.is_finite() && amount > 0.0
+}
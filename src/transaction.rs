@@ -1,6 +1,24 @@
 use std::time::{SystemTime, UNIX_EPOCH};
+use sha2::{Digest, Sha256};
 use ring::signature::{Ed25519KeyPair, UnparsedPublicKey, ED25519};
 use serde::{Deserialize, Serialize};
+use crate::contract::ContractCall;
+use crate::hex;
+use crate::nft::NftOperation;
+use crate::notary::Notarization;
+use crate::token::TokenOperation;
+use crate::wire;
+use crate::script::{self, Script, ScriptContext, SCRIPT_ADDRESS_PREFIX};
+
+/// The earliest point at which a `Transaction` may be included in a `Block`
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum LockTime {
+    /// The `Transaction` may only be included in `Block`s at or above this index
+    Height(u64),
+
+    /// The `Transaction` may only be included in `Block`s whose median time past is at or after this Unix time
+    Time(u64),
+}
 
 /// Represents a transaction for a `Block`
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -20,14 +38,51 @@ pub struct Transaction {
     /// The digital signature of this `Transaction`
     pub signature: Vec<u8>,
     
-    /// An optional unique identifier for this `Transaction`
+    /// An optional identifier for this `Transaction`, unique among those of its sender
+    ///
+    /// A pending `Transaction` is replaced by one with the same sender and identifier paying
+    /// a higher fee, and no two `Transaction`s with the same sender and identifier can be
+    /// included in the `Blockchain`
     pub transaction_id: Option<String>,
     
     /// An optional fee for this `Transaction`
     pub fee: Option<f64>,
     
     /// Optional additional data or notes related to this `Transaction`
-    pub metadata: Option<String>
+    pub metadata: Option<String>,
+
+    /// The `Script` locking the funds of the sender, if the sender is a `Script::address`
+    #[serde(default)]
+    pub redeem_script: Option<Script>,
+
+    /// The `Script` pushing the data, such as signatures, that satisfies the `redeem_script`
+    #[serde(default)]
+    pub unlocking_script: Option<Script>,
+
+    /// The `LockTime` before which this `Transaction` may not be included, or `None` if it
+    /// may be included right away
+    #[serde(default)]
+    pub lock_time: Option<LockTime>,
+
+    /// The `Contract` deployment or call this `Transaction` carries, if any
+    #[serde(default)]
+    pub contract: Option<ContractCall>,
+
+    /// The symbol of the `Token` the `amount` is paid in, or `None` for the native coin
+    #[serde(default)]
+    pub asset: Option<String>,
+
+    /// The `TokenOperation` this `Transaction` carries, if any
+    #[serde(default)]
+    pub token: Option<TokenOperation>,
+
+    /// The `NftOperation` this `Transaction` carries, if any
+    #[serde(default)]
+    pub nft: Option<NftOperation>,
+
+    /// The `Notarization` this `Transaction` anchors, if any
+    #[serde(default)]
+    pub notarization: Option<Notarization>,
 }
 impl Transaction {
     /// Creates a new `Transaction`.
@@ -51,7 +106,15 @@ impl Transaction {
             signature: Vec::new(),
             transaction_id: None,
             fee,
-            metadata: None
+            metadata: None,
+            redeem_script: None,
+            unlocking_script: None,
+            lock_time: None,
+            contract: None,
+            asset: None,
+            token: None,
+            nft: None,
+            notarization: None,
         }
     }
    
@@ -74,7 +137,7 @@ impl Transaction {
     /// 
     /// # Parameters
     /// - `public_key` - The miner's public key, which is used to verify the digital signature
-    ///    of the transaction
+    ///   of the transaction
     /// 
     /// # Returns
     /// - `bool` - A boolean value containing whether the signature could be verified
@@ -87,4 +150,182 @@ impl Transaction {
         let public_key = UnparsedPublicKey::new(&ED25519, public_key);
         public_key.verify(message.as_bytes(), &self.signature).is_ok()
     }
+
+    /// Computes the message signed by the keys of a `Script`, committing to everything but
+    /// the `unlocking_script` which will carry the signatures
+    pub fn signature_hash(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}",
+                              self.sender,
+                              self.recipient,
+                              self.amount,
+                              self.timestamp,
+                              self.transaction_id,
+                              self.fee,
+                              self.metadata,
+                              self.redeem_script,
+                              self.lock_time,
+                              self.contract,
+                              self.asset,
+                              self.token,
+                              self.nft,
+                              self.notarization));
+        hasher.finalize().to_vec()
+    }
+
+    /// Signs the `signature_hash` of this `Transaction`, to be placed in its `unlocking_script`
+    ///
+    /// # Parameters
+    /// - `key_pair` - The ed25519 key-pair of one of the keys of the `redeem_script`
+    pub fn sign_script(&self, key_pair: &Ed25519KeyPair) -> Vec<u8> {
+        key_pair.sign(&self.signature_hash()).as_ref().to_vec()
+    }
+
+    /// Signs the `signature_hash` of this `Transaction` into its `signature`, authorizing it
+    /// on behalf of a key address or a `Token` issuer
+    ///
+    /// # Parameters
+    /// - `key_pair` - The ed25519 key-pair of the address or issuer
+    pub fn authorize(&mut self, key_pair: &Ed25519KeyPair) {
+        self.signature = self.sign_script(key_pair);
+    }
+
+    /// Verifies that `signature` was made by `authorize` with the given hex encoded public key
+    pub fn is_authorized_by(&self, public_key: &str) -> bool {
+        hex::decode(public_key).is_some_and(|public_key| {
+            UnparsedPublicKey::new(&ED25519, public_key).verify(&self.signature_hash(), &self.signature).is_ok()
+        })
+    }
+
+    /// Checks that the sender of this `Transaction` authorized it: a script address with its
+    /// `Script`s, and a key address with a signature made by `authorize`
+    ///
+    /// # Parameters
+    /// - `height` - The index of the `Block` that would include this `Transaction`
+    /// - `time` - The time the `Script`s are evaluated at
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if the `Transaction` is authorized or has no sender,
+    ///   or an `Err(&str)` describing why it is not
+    pub fn verify_sender(&self, height: u64, time: u64) -> Result<(), &'static str> {
+        self.verify_script(height, time)?;
+        match &self.sender {
+            Some(sender) if !sender.starts_with(SCRIPT_ADDRESS_PREFIX) && !self.is_authorized_by(sender) => {
+                Err("Transaction is not authorized by its sender")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks whether an address is the sender or the recipient of this `Transaction`
+    pub fn involves(&self, address: &str) -> bool {
+        self.sender.as_deref() == Some(address) || self.recipient.as_deref() == Some(address)
+    }
+
+    /// The native coins the sender of this `Transaction` is debited: its `amount`, unless it
+    /// is paid in a `Token`, plus its `total_fee`
+    pub fn cost(&self) -> f64 {
+        let amount = if self.asset.is_some() { 0.0 } else { self.amount };
+        amount + self.total_fee()
+    }
+
+    /// Checks whether this `Transaction` transfers a `Token` or carries a `TokenOperation`
+    pub fn moves_tokens(&self) -> bool {
+        self.asset.is_some() || self.token.is_some()
+    }
+
+    /// The gas fee of the `ContractCall` of this `Transaction`, or `0.0` if there is none
+    pub fn gas_fee(&self) -> f64 {
+        self.contract.as_ref().map_or(0.0, ContractCall::gas_fee)
+    }
+
+    /// Checks whether this `Transaction` conflicts with another one, because they are
+    /// different `Transaction`s with the same sender and `transaction_id`
+    pub fn conflicts_with(&self, other: &Transaction) -> bool {
+        self.sender.is_some()
+            && self.transaction_id.is_some()
+            && self.sender == other.sender
+            && self.transaction_id == other.transaction_id
+            && self.calculate_hash() != other.calculate_hash()
+    }
+
+    /// Gets the size in bytes of this `Transaction` in the binary wire encoding, or `usize::MAX` if
+    /// it cannot be encoded, so that it is over every size limit
+    pub fn size(&self) -> usize {
+        wire::to_bytes(self).map_or(usize::MAX, |bytes| bytes.len())
+    }
+
+    /// Everything the sender pays the producer of the `Block` including this `Transaction`:
+    /// its `fee` plus its `gas_fee`
+    pub fn total_fee(&self) -> f64 {
+        self.fee.unwrap_or(0.0) + self.gas_fee()
+    }
+
+    /// Checks whether the `lock_time` of this `Transaction` has passed
+    ///
+    /// # Parameters
+    /// - `height` - The index of the `Block` that would include this `Transaction`
+    /// - `time` - The median time past of the `Block` that would include this `Transaction`
+    pub fn is_final(&self, height: u64, time: u64) -> bool {
+        match self.lock_time {
+            None => true,
+            Some(LockTime::Height(lock_height)) => height >= lock_height,
+            Some(LockTime::Time(lock_time)) => time >= lock_time,
+        }
+    }
+
+    /// Verifies that this `Transaction` may spend the funds of its sender
+    ///
+    /// Senders which are a `Script::address` require a matching `redeem_script`, and an
+    /// `unlocking_script` which satisfies it. Other senders must not carry any `Script`
+    ///
+    /// # Parameters
+    /// - `height` - The index of the `Block` including this `Transaction`
+    /// - `time` - The median time past of the `Block` including this `Transaction`
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if the `Transaction` may be included, or an `Err(&str)`
+    ///   describing why its `Script`s failed
+    pub fn verify_script(&self, height: u64, time: u64) -> Result<(), &'static str> {
+        let locked = self.sender.as_deref().is_some_and(|sender| sender.starts_with(SCRIPT_ADDRESS_PREFIX));
+        match (&self.redeem_script, &self.unlocking_script) {
+            (Some(redeem_script), Some(unlocking_script)) if locked => {
+                if self.sender.as_deref() != Some(redeem_script.address().as_str()) {
+                    return Err("Redeem script does not match the sender address");
+                }
+                let context = ScriptContext { message: self.signature_hash(), height, time };
+                script::verify(unlocking_script, redeem_script, &context)
+            }
+            _ if locked => Err("Script address spent without its scripts"),
+            (None, None) => Ok(()),
+            _ => Err("Only script addresses can carry scripts"),
+        }
+    }
+
+    /// Generates a [SHA-256](https://securiti.ai/glossary/secure-hash-algorithm-sha-256-bit/)
+    /// hash of this `Transaction`'s contents, used to identify it in the `Mempool` and in `Block`s
+    ///
+    /// # Returns
+    /// - `String` - A hexadecimal `String` containing the hash of this `Transaction`
+    pub fn calculate_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}{:?}",
+                              self.sender,
+                              self.recipient,
+                              self.amount,
+                              self.timestamp,
+                              self.transaction_id,
+                              self.fee,
+                              self.metadata,
+                              self.signature,
+                              self.redeem_script,
+                              self.unlocking_script,
+                              self.lock_time,
+                              self.contract,
+                              self.asset,
+                              self.token,
+                              self.nft,
+                              self.notarization));
+        format!("{:x}", hasher.finalize())
+    }
 }
\ No newline at end of file
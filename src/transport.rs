@@ -0,0 +1,331 @@
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::agreement::{self, agree_ephemeral, EphemeralPrivateKey, X25519};
+use ring::hkdf::{Prk, Salt, HKDF_SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{self, Ed25519KeyPair, KeyPair, ED25519};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use crate::hex;
+use crate::network::{Message, MAX_FRAME_SIZE};
+use crate::wire::Encoding;
+
+/// The name of the handshake protocol, mixed into the handshake transcript so keys
+/// derived for it cannot be confused with keys of any other protocol
+const PROTOCOL_NAME: &[u8] = b"blockchain-network/X25519/Ed25519/ChaCha20-Poly1305/SHA256";
+
+/// The length of an X25519 public key, the only frame accepted before a peer is authenticated
+const EPHEMERAL_KEY_LEN: usize = 32;
+
+/// The length of the authentication tag appended to every encrypted frame
+const TAG_LEN: usize = 16;
+
+/// The bit of the handshake encoding flags announcing support for `Encoding::Json`
+const JSON_ENCODING: u8 = 1;
+
+/// The bit of the handshake encoding flags announcing support for `Encoding::Binary`
+const BINARY_ENCODING: u8 = 2;
+
+static IDENTITY: OnceLock<Ed25519KeyPair> = OnceLock::new();
+
+static JSON_WIRE: AtomicBool = AtomicBool::new(false);
+
+/// Makes this process only offer `Encoding::Json` in handshakes, so the traffic of its
+/// connections can be read while debugging
+pub fn set_json_wire(enabled: bool) {
+    JSON_WIRE.store(enabled, Ordering::Relaxed);
+}
+
+/// Sets the Ed25519 identity key this process authenticates itself with to its peers
+///
+/// # Returns
+/// - `Result<(), &str>` - `Ok(())`, or an `Err(&str)` if an identity is already in use
+pub fn set_identity(key_pair: Ed25519KeyPair) -> Result<(), &'static str> {
+    IDENTITY.set(key_pair).map_err(|_| "Node identity is already set")
+}
+
+/// Gets the Ed25519 identity key of this process, generating a random one if none was set
+pub fn identity() -> &'static Ed25519KeyPair {
+    IDENTITY.get_or_init(|| {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .expect("Failed to generate node identity");
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).expect("Generated node identity is valid")
+    })
+}
+
+/// Writes a single frame to `socket`: its length as a big-endian `u32`, followed by `data`
+pub async fn write_frame<W: AsyncWrite + Unpin>(socket: &mut W, data: &[u8]) -> Result<(), Error> {
+    if data.len() > MAX_FRAME_SIZE {
+        return Err(Error::new(ErrorKind::InvalidInput, "Message exceeds the maximum frame size"));
+    }
+    socket.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    socket.write_all(data).await
+}
+
+/// Reads a single frame from `socket`
+///
+/// # Returns
+/// - `Result<Vec<u8>, Error>` - The contents of the frame, or an `Error` of kind
+///   `ErrorKind::InvalidData` if the frame is larger than `MAX_FRAME_SIZE`
+pub async fn read_frame<R: AsyncRead + Unpin>(socket: &mut R) -> Result<Vec<u8>, Error> {
+    let mut length = [0; 4];
+    socket.read_exact(&mut length).await?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_FRAME_SIZE {
+        return Err(Error::new(ErrorKind::InvalidData, "Frame exceeds the maximum frame size"));
+    }
+
+    let mut data = vec![0; length];
+    socket.read_exact(&mut data).await?;
+    Ok(data)
+}
+
+/// Reads the ephemeral X25519 public key a peer opens the handshake with, refusing any other
+/// frame before it is allocated, as the peer is not authenticated yet
+async fn read_ephemeral_key<R: AsyncRead + Unpin>(socket: &mut R) -> Result<Vec<u8>, Error> {
+    let mut length = [0; 4];
+    socket.read_exact(&mut length).await?;
+    if u32::from_be_bytes(length) as usize != EPHEMERAL_KEY_LEN {
+        return Err(handshake_error());
+    }
+
+    let mut key = vec![0; EPHEMERAL_KEY_LEN];
+    socket.read_exact(&mut key).await?;
+    Ok(key)
+}
+
+fn handshake_error() -> Error {
+    Error::new(ErrorKind::PermissionDenied, "Handshake failed")
+}
+
+/// An encrypted and authenticated connection to a peer
+///
+/// Both sides exchange ephemeral X25519 keys, derive a ChaCha20-Poly1305 key for each
+/// direction from the shared secret with HKDF, and then prove their Ed25519 identity by
+/// signing the handshake transcript, announcing the `Encoding`s they support at the same time.
+/// Every later frame is encrypted with a counter nonce, so tampered, replayed or reordered
+/// frames fail to decrypt
+pub struct SecureChannel {
+    sender: ChannelSender,
+    receiver: ChannelReceiver,
+    remote_identity: Vec<u8>,
+}
+impl SecureChannel {
+    /// Opens a `SecureChannel` to a peer, authenticating with the identity of this process
+    ///
+    /// # Parameters
+    /// - `target` - The `address:port` of the peer
+    /// - `expected_identity` - The hex encoded Ed25519 public key the peer must authenticate
+    ///   with, if it is known from the `AddressBook`, the seeds or the `ChainSpec`
+    ///
+    /// # Returns
+    /// - `Result<SecureChannel, Error>` - The `SecureChannel`, or an `Error` of kind
+    ///   `ErrorKind::PermissionDenied` if the peer failed to authenticate as `expected_identity`
+    pub async fn connect(target: &str, expected_identity: Option<&str>) -> Result<Self, Error> {
+        let socket = TcpStream::connect(target).await?;
+        let channel = Self::handshake(socket, identity(), true).await?;
+        if expected_identity.is_some_and(|expected| !expected.eq_ignore_ascii_case(&channel.remote_identity())) {
+            return Err(handshake_error());
+        }
+        Ok(channel)
+    }
+
+    /// Accepts a `SecureChannel` from a peer which connected to this process
+    pub async fn accept(socket: TcpStream) -> Result<Self, Error> {
+        Self::handshake(socket, identity(), false).await
+    }
+
+    /// Performs the handshake establishing a `SecureChannel` over `socket`
+    ///
+    /// # Parameters
+    /// - `socket` - The connection to the peer
+    /// - `identity` - The Ed25519 identity key this side authenticates with
+    /// - `initiator` - Whether this side opened the connection
+    ///
+    /// # Returns
+    /// - `Result<SecureChannel, Error>` - The `SecureChannel`, or an `Error` of kind
+    ///   `ErrorKind::PermissionDenied` if the peer failed to authenticate
+    pub async fn handshake(mut socket: TcpStream, identity: &Ed25519KeyPair, initiator: bool)
+                           -> Result<Self, Error> {
+        let ephemeral = EphemeralPrivateKey::generate(&X25519, &SystemRandom::new())
+            .map_err(|_| handshake_error())?;
+        let local_ephemeral = ephemeral.compute_public_key().map_err(|_| handshake_error())?;
+        write_frame(&mut socket, local_ephemeral.as_ref()).await?;
+        let remote_ephemeral = read_ephemeral_key(&mut socket).await?;
+
+        let (initiator_ephemeral, responder_ephemeral) = if initiator {
+            (local_ephemeral.as_ref(), remote_ephemeral.as_slice())
+        } else {
+            (remote_ephemeral.as_slice(), local_ephemeral.as_ref())
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(PROTOCOL_NAME);
+        hasher.update(initiator_ephemeral);
+        hasher.update(responder_ephemeral);
+        let transcript = hasher.finalize();
+
+        let (initiator_key, responder_key) = agree_ephemeral(
+            ephemeral,
+            &agreement::UnparsedPublicKey::new(&X25519, &remote_ephemeral),
+            |shared_secret| {
+                let prk = Salt::new(HKDF_SHA256, &transcript).extract(shared_secret);
+                (derive_key(&prk, b"initiator"), derive_key(&prk, b"responder"))
+            },
+        ).map_err(|_| handshake_error())?;
+        let (sealing_key, opening_key, local_role, remote_role): (_, _, &[u8], &[u8]) = if initiator {
+            (initiator_key, responder_key, b"initiator", b"responder")
+        } else {
+            (responder_key, initiator_key, b"responder", b"initiator")
+        };
+
+        let (reader, writer) = socket.into_split();
+        let mut channel = SecureChannel {
+            sender: ChannelSender { writer, sealing_key, sent: 0, encoding: Encoding::Json },
+            receiver: ChannelReceiver { reader, opening_key, received: 0, encoding: Encoding::Json },
+            remote_identity: Vec::new(),
+        };
+
+        let local_signature = identity.sign(&[transcript.as_slice(), local_role].concat());
+        let local_encodings = if JSON_WIRE.load(Ordering::Relaxed) {
+            JSON_ENCODING
+        } else {
+            JSON_ENCODING | BINARY_ENCODING
+        };
+        channel.send_frame([identity.public_key().as_ref(), local_signature.as_ref(), &[local_encodings]].concat()).await?;
+
+        let authentication = channel.receive_frame().await?;
+        let remote_encodings = match authentication.len() {
+            96 => JSON_ENCODING,
+            97 => authentication[96],
+            _ => return Err(handshake_error()),
+        };
+        let (remote_identity, remote_signature) = authentication[..96].split_at(32);
+        signature::UnparsedPublicKey::new(&ED25519, remote_identity)
+            .verify(&[transcript.as_slice(), remote_role].concat(), remote_signature)
+            .map_err(|_| handshake_error())?;
+        channel.remote_identity = remote_identity.to_vec();
+        if local_encodings & remote_encodings & BINARY_ENCODING != 0 {
+            channel.sender.encoding = Encoding::Binary;
+            channel.receiver.encoding = Encoding::Binary;
+        }
+
+        Ok(channel)
+    }
+
+    /// The hex encoded Ed25519 public key the peer authenticated with
+    pub fn remote_identity(&self) -> String {
+        hex::encode(&self.remote_identity)
+    }
+
+    /// The `Encoding` of the `Message`s sent over this `SecureChannel`
+    pub fn encoding(&self) -> Encoding {
+        self.sender.encoding
+    }
+
+    /// Splits this `SecureChannel` into its sending and receiving halves, so a
+    /// `Message` can be sent while another task waits to receive one
+    pub fn split(self) -> (ChannelSender, ChannelReceiver) {
+        (self.sender, self.receiver)
+    }
+
+    /// Encrypts and sends a single frame
+    pub async fn send_frame(&mut self, data: Vec<u8>) -> Result<(), Error> {
+        self.sender.send_frame(data).await
+    }
+
+    /// Receives and decrypts a single frame
+    ///
+    /// # Returns
+    /// - `Result<Vec<u8>, Error>` - The decrypted frame, or an `Error` of kind
+    ///   `ErrorKind::InvalidData` if the frame is too large, or `ErrorKind::PermissionDenied`
+    ///   if it was not sent by the peer of this `SecureChannel`
+    pub async fn receive_frame(&mut self) -> Result<Vec<u8>, Error> {
+        self.receiver.receive_frame().await
+    }
+
+    /// Sends a `Message` in the negotiated `Encoding`
+    pub async fn send(&mut self, message: &Message) -> Result<(), Error> {
+        self.sender.send(message).await
+    }
+
+    /// Receives a single `Message`
+    ///
+    /// # Returns
+    /// - `Result<Message, Error>` - The `Message`, or an `Error` if the connection was closed,
+    ///   a frame failed authentication or the frame is not a valid `Message`
+    pub async fn receive(&mut self) -> Result<Message, Error> {
+        self.receiver.receive().await
+    }
+}
+
+/// The sending half of a `SecureChannel`
+pub struct ChannelSender {
+    writer: OwnedWriteHalf,
+    sealing_key: LessSafeKey,
+    sent: u64,
+    encoding: Encoding,
+}
+impl ChannelSender {
+    /// Encrypts and sends a single frame
+    pub async fn send_frame(&mut self, mut data: Vec<u8>) -> Result<(), Error> {
+        if data.len() + TAG_LEN > MAX_FRAME_SIZE {
+            return Err(Error::new(ErrorKind::InvalidInput, "Message exceeds the maximum frame size"));
+        }
+        self.sealing_key
+            .seal_in_place_append_tag(counter_nonce(self.sent), Aad::empty(), &mut data)
+            .map_err(|_| Error::other("Failed to encrypt frame"))?;
+        self.sent += 1;
+        write_frame(&mut self.writer, &data).await
+    }
+
+    /// Sends a `Message` in the negotiated `Encoding`
+    pub async fn send(&mut self, message: &Message) -> Result<(), Error> {
+        let data = self.encoding.encode(message).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        self.send_frame(data).await
+    }
+}
+
+/// The receiving half of a `SecureChannel`
+pub struct ChannelReceiver {
+    reader: OwnedReadHalf,
+    opening_key: LessSafeKey,
+    received: u64,
+    encoding: Encoding,
+}
+impl ChannelReceiver {
+    /// Receives and decrypts a single frame
+    pub async fn receive_frame(&mut self) -> Result<Vec<u8>, Error> {
+        let mut data = read_frame(&mut self.reader).await?;
+        let length = self.opening_key
+            .open_in_place(counter_nonce(self.received), Aad::empty(), &mut data)
+            .map_err(|_| Error::new(ErrorKind::PermissionDenied, "Frame failed authentication"))?
+            .len();
+        self.received += 1;
+        data.truncate(length);
+        Ok(data)
+    }
+
+    /// Receives a single `Message`
+    pub async fn receive(&mut self) -> Result<Message, Error> {
+        let data = self.receive_frame().await?;
+        self.encoding.decode(&data).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+/// Derives the ChaCha20-Poly1305 key for one direction of a `SecureChannel`
+fn derive_key(prk: &Prk, role: &[u8]) -> LessSafeKey {
+    let info = [role];
+    let okm = prk.expand(&info, &CHACHA20_POLY1305).expect("Key length is valid for HKDF");
+    LessSafeKey::new(UnboundKey::from(okm))
+}
+
+/// Builds the nonce of the frame with the given sequence number
+fn counter_nonce(counter: u64) -> Nonce {
+    let mut nonce = [0; NONCE_LEN];
+    nonce[NONCE_LEN - 8..].copy_from_slice(&counter.to_be_bytes());
+    Nonce::assume_unique_for_key(nonce)
+}
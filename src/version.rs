@@ -0,0 +1,146 @@
+use std::io::{Error, ErrorKind};
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+use crate::chain_spec::ChainSpec;
+use crate::network::Message;
+use crate::transport::SecureChannel;
+
+/// The version of the peer-to-peer protocol spoken by this software
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The oldest protocol version this software can talk to
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// The peer stores the full `Blockchain` and answers `GetHeaders` and `GetBlocks`
+pub const NODE_NETWORK: u64 = 1;
+
+/// The peer reconstructs `CompactBlock`s, so new `Block`s can be announced to it compactly
+pub const NODE_COMPACT_BLOCKS: u64 = 1 << 1;
+
+/// The peer answers `GetAddr` requests
+pub const NODE_ADDRESS_EXCHANGE: u64 = 1 << 2;
+
+/// The peer answers `GetTransactions` and `GetMerkleProof` requests for light clients
+pub const NODE_MERKLE_PROOFS: u64 = 1 << 3;
+
+/// The capabilities of a full node running this software
+pub const FULL_NODE_CAPABILITIES: u64 = NODE_NETWORK | NODE_COMPACT_BLOCKS | NODE_ADDRESS_EXCHANGE | NODE_MERKLE_PROOFS;
+
+static NETWORK_ID: OnceLock<String> = OnceLock::new();
+
+/// Sets the id of the network this process connects to, as found in its `ChainSpec`
+///
+/// # Returns
+/// - `Result<(), &str>` - `Ok(())`, or an `Err(&str)` if a network id is already in use
+pub fn set_network_id(network_id: String) -> Result<(), &'static str> {
+    NETWORK_ID.set(network_id).map_err(|_| "Network id is already set")
+}
+
+/// Gets the id of the network this process connects to, the default `ChainSpec`'s if none was set
+pub fn network_id() -> &'static str {
+    NETWORK_ID.get_or_init(|| ChainSpec::default().network_id)
+}
+
+/// The `Version` exchanged by both sides at the start of every connection
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Version {
+    /// The version of the peer-to-peer protocol spoken by the peer
+    pub protocol_version: u32,
+
+    /// The version of the software the peer runs
+    pub software_version: String,
+
+    /// The index of the latest `Block` of the peer's `Blockchain`
+    pub best_height: u64,
+
+    /// The id of the network the peer belongs to
+    pub network_id: String,
+
+    /// The capability flags of the peer, such as `NODE_NETWORK`
+    pub capabilities: u64,
+}
+impl Version {
+    /// Constructs the `Version` of this software
+    ///
+    /// # Parameters
+    /// - `network_id` - The id of the network
+    /// - `best_height` - The index of the latest `Block`
+    /// - `capabilities` - The capability flags
+    pub fn new(network_id: &str, best_height: u64, capabilities: u64) -> Self {
+        Version {
+            protocol_version: PROTOCOL_VERSION,
+            software_version: format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            best_height,
+            network_id: network_id.to_string(),
+            capabilities,
+        }
+    }
+
+    /// Constructs the `Version` of a process without a `Blockchain`, such as a miner or a pool
+    pub fn client() -> Self {
+        Version::new(network_id(), 0, 0)
+    }
+
+    /// Checks whether this `Version`, received from a peer, is compatible with the local one
+    ///
+    /// # Returns
+    /// - `Result<(), &str>` - `Ok(())` if the peer can be talked to, or an `Err(&str)` otherwise
+    pub fn check(&self, local: &Version) -> Result<(), &'static str> {
+        if self.protocol_version < MIN_PROTOCOL_VERSION {
+            return Err("Peer protocol version is too old");
+        }
+        if self.network_id != local.network_id {
+            return Err("Peer belongs to a different network");
+        }
+        Ok(())
+    }
+
+    /// Checks whether the peer announced the given capability flags
+    pub fn supports(&self, capabilities: u64) -> bool {
+        self.capabilities & capabilities == capabilities
+    }
+}
+
+fn refused(reason: &str) -> Error {
+    Error::new(ErrorKind::ConnectionRefused, reason.to_string())
+}
+
+/// Performs the version handshake of an outgoing connection: sends the local `Version`
+/// and checks the `Version` the peer replies with
+///
+/// # Returns
+/// - `Result<Version, Error>` - The `Version` of the peer, or an `Error` of kind
+///   `ErrorKind::ConnectionRefused` if either side found the other incompatible
+pub async fn initiate_version(channel: &mut SecureChannel, local: &Version) -> Result<Version, Error> {
+    channel.send(&Message::Version(local.clone())).await?;
+    match channel.receive().await? {
+        Message::Version(remote) => {
+            remote.check(local).map_err(refused)?;
+            Ok(remote)
+        }
+        Message::Reject(reason) => Err(refused(&reason)),
+        _ => Err(Error::new(ErrorKind::InvalidData, "Expected a version")),
+    }
+}
+
+/// Performs the version handshake of an incoming connection: checks the `Version` of the
+/// peer and replies with the local `Version`, or with a `Reject` if they are incompatible
+///
+/// # Returns
+/// - `Result<Version, Error>` - The `Version` of the peer, or an `Error` of kind
+///   `ErrorKind::ConnectionRefused` if the peer is incompatible
+pub async fn accept_version(channel: &mut SecureChannel, local: &Version) -> Result<Version, Error> {
+    let remote = match channel.receive().await? {
+        Message::Version(remote) => remote,
+        _ => {
+            let _ = channel.send(&Message::Reject(String::from("Expected a version"))).await;
+            return Err(Error::new(ErrorKind::InvalidData, "Expected a version"));
+        }
+    };
+    if let Err(reason) = remote.check(local) {
+        let _ = channel.send(&Message::Reject(reason.to_string())).await;
+        return Err(refused(reason));
+    }
+    channel.send(&Message::Version(local.clone())).await?;
+    Ok(remote)
+}
@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of values on the stack of a running contract
+pub const MAX_VM_STACK: usize = 1024;
+
+/// A single instruction of a contract's bytecode
+///
+/// The VM is a deterministic stack machine over `i64` words. Every instruction costs gas,
+/// so every execution terminates once its gas limit is used up
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Instruction {
+    /// Pushes a constant
+    Push(i64),
+
+    /// Removes the top value
+    Pop,
+
+    /// Duplicates the top value
+    Dup,
+
+    /// Swaps the two top values
+    Swap,
+
+    /// Pops `b` and `a` and pushes `a + b`
+    Add,
+
+    /// Pops `b` and `a` and pushes `a - b`
+    Sub,
+
+    /// Pops `b` and `a` and pushes `a * b`
+    Mul,
+
+    /// Pops `b` and `a` and pushes `a / b`
+    Div,
+
+    /// Pops `b` and `a` and pushes `a % b`
+    Mod,
+
+    /// Pops `b` and `a` and pushes `1` if `a == b`, `0` otherwise
+    Eq,
+
+    /// Pops `b` and `a` and pushes `1` if `a < b`, `0` otherwise
+    Lt,
+
+    /// Pops `b` and `a` and pushes `1` if `a > b`, `0` otherwise
+    Gt,
+
+    /// Pops a value and pushes `1` if it is `0`, `0` otherwise
+    Not,
+
+    /// Continues at the given instruction
+    Jump(usize),
+
+    /// Pops a value and continues at the given instruction unless it is `0`
+    JumpIf(usize),
+
+    /// Pushes the argument of the call at the given position, or `0` if there are fewer
+    Input(usize),
+
+    /// Pops a key and pushes the value the contract stored under it, or `0` if there is none
+    Load,
+
+    /// Pops a value and a key and stores the value under the key
+    Store,
+
+    /// Stops successfully, returning the top value if there is one
+    Return,
+
+    /// Stops and discards every change made to the contract's storage
+    Revert,
+}
+impl Instruction {
+    /// The gas charged for executing this `Instruction`
+    pub fn gas_cost(&self) -> u64 {
+        match self {
+            Instruction::Mul | Instruction::Div | Instruction::Mod => 3,
+            Instruction::Load => 20,
+            Instruction::Store => 100,
+            _ => 1,
+        }
+    }
+}
+
+/// The outcome of running a contract
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Execution {
+    /// The gas used, at most the gas limit of the call
+    pub gas_used: u64,
+
+    /// The value returned by the contract, or the reason it failed
+    pub result: Result<Option<i64>, String>,
+}
+
+fn binary(stack: &mut Vec<i64>, operation: impl Fn(i64, i64) -> Option<i64>) -> Result<(), &'static str> {
+    let b = stack.pop().ok_or("Stack underflow")?;
+    let a = stack.pop().ok_or("Stack underflow")?;
+    stack.push(operation(a, b).ok_or("Arithmetic error")?);
+    Ok(())
+}
+
+/// Runs contract bytecode
+///
+/// The `storage` is only modified if the contract returns successfully, so a failed call
+/// leaves no trace but its gas cost
+///
+/// # Parameters
+/// - `code` - The bytecode of the contract
+/// - `storage` - The persistent storage of the contract
+/// - `input` - The arguments of the call
+/// - `gas_limit` - The maximum amount of gas the call may use
+///
+/// # Returns
+/// - `Execution` - The gas used and the value returned by the contract, or why it failed
+pub fn execute(code: &[Instruction], storage: &mut BTreeMap<i64, i64>, input: &[i64], gas_limit: u64) -> Execution {
+    let mut changes = storage.clone();
+    let mut stack: Vec<i64> = Vec::new();
+    let mut gas_used = 0;
+    let mut position = 0;
+
+    let result = loop {
+        let Some(instruction) = code.get(position) else {
+            break Ok(None);
+        };
+        gas_used += instruction.gas_cost();
+        if gas_used > gas_limit {
+            gas_used = gas_limit;
+            break Err("Out of gas");
+        }
+        position += 1;
+
+        let step = match instruction {
+            Instruction::Push(value) => {
+                stack.push(*value);
+                Ok(())
+            }
+            Instruction::Pop => stack.pop().map(|_| ()).ok_or("Stack underflow"),
+            Instruction::Dup => match stack.last() {
+                Some(top) => {
+                    stack.push(*top);
+                    Ok(())
+                }
+                None => Err("Stack underflow"),
+            },
+            Instruction::Swap => match stack.len() {
+                length if length >= 2 => {
+                    stack.swap(length - 1, length - 2);
+                    Ok(())
+                }
+                _ => Err("Stack underflow"),
+            },
+            Instruction::Add => binary(&mut stack, i64::checked_add),
+            Instruction::Sub => binary(&mut stack, i64::checked_sub),
+            Instruction::Mul => binary(&mut stack, i64::checked_mul),
+            Instruction::Div => binary(&mut stack, i64::checked_div),
+            Instruction::Mod => binary(&mut stack, i64::checked_rem),
+            Instruction::Eq => binary(&mut stack, |a, b| Some((a == b) as i64)),
+            Instruction::Lt => binary(&mut stack, |a, b| Some((a < b) as i64)),
+            Instruction::Gt => binary(&mut stack, |a, b| Some((a > b) as i64)),
+            Instruction::Not => match stack.pop() {
+                Some(value) => {
+                    stack.push((value == 0) as i64);
+                    Ok(())
+                }
+                None => Err("Stack underflow"),
+            },
+            Instruction::Jump(target) => {
+                position = *target;
+                Ok(())
+            }
+            Instruction::JumpIf(target) => match stack.pop() {
+                Some(condition) => {
+                    if condition != 0 {
+                        position = *target;
+                    }
+                    Ok(())
+                }
+                None => Err("Stack underflow"),
+            },
+            Instruction::Input(index) => {
+                stack.push(input.get(*index).copied().unwrap_or(0));
+                Ok(())
+            }
+            Instruction::Load => match stack.pop() {
+                Some(key) => {
+                    stack.push(changes.get(&key).copied().unwrap_or(0));
+                    Ok(())
+                }
+                None => Err("Stack underflow"),
+            },
+            Instruction::Store => match (stack.pop(), stack.pop()) {
+                (Some(value), Some(key)) => {
+                    changes.insert(key, value);
+                    Ok(())
+                }
+                _ => Err("Stack underflow"),
+            },
+            Instruction::Return => break Ok(stack.last().copied()),
+            Instruction::Revert => Err("Reverted"),
+        };
+        if let Err(e) = step {
+            break Err(e);
+        }
+        if stack.len() > MAX_VM_STACK {
+            break Err("Stack overflow");
+        }
+    };
+
+    if result.is_ok() {
+        *storage = changes;
+    }
+    Execution { gas_used, result: result.map_err(String::from) }
+}
@@ -0,0 +1,109 @@
+use std::io::{Error, ErrorKind};
+use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use crate::network::MAX_FRAME_SIZE;
+
+/// The GUID appended to a client's key to compute the `Sec-WebSocket-Accept` header
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The opcode of a frame holding UTF-8 text
+pub const OPCODE_TEXT: u8 = 0x1;
+
+/// The opcode of a frame closing the connection
+pub const OPCODE_CLOSE: u8 = 0x8;
+
+/// The opcode of a ping frame, which must be answered with a pong
+pub const OPCODE_PING: u8 = 0x9;
+
+/// The opcode of a pong frame
+pub const OPCODE_PONG: u8 = 0xA;
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as padded base64
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| group | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Computes the `Sec-WebSocket-Accept` header answering a client's `Sec-WebSocket-Key`
+pub fn accept_key(key: &str) -> String {
+    base64_encode(digest(&SHA1_FOR_LEGACY_USE_ONLY, format!("{}{}", key.trim(), WEBSOCKET_GUID).as_bytes()).as_ref())
+}
+
+/// Writes a single unmasked WebSocket frame, as sent by a server
+///
+/// # Parameters
+/// - `socket` - The connection to the client
+/// - `opcode` - The opcode of the frame, such as `OPCODE_TEXT`
+/// - `payload` - The contents of the frame
+pub async fn write_frame<W: AsyncWrite + Unpin>(socket: &mut W, opcode: u8, payload: &[u8]) -> Result<(), Error> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    socket.write_all(&frame).await
+}
+
+/// Reads a single masked WebSocket frame, as sent by a client
+///
+/// Fragmented messages are not supported, since clients only send short requests
+///
+/// # Returns
+/// - `Result<(u8, Vec<u8>), Error>` - The opcode and unmasked payload of the frame, or an
+///   `Error` if the frame is fragmented, unmasked or larger than `MAX_FRAME_SIZE`
+pub async fn read_frame<R: AsyncRead + Unpin>(socket: &mut R) -> Result<(u8, Vec<u8>), Error> {
+    let mut header = [0; 2];
+    socket.read_exact(&mut header).await?;
+    if header[0] & 0x80 == 0 {
+        return Err(Error::new(ErrorKind::InvalidData, "Fragmented frames are not supported"));
+    }
+    if header[1] & 0x80 == 0 {
+        return Err(Error::new(ErrorKind::InvalidData, "Client frames must be masked"));
+    }
+
+    let length = match header[1] & 0x7f {
+        126 => {
+            let mut length = [0; 2];
+            socket.read_exact(&mut length).await?;
+            u16::from_be_bytes(length) as usize
+        }
+        127 => {
+            let mut length = [0; 8];
+            socket.read_exact(&mut length).await?;
+            u64::from_be_bytes(length) as usize
+        }
+        length => length as usize,
+    };
+    if length > MAX_FRAME_SIZE {
+        return Err(Error::new(ErrorKind::InvalidData, "Frame exceeds the maximum frame size"));
+    }
+
+    let mut mask = [0; 4];
+    socket.read_exact(&mut mask).await?;
+    let mut payload = vec![0; length];
+    socket.read_exact(&mut payload).await?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok((header[0] & 0x0f, payload))
+}